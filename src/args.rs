@@ -1,7 +1,37 @@
-use rand::Rng;
-use std::collections::HashMap;
+use rand::{Rng, SeedableRng, prelude::StdRng};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug)]
+use crate::{
+    arquetipo::{Arquetipo, elegir_de_mezcla, parse_mezcla},
+    clima::{Clima, ConfiguracionClima, HORIZONTE_CLIMA_MS, generar, parse_clima},
+    config::Configuracion,
+    espectaculo::ConfiguracionEspectaculo,
+    salida::Cansancio,
+    juego::{ConfiguracionJuego, INTENSIDAD_MAXIMA, PROBABILIDAD_DE_DESPERFECTOS, TIEMPO_MAXIMO_ARREGLO_DESPERFECTO},
+    mapa::{Camino, Mapa, Puesto, Zona, caminos_uniformes},
+    persona::ConfiguracionPersona,
+    restricciones::{DistribucionAtributos, Restricciones},
+};
+
+/// Cantidad de juegos si no se especifica ningún parámetro de juegos.
+const CANTIDAD_JUEGOS_POR_DEFECTO: usize = 5;
+const COSTO_JUEGO_POR_DEFECTO: u32 = 10;
+const CAPACIDAD_JUEGO_POR_DEFECTO: u32 = 2;
+const DURACION_JUEGO_POR_DEFECTO: u32 = 25;
+/// Costo y capacidad por defecto cuando de los juegos sólo se indica la
+/// duración, como en la versión original.
+const COSTO_Y_CAPACIDAD_SOLO_DURACION: u32 = 25;
+const UNIDADES_JUEGO_POR_DEFECTO: u32 = 1;
+/// Por defecto las personas suben y bajan de los juegos instantáneamente.
+const CARGA_JUEGO_POR_DEFECTO: u32 = 0;
+const DESCARGA_JUEGO_POR_DEFECTO: u32 = 0;
+const INTENSIDAD_JUEGO_POR_DEFECTO: u32 = 3;
+const DESCANSO_POR_DEFECTO: u32 = 100;
+const PROBABILIDAD_IRSE_CANSADO_POR_DEFECTO: f64 = 0.3;
+const ANTICIPACION_ESPECTACULO_POR_DEFECTO: u32 = 300;
+const INTERES_ESPECTACULO_POR_DEFECTO: f64 = 0.5;
+
+#[derive(Debug, Clone)]
 pub struct Args {
     /// Cantidad de personas que puede haber dentro del parque
     /// simultáneamente.
@@ -10,6 +40,55 @@ pub struct Args {
     /// Presupuesto de cada una de las personas que ingresará al
     /// parque.
     pub presupuesto_personas: Vec<u32>,
+    /// Nombre de cada una de las personas. Un nombre vacío deja a la
+    /// persona sin nombre.
+    pub nombres_personas: Option<Vec<String>>,
+    /// Máxima cantidad de personas en la fila de entrada con la que
+    /// cada persona está dispuesta a esperar. Un valor vacío indica que
+    /// la persona espera siempre.
+    pub tolerancia_personas: Option<Vec<Option<u32>>>,
+    /// Cantidad de integrantes de cada grupo de visitantes. Los grupos
+    /// se forman con las primeras personas, en orden; el resto va sola.
+    pub grupos: Option<Vec<u32>>,
+    /// Si los grupos juntan el presupuesto de sus integrantes.
+    pub presupuesto_compartido: bool,
+    /// Tiempo promedio, en milisegundos, entre la llegada de dos
+    /// personas al parque. Si no se especifica llegan todas juntas.
+    pub intervalo_llegadas: Option<u32>,
+    /// Distribución de las edades, alturas y movilidad de las personas.
+    pub atributos_personas: DistribucionAtributos,
+    /// Arquetipos de visitantes disponibles: los predefinidos más los
+    /// del archivo de configuración.
+    pub arquetipos: Vec<Arquetipo>,
+    /// Peso de cada arquetipo en la población. Sin mezcla las personas
+    /// no tienen arquetipo.
+    pub mezcla_visitantes: Option<Vec<(String, u32)>>,
+    /// Tiempo máximo que se queda cada persona, en milisegundos. Si el
+    /// arquetipo tiene uno menor se usa ese.
+    pub estadia_maxima: Option<u32>,
+    /// Suma de intensidades de los juegos a partir de la cual una
+    /// persona está cansada. Si no se especifica nadie se cansa.
+    pub cansancio_maximo: Option<u32>,
+    /// Duración de cada descanso, en milisegundos.
+    pub descanso: u32,
+    /// Probabilidad de irse en lugar de descansar al estar cansada.
+    pub probabilidad_irse_cansado: f64,
+    /// Satisfacción debajo de la cual una persona se va.
+    pub satisfaccion_minima: Option<f64>,
+    /// Momento en que cierra el parque, en milisegundos desde el inicio.
+    pub cierre: Option<u32>,
+    /// Tiempo, en milisegundos, que se camina entre dos lugares del
+    /// parque sin un camino más corto. Sin caminata ni caminos las
+    /// personas van de un juego a otro sin demora.
+    pub caminata: Option<u32>,
+    /// Zonas, puestos de comida y caminos del archivo de configuración.
+    pub zonas: Vec<Zona>,
+    pub puestos: Vec<Puesto>,
+    pub caminos: Vec<Camino>,
+    /// Espectáculos del archivo de configuración.
+    pub espectaculos: Vec<ConfiguracionEspectaculo>,
+    /// Clima a lo largo del día. Sin pronóstico siempre está soleado.
+    pub clima: Option<ConfiguracionClima>,
     
     /// Costo de cada uno de los juegos
     pub costo_juegos: Option<Vec<u32>>,
@@ -18,15 +97,66 @@ pub struct Args {
     pub capacidad_juegos: Option<Vec<u32>>,
     /// Duración, en milisegundos, de la vuelta de cada juego.
     pub duracion_juegos: Option<Vec<u32>>,
+    /// Cantidad de unidades idénticas de cada juego, que cargan
+    /// personas de una misma fila.
+    pub unidades_juegos: Option<Vec<u32>>,
+    /// Tiempo, en milisegundos, que tarda en subir cada persona a cada
+    /// juego.
+    pub carga_juegos: Option<Vec<u32>>,
+    /// Tiempo, en milisegundos, que tarda en bajar cada persona de cada
+    /// juego.
+    pub descarga_juegos: Option<Vec<u32>>,
+    /// Probabilidad de desperfecto antes de cada vuelta de cada juego.
+    pub probabilidad_desperfecto_juegos: Option<Vec<f64>>,
+    /// Tiempo máximo, en milisegundos, de arreglo de cada juego.
+    pub tiempo_arreglo_juegos: Option<Vec<u32>>,
+    /// Nombre de cada juego. Un nombre vacío deja al juego sin nombre.
+    pub nombres_juegos: Option<Vec<String>>,
+    /// Dejar que las personas de la fila de un juego suban en cualquier
+    /// orden en lugar de en orden de llegada.
+    pub filas_injustas: bool,
+    /// Si los juegos devuelven la entrada a quienes esperaron en la fila
+    /// durante un desperfecto.
+    pub reembolsar_desperfectos: bool,
+    /// Si cada juego tiene una fila individual, que sólo ocupa los
+    /// lugares que deja libres la fila principal.
+    pub fila_individual_juegos: Option<Vec<bool>>,
+    /// Agregar una fila individual a todos los juegos.
+    pub filas_individuales: bool,
+    /// Altura mínima y máxima, en centímetros, y edad mínima para subir
+    /// a cada juego. Un valor vacío indica que no hay restricción.
+    pub altura_minima_juegos: Option<Vec<Option<u32>>>,
+    pub altura_maxima_juegos: Option<Vec<Option<u32>>>,
+    pub edad_minima_juegos: Option<Vec<Option<u32>>>,
+    /// Si pueden subir a cada juego personas con movilidad reducida.
+    pub accesible_juegos: Option<Vec<bool>>,
+    /// Intensidad de cada juego, de 1 a `INTENSIDAD_MAXIMA`.
+    pub intensidad_juegos: Option<Vec<u32>>,
+    /// Si cada juego está bajo techo y no cierra con tormenta.
+    pub cubierto_juegos: Option<Vec<bool>>,
+    /// Zona de cada juego, como índice en `zonas`.
+    pub zona_juegos: Option<Vec<Option<usize>>>,
+
+    /// Archivo de configuración del que se leyeron los valores
+    /// iniciales.
+    pub config: Option<String>,
 
     /// Imprimir salida a un archivo
     pub debug: bool,
+    /// Acelerar todos los tiempos de la simulación
+    pub rapido: bool,
     /// Semilla aleatoria
     pub semilla: u32,
+    /// Tiempo, en milisegundos, sin progreso en la simulación a partir
+    /// del cual se considera que hay un deadlock.
+    pub watchdog: Option<u32>,
+
+    /// Generador para las listas aleatorias, derivado de la semilla.
+    rng: StdRng,
 }
 
 pub enum ParseArgsResult {
-    Ok(Args),
+    Ok(Box<Args>),
     MostrarAyuda,
     Error(String)
 }
@@ -39,24 +169,68 @@ impl ParseArgsResult {
 
 type Parser = fn(&mut Args, &str) -> Result<(), String>;
 
-pub fn parse_args() -> ParseArgsResult {
+/// Interpreta los argumentos de línea de comandos `argv` (sin el
+/// nombre del ejecutable).
+///
+/// Primero se aplica la semilla, para que las listas aleatorias sean
+/// reproducibles, luego el archivo de configuración (si lo hay) y por
+/// último el resto de las opciones, que pisan los valores del archivo.
+pub fn parse_args_desde(argv: &[String]) -> ParseArgsResult {
     let mut args = Args::default();
     let parsers = Args::parsers();
 
-    for arg in std::env::args().skip(1) {
-        let val = arg.split('=').collect::<Vec<&str>>();
-        if val[0] == "-h" || val[0] == "--help" {
+    for arg in argv {
+        if let Some(semilla) = arg.strip_prefix("--semilla=") {
+            if let Err(e) = Args::parse_semilla(&mut args, semilla) {
+                return ParseArgsResult::error("--semilla", e);
+            }
+        }
+    }
+
+    for arg in argv {
+        if let Some(ruta) = arg.strip_prefix("--config=") {
+            let semilla_explicita = argv.iter().any(|a| a.starts_with("--semilla="));
+            if let Err(e) = args.aplicar_configuracion(ruta, semilla_explicita) {
+                return ParseArgsResult::error("--config", e);
+            }
+        }
+    }
+
+    for arg in argv {
+        // sólo el primer `=` separa el nombre del valor, que puede tener
+        // otros (por ejemplo en los nombres)
+        let (nombre, valor) = match arg.split_once('=') {
+            Some((nombre, valor)) => (nombre, Some(valor)),
+            None => (arg.as_str(), None),
+        };
+        if nombre == "-h" || nombre == "--help" {
             return ParseArgsResult::MostrarAyuda;
-        } else if val[0] == "-d" || val[0] == "--debug" {
+        } else if nombre == "-d" || nombre == "--debug" {
             args.debug = true;
             continue;
+        } else if nombre == "--rapido" {
+            args.rapido = true;
+            continue;
+        } else if nombre == "--filas-injustas" {
+            args.filas_injustas = true;
+            continue;
+        } else if nombre == "--filas-individuales" {
+            args.filas_individuales = true;
+            continue;
+        } else if nombre == "--reembolsar-desperfectos" {
+            args.reembolsar_desperfectos = true;
+            continue;
+        } else if nombre == "--presupuesto-compartido" {
+            args.presupuesto_compartido = true;
+            continue;
         }
 
-        if val.len() != 2 {
-            return ParseArgsResult::error(&arg, "Argumento inválido".into());
+        let (argname, Some(argvalue)) = (nombre, valor) else {
+            return ParseArgsResult::error(arg, "Argumento inválido".into());
+        };
+        if argname == "--semilla" || argname == "--config" {
+            continue;
         }
-
-        let (argname, argvalue) = (val[0], val[1]);
         match parsers.get(argname) {
             Some(parser) => {
                 match parser(&mut args, argvalue) {
@@ -64,63 +238,434 @@ pub fn parse_args() -> ParseArgsResult {
                     Err(e) => return ParseArgsResult::error(argname, e)
                 }
             },
-            None => return ParseArgsResult::error(&argname, "Argumento inválido".into())
+            None => return ParseArgsResult::error(argname, "Argumento inválido".into())
         };
     }
 
     if let Err(e) = args.resolver() {
         return ParseArgsResult::Error(e)
     }
-    ParseArgsResult::Ok(args)
+    ParseArgsResult::Ok(Box::new(args))
 }
 
 pub fn mostrar_ayuda() {
     let args: Vec<String> = std::env::args().collect();
-    eprint!("Uso: {} [--personas=<LISTA>] ", args[0]);
-    eprint!("[--costo-juegos=<LISTA>] [--capacidad-juegos=<LISTA>] [--duracion-juegos=<LISTA>] ");
-    eprintln!("[--capacidad=<N>] [-h|--help] [--semilla=<N>] [-d|--debug]\n");
+    eprint!("Uso: {} [--config=<ARCHIVO>] [--personas=<LISTA>] ", args[0]);
+    eprint!("[--nombres-personas=<NOMBRES>] [--tolerancia-personas=<LISTA>] [--grupos=<LISTA>] [--presupuesto-compartido] [--intervalo-llegadas=<N>] [--edades=<MIN>..<MAX>] [--movilidad-reducida=<X>] [--mezcla-visitantes=<MEZCLA>] [--estadia-maxima=<N>] [--cansancio-maximo=<N>] [--descanso=<N>] [--irse-cansado=<X>] [--satisfaccion-minima=<X>] [--costo-juegos=<LISTA>] [--capacidad-juegos=<LISTA>] ");
+    eprint!("[--duracion-juegos=<LISTA>] [--unidades-juegos=<LISTA>] [--carga-juegos=<LISTA>] [--descarga-juegos=<LISTA>] [--nombres-juegos=<NOMBRES>] [--filas-injustas] [--filas-individuales] [--reembolsar-desperfectos] ");
+    eprint!("[--altura-minima-juegos=<LISTA>] [--altura-maxima-juegos=<LISTA>] [--edad-minima-juegos=<LISTA>] [--accesibles-juegos=<LISTA>] [--intensidad-juegos=<LISTA>] [--cubiertos-juegos=<LISTA>] ");
+    eprintln!("[--capacidad=<N>] [--cierre=<N>] [--caminata=<N>] [--clima=<PRONÓSTICO>] [-h|--help] [--semilla=<N>] [-d|--debug] [--rapido] [--watchdog=<N>]");
+    eprintln!("     {} lote [--corridas=<N>] [--semillas=<DESDE>..<HASTA>] [--hilos=<N>] [OPCIONES]", args[0]);
+    eprintln!("     {} barrido --barrer <PARÁMETRO>=<VALORES> [--barrer ...] [--salida=<ARCHIVO>] [OPCIONES DE LOTE] [OPCIONES]", args[0]);
+    eprintln!("     {} optimizar [--metodo=<MÉTODO>] [--precios=<VALORES>] [--evaluaciones=<N>] [--peso-espera=<X>] [--peso-satisfaccion=<X>] [OPCIONES DE LOTE] [OPCIONES]", args[0]);
+    eprintln!("     {} teoria [--tolerancia=<X>] [--tiempo-real] [OPCIONES DE LOTE] [OPCIONES]", args[0]);
+    eprintln!("     {} planificar --espera-maxima=<MS> [--iteraciones=<N>] [--tiempo-real] [OPCIONES DE LOTE] [OPCIONES]", args[0]);
+    eprintln!("     {} temporada [--dias=<N>] [--regreso=<X>] [--mantenimiento=<N>] [OPCIONES]\n", args[0]);
     
     eprintln!("TIPOS DE OPCIONES: ");
     eprintln!("\t--opcion=<N>: Opción que acepta un número natural, por ejemplo, --opcion=23.");
     eprintln!("\t--opcion=<LISTA>: Opción que acepta una lista de números naturales.");
+    eprintln!("\t--opcion=<NOMBRES>: Opción que acepta una lista de nombres separados por coma, por ejemplo, --opcion=Carrusel,,Autitos.");
     eprintln!("\t--opcion: Opción que no acepta valores, su presencia activa una bandera.\n");
 
     eprintln!("OPCIONES: ");
-    eprintln!("\t --personas=<LISTA>: Presupuesto de cada pesona que ingresará al parque.\n");
+    eprintln!("\t --config=<ARCHIVO>: Leer el escenario de un archivo TOML o JSON. El resto de las opciones pisan sus valores.\n");
+    eprintln!("\t --personas=<LISTA>: Presupuesto de cada pesona que ingresará al parque.");
+    eprintln!("\t --nombres-personas=<NOMBRES>: Nombre de cada persona. Un nombre vacío deja a la persona sin nombre.");
+    eprintln!("\t --tolerancia-personas=<LISTA>: Máximo de personas en la fila de entrada con el que cada persona espera para entrar; si hay más se va.");
+    eprintln!("\t                                Acepta 0, y un valor vacío indica que la persona espera siempre (por defecto, todas esperan).");
+    eprintln!("\t --grupos=<LISTA>: Cantidad de integrantes de cada grupo de visitantes, formados con las primeras personas en orden.");
+    eprintln!("\t                    Los grupos entran, juegan y se van juntos (por defecto todas las personas van solas).");
+    eprintln!("\t --presupuesto-compartido: Los grupos juntan el presupuesto de sus integrantes (por defecto cada uno paga lo suyo).");
+    eprintln!("\t --intervalo-llegadas=N: Tiempo promedio en milisegundos entre llegadas de personas (por defecto llegan todas juntas).");
+    eprintln!("\t --edades=<MIN>..<MAX>: Rango de edades de las personas (por defecto 3..70); la altura se genera según la edad.");
+    eprintln!("\t --movilidad-reducida=X: Probabilidad de que una persona tenga movilidad reducida (por defecto 0.05).");
+    eprintln!("\t --mezcla-visitantes=<MEZCLA>: Peso de cada arquetipo de visitante, por ejemplo aventurero:30,familia:50,ahorrativo:10,casual:10.");
+    eprintln!("\t                               Sin mezcla las personas eligen los juegos al azar y no compran comida.");
+    eprintln!("\t --estadia-maxima=N: Tiempo máximo en milisegundos que se queda cada persona en el parque (por defecto, hasta quedarse sin dinero).");
+    eprintln!("\t --cansancio-maximo=N: Suma de las intensidades de los juegos a partir de la cual una persona está cansada (por defecto nadie se cansa).");
+    eprintln!("\t --descanso=N: Milisegundos que descansa una persona cansada antes de seguir (por defecto 100).");
+    eprintln!("\t --irse-cansado=X: Probabilidad de que una persona cansada se vaya en lugar de descansar (por defecto 0.3).");
+    eprintln!("\t --satisfaccion-minima=X: Satisfacción debajo de la cual una persona se va del parque (por defecto nunca se va por eso).\n");
     
     eprintln!("\t --costo-juegos=<LISTA>: Costo de cada juego del parque.");
     eprintln!("\t --capacidad-juegos=<LISTA>: Capacidad de cada juego del parque.");
-    eprintln!("\t --duracion-juegos=<LISTA>: Duración de cada juego del parque.\n");
+    eprintln!("\t --duracion-juegos=<LISTA>: Duración de cada juego del parque.");
+    eprintln!("\t --unidades-juegos=<LISTA>: Cantidad de unidades de cada juego, que cargan personas de una única fila (por defecto 1).");
+    eprintln!("\t --carga-juegos=<LISTA>: Milisegundos que tarda en subir cada persona a cada juego, separados por coma; 0 es instantáneo (por defecto suben instantáneamente).");
+    eprintln!("\t --descarga-juegos=<LISTA>: Milisegundos que tarda en bajar cada persona de cada juego (por defecto bajan instantáneamente).");
+    eprintln!("\t --nombres-juegos=<NOMBRES>: Nombre de cada juego del parque. Un nombre vacío deja al juego sin nombre.");
+    eprintln!("\t --filas-injustas: Las personas de la fila de un juego suben en cualquier orden (por defecto, en orden de llegada).");
+    eprintln!("\t --reembolsar-desperfectos: Los juegos devuelven la entrada a quienes esperaron en la fila durante un desperfecto.");
+    eprintln!("\t --filas-individuales: Agregar a cada juego una fila individual que ocupa los lugares que deja libres la fila principal.");
+    eprintln!("\t --altura-minima-juegos=<LISTA>: Altura mínima en centímetros para subir a cada juego. Un valor vacío indica que no hay restricción.");
+    eprintln!("\t --altura-maxima-juegos=<LISTA>: Altura máxima en centímetros para subir a cada juego.");
+    eprintln!("\t --edad-minima-juegos=<LISTA>: Edad mínima para subir a cada juego.");
+    eprintln!("\t --accesibles-juegos=<LISTA>: Si pueden subir a cada juego personas con movilidad reducida (si o no, por defecto si).");
+    eprintln!("\t --intensidad-juegos=<LISTA>: Intensidad de cada juego, de 1 (tranquilo) a 5 (extremo), por defecto 3.");
+    eprintln!("\t --cubiertos-juegos=<LISTA>: Si cada juego está bajo techo y no cierra con tormenta (si o no, por defecto no).\n");
     
     eprintln!("\t --capacidad=N: Capacidad total de personas que puede haber dentro del parque.");
+    eprintln!("\t --cierre=N: Milisegundos desde el inicio a los que cierra el parque; quienes están adentro se van (por defecto no cierra).");
+    eprintln!("\t --caminata=N: Milisegundos que se tarda en caminar entre dos juegos o desde la entrada (por defecto no se camina).");
+    eprintln!("\t                Las zonas, los puestos de comida, los espectáculos y los caminos se definen con --config.");
+    eprintln!("\t --clima=<PRONÓSTICO>: aleatorio o cambios de clima como <MS>:<CLIMA>, por ejemplo 0:soleado,8000:tormenta,12000:lluvia.");
+    eprintln!("\t                       Las tormentas cierran los juegos al aire libre (por defecto siempre está soleado).");
     eprintln!("\t --semilla=N: Semilla aleatoria a utilizar.");
     eprintln!("\t -d|--debug: Habilitar registro a un archivo.");
+    eprintln!("\t --rapido: Acelerar mil veces todos los tiempos de la simulación.");
+    eprintln!("\t --watchdog=N: Abortar si la simulación no avanza durante N milisegundos.");
     eprintln!("\t -h|--help: Muestra esta ayuda.\n");
 
+    eprintln!("MODO LOTE: ");
+    eprintln!("\t lote: Correr muchas simulaciones del escenario en modo rápido y resumir caja, desperfectos, esperas y viajes.");
+    eprintln!("\t --corridas=N: Cantidad de corridas (por defecto 10, con semillas consecutivas desde --semilla).");
+    eprintln!("\t --semillas=<DESDE>..<HASTA>: Semillas de las corridas, como rango o como lista separada por coma.");
+    eprintln!("\t --hilos=N: Cantidad de simulaciones en paralelo (por defecto, la cantidad de procesadores).\n");
+
+    eprintln!("BARRIDO DE PARÁMETROS: ");
+    eprintln!("\t barrido: Correr un lote por cada combinación de valores de los parámetros barridos y escribir una tabla CSV.");
+    eprintln!("\t --barrer <PARÁMETRO>=<VALORES>: Valores como lista (5,10,20) o rango (5..20:5). Parámetros: capacidad, costo-juegos,");
+    eprintln!("\t                                 capacidad-juegos, duracion-juegos, unidades-juegos y presupuesto.");
+    eprintln!("\t --salida=<ARCHIVO>: Escribir la tabla en un archivo en lugar de la salida estándar.\n");
+
+    eprintln!("OPTIMIZADOR DE PRECIOS: ");
+    eprintln!("\t optimizar: Buscar los precios de los juegos que maximizan la caja promedio de un lote.");
+    eprintln!("\t --metodo=<MÉTODO>: grilla, aleatorio, escalada (por defecto) o recocido.");
+    eprintln!("\t --precios=<VALORES>: Precios posibles de cada juego, como lista o rango (por defecto 5..40:5).");
+    eprintln!("\t --evaluaciones=N: Cantidad máxima de configuraciones a evaluar (por defecto 100, la grilla las evalúa todas).");
+    eprintln!("\t --peso-espera=X: Restar a la caja X pesos por milisegundo de espera promedio en las filas.");
+    eprintln!("\t --peso-satisfaccion=X: Sumar a la caja X pesos por punto de satisfacción promedio de los visitantes.\n");
+
+    eprintln!("MODELO ANALÍTICO: ");
+    eprintln!("\t teoria: Comparar las predicciones de teoría de colas para cada juego con las estadísticas de un lote.");
+    eprintln!("\t --tolerancia=X: Diferencia relativa a partir de la cual se marca una divergencia (por defecto 0.5).");
+    eprintln!("\t --tiempo-real: Correr las simulaciones en tiempo real (las esperas en modo rápido incluyen demoras del sistema).\n");
+
+    eprintln!("PLANIFICADOR DE CAPACIDAD: ");
+    eprintln!("\t planificar: Buscar capacidades, duraciones y unidades de los juegos y una capacidad del parque que cumplan una espera máxima.");
+    eprintln!("\t --espera-maxima=<MS>: Espera promedio máxima en la fila de cada juego, en milisegundos.");
+    eprintln!("\t --iteraciones=N: Cantidad máxima de cambios a los juegos (por defecto 20).");
+    eprintln!("\t --tiempo-real: Correr las simulaciones en tiempo real.\n");
+
+    eprintln!("TEMPORADA: ");
+    eprintln!("\t temporada: Simular varios días seguidos del parque, con visitantes que vuelven según su satisfacción y juegos que se desgastan.");
+    eprintln!("\t --dias=N: Cantidad de días (por defecto 3).");
+    eprintln!("\t --regreso=X: Probabilidad de volver al día siguiente de un visitante con satisfacción 0 (por defecto 0.5).");
+    eprintln!("\t --mantenimiento=N: Hacer mantenimiento por la noche a los juegos con N vueltas desde el anterior (por defecto nunca).\n");
+
     eprintln!("Para más información ver README.md");
 }
 
 impl Args {
     pub fn default() -> Self {
-        let mut rng = rand::thread_rng();
+        let semilla = rand::thread_rng().gen();
         Self {
             capacidad_parque: 10,
             presupuesto_personas: vec![40, 40, 40, 40, 40],
+            nombres_personas: None,
+            tolerancia_personas: None,
+            grupos: None,
+            presupuesto_compartido: false,
+            intervalo_llegadas: None,
+            atributos_personas: DistribucionAtributos::default(),
+            arquetipos: Arquetipo::predefinidos(),
+            mezcla_visitantes: None,
+            estadia_maxima: None,
+            cansancio_maximo: None,
+            descanso: DESCANSO_POR_DEFECTO,
+            probabilidad_irse_cansado: PROBABILIDAD_IRSE_CANSADO_POR_DEFECTO,
+            satisfaccion_minima: None,
+            cierre: None,
+            caminata: None,
+            zonas: vec![],
+            puestos: vec![],
+            caminos: vec![],
+            espectaculos: vec![],
+            clima: None,
             costo_juegos: None,
             capacidad_juegos: None,
             duracion_juegos: None,
+            unidades_juegos: None,
+            carga_juegos: None,
+            descarga_juegos: None,
+            probabilidad_desperfecto_juegos: None,
+            tiempo_arreglo_juegos: None,
+            nombres_juegos: None,
+            filas_injustas: false,
+            reembolsar_desperfectos: false,
+            fila_individual_juegos: None,
+            filas_individuales: false,
+            altura_minima_juegos: None,
+            altura_maxima_juegos: None,
+            edad_minima_juegos: None,
+            accesible_juegos: None,
+            intensidad_juegos: None,
+            cubierto_juegos: None,
+            zona_juegos: None,
+            config: None,
             debug: false,
-            semilla: rng.gen()
+            rapido: false,
+            semilla,
+            watchdog: None,
+            rng: StdRng::seed_from_u64(semilla as u64),
         }
     }
 
+    /// Aplica los valores del archivo de configuración `ruta`. La
+    /// semilla del archivo se ignora si se especificó por línea de
+    /// comandos.
+    fn aplicar_configuracion(&mut self, ruta: &str, semilla_explicita: bool) -> Result<(), String> {
+        let config = Configuracion::cargar(ruta)?;
+        self.config = Some(ruta.into());
+
+        if let (Some(semilla), false) = (config.semilla, semilla_explicita) {
+            self.semilla = semilla;
+            self.rng = StdRng::seed_from_u64(semilla as u64);
+        }
+        if let Some(capacidad) = config.capacidad {
+            self.capacidad_parque = capacidad.0;
+        }
+        if let Some(rapido) = config.rapido {
+            self.rapido = rapido;
+        }
+        if let Some(ms) = config.watchdog {
+            self.watchdog = Some(ms.0);
+        }
+        if let Some(ms) = config.intervalo_llegadas {
+            self.intervalo_llegadas = Some(ms.0);
+        }
+        if let Some(ms) = config.estadia_maxima {
+            self.estadia_maxima = Some(ms.0);
+        }
+        if let Some(maximo) = config.cansancio_maximo {
+            self.cansancio_maximo = Some(maximo.0);
+        }
+        if let Some(ms) = config.descanso {
+            self.descanso = ms.0;
+        }
+        if let Some(probabilidad) = config.irse_cansado {
+            self.probabilidad_irse_cansado = probabilidad.0;
+        }
+        if let Some(minima) = config.satisfaccion_minima {
+            self.satisfaccion_minima = Some(minima);
+        }
+        if let Some(ms) = config.cierre {
+            self.cierre = Some(ms.0);
+        }
+        if let Some(ms) = config.caminata {
+            self.caminata = Some(ms.0);
+        }
+        if let Some(pronostico) = &config.clima {
+            self.clima = Some(pronostico.0.clone());
+        }
+        if let Some(injustas) = config.filas_injustas {
+            self.filas_injustas = injustas;
+        }
+        if let Some(reembolsar) = config.reembolsar_desperfectos {
+            self.reembolsar_desperfectos = reembolsar;
+        }
+        if let Some(compartido) = config.presupuesto_compartido {
+            self.presupuesto_compartido = compartido;
+        }
+        if let Some(edad) = config.edad_min {
+            self.atributos_personas.edad_minima = edad;
+        }
+        if let Some(edad) = config.edad_max {
+            self.atributos_personas.edad_maxima = edad;
+        }
+        if let Some(probabilidad) = config.movilidad_reducida {
+            self.atributos_personas.probabilidad_movilidad_reducida = probabilidad.0;
+        }
+        for archivo in &config.arquetipos {
+            let arquetipo = match self.arquetipos.iter().position(|a| a.nombre == archivo.nombre) {
+                Some(i) => &mut self.arquetipos[i],
+                None => {
+                    self.arquetipos.push(Arquetipo::neutro(&archivo.nombre));
+                    self.arquetipos.last_mut().expect("recién agregado")
+                }
+            };
+            if let Some(intensidad) = archivo.intensidad_preferida {
+                arquetipo.intensidad_preferida = Some(intensidad.0);
+            }
+            if let Some(intensidad) = archivo.intensidad_maxima {
+                arquetipo.intensidad_maxima = Some(intensidad.0);
+            }
+            if let Some(paciencia) = archivo.paciencia {
+                arquetipo.paciencia = Some(paciencia);
+            }
+            if let Some(probabilidad) = archivo.probabilidad_comida {
+                arquetipo.probabilidad_comida = probabilidad.0;
+            }
+            if let Some(gasto) = archivo.gasto_comida {
+                arquetipo.gasto_comida = gasto;
+            }
+            if let Some(ms) = archivo.tiempo_maximo {
+                arquetipo.tiempo_maximo_ms = Some(ms.0 as u64);
+            }
+            if let Some(estrategia) = archivo.estrategia {
+                arquetipo.estrategia = estrategia;
+            }
+        }
+        if let Some(mezcla) = &config.mezcla_visitantes {
+            self.mezcla_visitantes = Some(mezcla.iter().map(|(nombre, peso)| (nombre.clone(), *peso)).collect());
+        }
+
+        if !config.juegos.is_empty() {
+            let juegos = &config.juegos;
+            self.costo_juegos = Some(juegos.iter().map(|j| j.precio.0).collect());
+            self.capacidad_juegos = Some(juegos.iter()
+                .map(|j| j.capacidad.map_or(CAPACIDAD_JUEGO_POR_DEFECTO, |c| c.0))
+                .collect());
+            self.duracion_juegos = Some(juegos.iter()
+                .map(|j| j.duracion.map_or(DURACION_JUEGO_POR_DEFECTO, |d| d.0))
+                .collect());
+            self.unidades_juegos = Some(juegos.iter()
+                .map(|j| j.unidades.map_or(UNIDADES_JUEGO_POR_DEFECTO, |u| u.0))
+                .collect());
+            self.carga_juegos = Some(juegos.iter()
+                .map(|j| j.carga.unwrap_or(CARGA_JUEGO_POR_DEFECTO))
+                .collect());
+            self.descarga_juegos = Some(juegos.iter()
+                .map(|j| j.descarga.unwrap_or(DESCARGA_JUEGO_POR_DEFECTO))
+                .collect());
+            self.probabilidad_desperfecto_juegos = Some(juegos.iter()
+                .map(|j| j.desperfectos.as_ref().map_or(PROBABILIDAD_DE_DESPERFECTOS, |d| d.probabilidad))
+                .collect());
+            self.tiempo_arreglo_juegos = Some(juegos.iter()
+                .map(|j| j.desperfectos.as_ref().map_or(TIEMPO_MAXIMO_ARREGLO_DESPERFECTO, |d| d.tiempo_maximo_arreglo))
+                .collect());
+            self.fila_individual_juegos = Some(juegos.iter()
+                .map(|j| j.fila_individual.unwrap_or(false))
+                .collect());
+            self.altura_minima_juegos = Some(juegos.iter().map(|j| j.altura_minima.map(|a| a.0)).collect());
+            self.altura_maxima_juegos = Some(juegos.iter().map(|j| j.altura_maxima.map(|a| a.0)).collect());
+            self.edad_minima_juegos = Some(juegos.iter().map(|j| j.edad_minima.map(|e| e.0)).collect());
+            self.accesible_juegos = Some(juegos.iter().map(|j| j.accesible.unwrap_or(true)).collect());
+            self.intensidad_juegos = Some(juegos.iter()
+                .map(|j| j.intensidad.map_or(INTENSIDAD_JUEGO_POR_DEFECTO, |i| i.0))
+                .collect());
+            self.cubierto_juegos = Some(juegos.iter().map(|j| j.cubierto.unwrap_or(false)).collect());
+            if juegos.iter().any(|j| j.nombre.is_some()) {
+                self.nombres_juegos = Some(juegos.iter()
+                    .map(|j| j.nombre.clone().unwrap_or_default())
+                    .collect());
+            }
+        }
+
+        // las zonas y los lugares de los caminos ya se validaron al leer
+        // el archivo
+        let zona = |nombre: &Option<String>| nombre.as_deref()
+            .map(|nombre| config.zona(nombre).expect("zona validada al leer el archivo"));
+        let lugar = |nombre: &str| config.lugar(nombre).expect("lugar validado al leer el archivo");
+        self.zonas = config.zonas.iter()
+            .map(|z| Zona { nombre: z.nombre.clone(), capacidad: z.capacidad.map(|c| c.0) })
+            .collect();
+        self.puestos = config.puestos.iter()
+            .map(|p| Puesto { nombre: p.nombre.clone(), zona: zona(&p.zona) })
+            .collect();
+        if config.juegos.iter().any(|j| j.zona.is_some()) {
+            self.zona_juegos = Some(config.juegos.iter().map(|j| zona(&j.zona)).collect());
+        }
+        self.caminos = config.caminos.iter()
+            .map(|c| Camino { desde: lugar(&c.desde), hasta: lugar(&c.hasta), ms: c.ms as u64 })
+            .collect();
+        self.espectaculos = config.espectaculos.iter()
+            .map(|e| ConfiguracionEspectaculo {
+                nombre: e.nombre.clone(),
+                precio: e.precio,
+                capacidad: e.capacidad.0,
+                duracion_ms: e.duracion.0 as u64,
+                horarios_ms: e.horarios.0.iter().map(|&h| h as u64).collect(),
+                anticipacion_ms: e.anticipacion.unwrap_or(ANTICIPACION_ESPECTACULO_POR_DEFECTO) as u64,
+                interes: e.interes.map_or(INTERES_ESPECTACULO_POR_DEFECTO, |i| i.0),
+                zona: zona(&e.zona),
+            })
+            .collect();
+
+        if !config.visitantes.is_empty() {
+            self.presupuesto_personas = vec![];
+            let mut nombres = vec![];
+            let mut tolerancias = vec![];
+            let mut grupos = vec![];
+            for grupo in &config.visitantes {
+                let integrantes = grupo.integrantes.unwrap_or(1);
+                let mut restantes = grupo.cantidad;
+                while restantes > 0 {
+                    grupos.push(integrantes.min(restantes));
+                    restantes -= integrantes.min(restantes);
+                }
+                for i in 1..=grupo.cantidad {
+                    tolerancias.push(grupo.tolerancia);
+                    nombres.push(grupo.nombre.as_ref()
+                        .map(|nombre| format!("{} {}", nombre, i))
+                        .unwrap_or_default());
+                    let presupuesto = if grupo.presupuesto_min == grupo.presupuesto_max {
+                        grupo.presupuesto_min
+                    } else {
+                        self.rng.gen_range(grupo.presupuesto_min..grupo.presupuesto_max)
+                    };
+                    self.presupuesto_personas.push(presupuesto);
+                }
+            }
+            self.nombres_personas = if config.visitantes.iter().any(|g| g.nombre.is_some()) {
+                Some(nombres)
+            } else {
+                None
+            };
+            self.grupos = if config.visitantes.iter().any(|g| g.integrantes.is_some()) {
+                Some(grupos)
+            } else {
+                None
+            };
+            self.tolerancia_personas = if config.visitantes.iter().any(|g| g.tolerancia.is_some()) {
+                Some(tolerancias)
+            } else {
+                None
+            };
+        }
+        Ok(())
+    }
+
     pub fn as_str(&self) -> String {
         let exe = &std::env::args().collect::<Vec<String>>()[0];
         let debug = if self.debug { "-d" } else { "" };
+        let rapido = if self.rapido { " --rapido" } else { "" };
 
-        let mut result = format!("{} --capacidad={} {}", 
-            exe, self.capacidad_parque, 
+        let mut result = exe.clone();
+        if let Some(ruta) = &self.config {
+            result += &format!(" --config={}", ruta);
+        }
+        result += &format!(" --capacidad={} {}",
+            self.capacidad_parque,
             Self::stringify_array("--personas", &self.presupuesto_personas));
+
+        if let Some(nombres) = &self.nombres_personas {
+            result += &Self::stringify_nombres(" --nombres-personas", nombres);
+        }
+
+        if let Some(grupos) = &self.grupos {
+            result += &Self::stringify_array(" --grupos", grupos);
+        }
+
+        if self.presupuesto_compartido {
+            result += " --presupuesto-compartido";
+        }
+
+        if let Some(tolerancias) = &self.tolerancia_personas {
+            result += &Self::stringify_opcionales(" --tolerancia-personas", tolerancias);
+        }
+
+        if let Some(mezcla) = &self.mezcla_visitantes {
+            let mezcla: Vec<String> = mezcla.iter().map(|(nombre, peso)| format!("{}:{}", nombre, peso)).collect();
+            result += &format!(" --mezcla-visitantes={}", mezcla.join(","));
+        }
+
+        if self.atributos_personas != DistribucionAtributos::default() {
+            let atributos = &self.atributos_personas;
+            result += &format!(" --edades={}..{} --movilidad-reducida={}",
+                atributos.edad_minima, atributos.edad_maxima, atributos.probabilidad_movilidad_reducida);
+        }
         
         if let Some(data) = &self.costo_juegos {
             result += &Self::stringify_array(" --costo-juegos", data);
@@ -134,7 +679,102 @@ impl Args {
             result += &Self::stringify_array(" --duracion-juegos", data);
         }
 
-        result + &format!(" --semilla={} {}", self.semilla, debug)
+        if let Some(data) = &self.unidades_juegos {
+            result += &Self::stringify_array(" --unidades-juegos", data);
+        }
+
+        // los tiempos de carga y descarga sólo se muestran si alguno no es
+        // instantáneo
+        if let Some(data) = self.carga_juegos.as_ref().filter(|d| d.iter().any(|ms| *ms > 0)) {
+            result += &Self::stringify_array(" --carga-juegos", data);
+        }
+
+        if let Some(data) = self.descarga_juegos.as_ref().filter(|d| d.iter().any(|ms| *ms > 0)) {
+            result += &Self::stringify_array(" --descarga-juegos", data);
+        }
+
+        if let Some(nombres) = &self.nombres_juegos {
+            result += &Self::stringify_nombres(" --nombres-juegos", nombres);
+        }
+
+        if self.filas_injustas {
+            result += " --filas-injustas";
+        }
+        if self.reembolsar_desperfectos {
+            result += " --reembolsar-desperfectos";
+        }
+
+        if self.filas_individuales {
+            result += " --filas-individuales";
+        }
+
+        // las restricciones sólo se muestran si algún juego tiene
+        for (nombre, lista) in [
+            (" --altura-minima-juegos", &self.altura_minima_juegos),
+            (" --altura-maxima-juegos", &self.altura_maxima_juegos),
+            (" --edad-minima-juegos", &self.edad_minima_juegos),
+        ] {
+            if let Some(lista) = lista.as_ref().filter(|l| l.iter().any(Option::is_some)) {
+                result += &Self::stringify_opcionales(nombre, lista);
+            }
+        }
+
+        if let Some(data) = self.intensidad_juegos.as_ref().filter(|d| d.iter().any(|i| *i != INTENSIDAD_JUEGO_POR_DEFECTO)) {
+            result += &Self::stringify_array(" --intensidad-juegos", data);
+        }
+
+        if let Some(accesibles) = self.accesible_juegos.as_ref().filter(|a| a.iter().any(|a| !a)) {
+            result += &Self::stringify_si_no(" --accesibles-juegos", accesibles);
+        }
+
+        if let Some(cubiertos) = self.cubierto_juegos.as_ref().filter(|c| c.iter().any(|c| *c)) {
+            result += &Self::stringify_si_no(" --cubiertos-juegos", cubiertos);
+        }
+
+        if let Some(ms) = self.intervalo_llegadas {
+            result += &format!(" --intervalo-llegadas={}", ms);
+        }
+
+        if let Some(ms) = self.estadia_maxima {
+            result += &format!(" --estadia-maxima={}", ms);
+        }
+
+        if let Some(maximo) = self.cansancio_maximo {
+            result += &format!(" --cansancio-maximo={}", maximo);
+            if self.descanso != DESCANSO_POR_DEFECTO {
+                result += &format!(" --descanso={}", self.descanso);
+            }
+            if self.probabilidad_irse_cansado != PROBABILIDAD_IRSE_CANSADO_POR_DEFECTO {
+                result += &format!(" --irse-cansado={}", self.probabilidad_irse_cansado);
+            }
+        }
+
+        if let Some(minima) = self.satisfaccion_minima {
+            result += &format!(" --satisfaccion-minima={}", minima);
+        }
+
+        if let Some(ms) = self.cierre {
+            result += &format!(" --cierre={}", ms);
+        }
+
+        if let Some(ms) = self.caminata {
+            result += &format!(" --caminata={}", ms);
+        }
+
+        match &self.clima {
+            Some(ConfiguracionClima::Aleatorio) => result += " --clima=aleatorio",
+            Some(ConfiguracionClima::Fijo(cambios)) => {
+                let cambios: Vec<String> = cambios.iter().map(|(ms, clima)| format!("{}:{}", ms, clima)).collect();
+                result += &format!(" --clima={}", cambios.join(","));
+            },
+            None => {},
+        }
+
+        if let Some(ms) = self.watchdog {
+            result += &format!(" --watchdog={}", ms);
+        }
+
+        result + &format!("{} --semilla={} {}", rapido, self.semilla, debug)
     }
 
     fn stringify_array(nombre: &str, array: &[u32]) -> String {
@@ -147,34 +787,125 @@ impl Args {
         )
     }
 
+    /// Lista en la que los valores faltantes quedan vacíos.
+    fn stringify_opcionales(nombre: &str, lista: &[Option<u32>]) -> String {
+        let valores: Vec<String> = lista.iter()
+            .map(|v| v.map(|v| v.to_string()).unwrap_or_default())
+            .collect();
+        format!("{}={}", nombre, valores.join(","))
+    }
+
+    fn stringify_si_no(nombre: &str, lista: &[bool]) -> String {
+        let valores: Vec<&str> = lista.iter().map(|v| if *v { "si" } else { "no" }).collect();
+        format!("{}={}", nombre, valores.join(","))
+    }
+
+    /// Lista de nombres entre comillas, para poder copiarla a la línea
+    /// de comandos aunque tenga espacios.
+    fn stringify_nombres(nombre: &str, nombres: &[String]) -> String {
+        format!("{}=\"{}\"", nombre, nombres.join(","))
+    }
+
     pub fn parsers() -> HashMap<&'static str, Parser> {
         let mut result: HashMap<&'static str, Parser> = HashMap::new();
         result.insert("--personas", Self::parse_personas);
+        result.insert("--nombres-personas", Self::parse_nombres_personas);
+        result.insert("--tolerancia-personas", Self::parse_tolerancia_personas);
+        result.insert("--grupos", Self::parse_grupos);
+        result.insert("--nombres-juegos", Self::parse_nombres_juegos);
         result.insert("--costo-juegos", Self::parse_costo_juegos);
         result.insert("--capacidad-juegos", Self::parse_capacidad_juegos);
         result.insert("--duracion-juegos", Self::parse_duracion_juegos);
+        result.insert("--unidades-juegos", Self::parse_unidades_juegos);
+        result.insert("--carga-juegos", Self::parse_carga_juegos);
+        result.insert("--descarga-juegos", Self::parse_descarga_juegos);
         result.insert("--capacidad", Self::parse_capacidad);
         result.insert("--semilla", Self::parse_semilla);
+        result.insert("--config", Self::parse_config);
+        result.insert("--watchdog", Self::parse_watchdog);
+        result.insert("--intervalo-llegadas", Self::parse_intervalo_llegadas);
+        result.insert("--estadia-maxima", Self::parse_estadia_maxima);
+        result.insert("--cansancio-maximo", Self::parse_cansancio_maximo);
+        result.insert("--descanso", Self::parse_descanso);
+        result.insert("--irse-cansado", Self::parse_irse_cansado);
+        result.insert("--satisfaccion-minima", Self::parse_satisfaccion_minima);
+        result.insert("--cierre", Self::parse_cierre);
+        result.insert("--caminata", Self::parse_caminata);
+        result.insert("--edades", Self::parse_edades);
+        result.insert("--movilidad-reducida", Self::parse_movilidad_reducida);
+        result.insert("--altura-minima-juegos", Self::parse_altura_minima_juegos);
+        result.insert("--altura-maxima-juegos", Self::parse_altura_maxima_juegos);
+        result.insert("--edad-minima-juegos", Self::parse_edad_minima_juegos);
+        result.insert("--accesibles-juegos", Self::parse_accesibles_juegos);
+        result.insert("--intensidad-juegos", Self::parse_intensidad_juegos);
+        result.insert("--cubiertos-juegos", Self::parse_cubiertos_juegos);
+        result.insert("--clima", Self::parse_clima);
+        result.insert("--mezcla-visitantes", Self::parse_mezcla_visitantes);
         result
     }
 
     fn parse_personas(args: &mut Args, data: &str) -> Result<(), String> {
-        args.presupuesto_personas = Self::parse_array(data)?;
+        args.presupuesto_personas = args.parse_array(data)?;
+        Ok(())
+    }
+
+    fn parse_nombres_personas(args: &mut Args, data: &str) -> Result<(), String> {
+        args.nombres_personas = Some(Self::parse_nombres(data));
+        Ok(())
+    }
+
+    /// Lista de tolerancias separadas por coma, en la que un valor
+    /// vacío indica que la persona espera siempre. A diferencia del resto
+    /// de las listas se acepta 0: entrar sólo si no hay nadie esperando.
+    fn parse_tolerancia_personas(args: &mut Args, data: &str) -> Result<(), String> {
+        args.tolerancia_personas = Some(data.split(',')
+            .map(|valor| match valor.trim() {
+                "" => Ok(None),
+                valor => valor.parse::<u32>()
+                    .map(Some)
+                    .map_err(|_| format!("'{}' no es un número entero no negativo", valor)),
+            })
+            .collect::<Result<_, _>>()?);
+        Ok(())
+    }
+
+    fn parse_grupos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.grupos = Some(args.parse_array(data)?);
+        Ok(())
+    }
+
+    fn parse_nombres_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.nombres_juegos = Some(Self::parse_nombres(data));
         Ok(())
     }
 
     fn parse_costo_juegos(args: &mut Args, data: &str) -> Result<(), String> {
-        args.costo_juegos = Some(Self::parse_array(data)?);
+        args.costo_juegos = Some(args.parse_array(data)?);
         Ok(())
     }
 
     fn parse_capacidad_juegos(args: &mut Args, data: &str) -> Result<(), String> {
-        args.capacidad_juegos = Some(Self::parse_array(data)?);
+        args.capacidad_juegos = Some(args.parse_array(data)?);
         Ok(())
     }
 
     fn parse_duracion_juegos(args: &mut Args, data: &str) -> Result<(), String> {
-        args.duracion_juegos = Some(Self::parse_array(data)?);
+        args.duracion_juegos = Some(args.parse_array(data)?);
+        Ok(())
+    }
+
+    fn parse_unidades_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.unidades_juegos = Some(args.parse_array(data)?);
+        Ok(())
+    }
+
+    fn parse_carga_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.carga_juegos = Some(Self::parse_tiempos(data)?);
+        Ok(())
+    }
+
+    fn parse_descarga_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.descarga_juegos = Some(Self::parse_tiempos(data)?);
         Ok(())
     }
 
@@ -185,10 +916,162 @@ impl Args {
 
     fn parse_semilla(args: &mut Args, data: &str) -> Result<(), String> {
         args.semilla = Self::parse_u32(data)?;
+        args.rng = StdRng::seed_from_u64(args.semilla as u64);
+        Ok(())
+    }
+
+    /// El archivo de configuración se aplica antes que el resto de las
+    /// opciones en `parse_args_desde`.
+    fn parse_config(_args: &mut Args, _data: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn parse_watchdog(args: &mut Args, data: &str) -> Result<(), String> {
+        args.watchdog = Some(Self::parse_u32(data)?);
+        Ok(())
+    }
+
+    fn parse_intervalo_llegadas(args: &mut Args, data: &str) -> Result<(), String> {
+        args.intervalo_llegadas = Some(Self::parse_u32(data)?);
+        Ok(())
+    }
+
+    fn parse_estadia_maxima(args: &mut Args, data: &str) -> Result<(), String> {
+        args.estadia_maxima = Some(Self::parse_u32(data)?);
+        Ok(())
+    }
+
+    fn parse_cansancio_maximo(args: &mut Args, data: &str) -> Result<(), String> {
+        args.cansancio_maximo = Some(Self::parse_u32(data)?);
+        Ok(())
+    }
+
+    fn parse_descanso(args: &mut Args, data: &str) -> Result<(), String> {
+        args.descanso = Self::parse_u32(data)?;
+        Ok(())
+    }
+
+    fn parse_irse_cansado(args: &mut Args, data: &str) -> Result<(), String> {
+        args.probabilidad_irse_cansado = Self::parse_probabilidad(data)?;
+        Ok(())
+    }
+
+    fn parse_satisfaccion_minima(args: &mut Args, data: &str) -> Result<(), String> {
+        args.satisfaccion_minima = Some(data.parse::<f64>()
+            .ok()
+            .filter(|minima| minima.is_finite())
+            .ok_or_else(|| format!("'{}' no es un número", data))?);
+        Ok(())
+    }
+
+    fn parse_cierre(args: &mut Args, data: &str) -> Result<(), String> {
+        args.cierre = Some(Self::parse_u32(data)?);
         Ok(())
     }
 
-    fn parse_array(data: &str) -> Result<Vec<u32>, String> {
+    fn parse_caminata(args: &mut Args, data: &str) -> Result<(), String> {
+        args.caminata = Some(Self::parse_u32(data)?);
+        Ok(())
+    }
+
+    /// Rango de edades `<MIN>..<MAX>`, incluyendo ambos extremos. Se
+    /// acepta 0 para los recién nacidos.
+    fn parse_edades(args: &mut Args, data: &str) -> Result<(), String> {
+        let (minima, maxima) = data.split_once("..")
+            .ok_or_else(|| format!("'{}' no es un rango <MIN>..<MAX>", data))?;
+        let edad = |valor: &str| valor.parse::<u32>()
+            .map_err(|_| format!("'{}' no es un número entero no negativo", valor));
+        args.atributos_personas.edad_minima = edad(minima)?;
+        args.atributos_personas.edad_maxima = edad(maxima)?;
+        Ok(())
+    }
+
+    fn parse_movilidad_reducida(args: &mut Args, data: &str) -> Result<(), String> {
+        args.atributos_personas.probabilidad_movilidad_reducida = Self::parse_probabilidad(data)?;
+        Ok(())
+    }
+
+    fn parse_probabilidad(data: &str) -> Result<f64, String> {
+        let probabilidad = data.parse::<f64>()
+            .map_err(|_| format!("'{}' no es un número", data))?;
+        if !(0.0..=1.0).contains(&probabilidad) {
+            return Err(format!("La probabilidad debe estar entre 0 y 1 ({})", probabilidad));
+        }
+        Ok(probabilidad)
+    }
+
+    fn parse_altura_minima_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.altura_minima_juegos = Some(Self::parse_opcionales(data)?);
+        Ok(())
+    }
+
+    fn parse_altura_maxima_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.altura_maxima_juegos = Some(Self::parse_opcionales(data)?);
+        Ok(())
+    }
+
+    fn parse_edad_minima_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.edad_minima_juegos = Some(Self::parse_opcionales(data)?);
+        Ok(())
+    }
+
+    fn parse_accesibles_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.accesible_juegos = Some(Self::parse_si_no(data)?);
+        Ok(())
+    }
+
+    fn parse_cubiertos_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.cubierto_juegos = Some(Self::parse_si_no(data)?);
+        Ok(())
+    }
+
+    fn parse_clima(args: &mut Args, data: &str) -> Result<(), String> {
+        args.clima = Some(parse_clima(data)?);
+        Ok(())
+    }
+
+    /// Lista de `si` o `no` separados por coma.
+    fn parse_si_no(data: &str) -> Result<Vec<bool>, String> {
+        data.split(',')
+            .map(|valor| match valor.trim() {
+                "si" => Ok(true),
+                "no" => Ok(false),
+                valor => Err(format!("'{}' no es si o no", valor)),
+            })
+            .collect()
+    }
+
+    fn parse_intensidad_juegos(args: &mut Args, data: &str) -> Result<(), String> {
+        args.intensidad_juegos = Some(args.parse_array(data)?);
+        Ok(())
+    }
+
+    fn parse_mezcla_visitantes(args: &mut Args, data: &str) -> Result<(), String> {
+        args.mezcla_visitantes = Some(parse_mezcla(data)?);
+        Ok(())
+    }
+
+    /// Lista de naturales separados por coma en la que un valor vacío
+    /// indica que no hay restricción.
+    fn parse_opcionales(data: &str) -> Result<Vec<Option<u32>>, String> {
+        data.split(',')
+            .map(|valor| match valor.trim() {
+                "" => Ok(None),
+                valor => Self::parse_u32(valor).map(Some),
+            })
+            .collect()
+    }
+
+    /// Lista de milisegundos separados por coma. A diferencia de
+    /// `parse_array` acepta 0, que es instantáneo, y no admite rangos.
+    fn parse_tiempos(data: &str) -> Result<Vec<u32>, String> {
+        data.split(',')
+            .map(|valor| valor.trim().parse::<u32>()
+                .map_err(|_| format!("'{}' no es un número entero no negativo", valor)))
+            .collect()
+    }
+
+    fn parse_array(&mut self, data: &str) -> Result<Vec<u32>, String> {
         // Formatos posibles:
         // N,N,N,N
         // N:P
@@ -210,10 +1093,10 @@ impl Args {
         }
         
         // N:P o N:Pm:PM
-        let n_personas = Self::parse_u32(&partes[0])?;
-        let presupuesto_min = Self::parse_u32(&partes[1])?;
+        let n_personas = Self::parse_u32(partes[0])?;
+        let presupuesto_min = Self::parse_u32(partes[1])?;
         let presupuesto_max = if partes.len() == 3 {
-            Self::parse_u32(&partes[2])?
+            Self::parse_u32(partes[2])?
         } else {
             presupuesto_min
         };
@@ -230,10 +1113,9 @@ impl Args {
             }
         } else {
             // N:Pm:PM
-            let mut rng = rand::thread_rng();
             for _ in 0..n_personas {
                 resultado.push(
-                    rng.gen_range(presupuesto_min..presupuesto_max)
+                    self.rng.gen_range(presupuesto_min..presupuesto_max)
                 );
             }
         }
@@ -241,6 +1123,10 @@ impl Args {
         Ok(resultado)
     }
 
+    fn parse_nombres(data: &str) -> Vec<String> {
+        data.split(',').map(|nombre| nombre.trim().to_string()).collect()
+    }
+
     fn parse_u32(data: &str) -> Result<u32, String> {
         let ret = data.parse::<u32>()
             .map_err(|_| format!("'{}' no es un número natural", data))?;
@@ -258,88 +1144,680 @@ impl Args {
     /// la cantidad correcta de valores por defecto para los otros 
     /// parámetros.
     ///
-    /// En caso de que se especifiquen todos los parámetros, se 
-    /// revisará que todos representen la misma cantidad de elementos.
+    /// En caso de que se especifiquen varios parámetros, se revisará
+    /// que todos representen la misma cantidad de elementos.
     pub fn resolver(&mut self) -> Result<(), String> {
-        if self.costo_juegos.is_none() &&
-           self.capacidad_juegos.is_none() && 
-           self.duracion_juegos.is_none() {
-                self.costo_juegos = Some(vec![10;5]);
-                self.capacidad_juegos = Some(vec![2;5]);
-                self.duracion_juegos = Some(vec![25;5]);
-                return Ok(())
-        }
-
-        let (costos, capacidad, duraciones) = 
-        if let Some(costos) = self.costo_juegos.take() {
-            let capacidad = Self::igualar_arrays(
-                &costos, 
-                &mut self.capacidad_juegos,
-                2,
-                "--costo-juegos",
-                "--capacidad-juegos"
-            )?;
-            let duraciones = Self::igualar_arrays(
-                &costos, 
-                &mut self.duracion_juegos,
-                25,
-                "--costo-juegos",
-                "--duracion-juegos"
-            )?;
-            (costos, capacidad, duraciones)
-        } else if let Some(capacidad) = self.capacidad_juegos.take() {
-            let costos = Self::igualar_arrays(
-                &capacidad, 
-                &mut self.costo_juegos,
-                10,
-                "--capacidad-juegos",
-                "--costo-juegos"
-            )?;
-            let duraciones = Self::igualar_arrays(
-                &capacidad, 
-                &mut self.duracion_juegos,
-                25,
-                "--capacidad-juegos",
-                "--duracion-juegos"
-            )?;
-            (costos, capacidad, duraciones)
+        let longitudes = [
+            ("--costo-juegos", self.costo_juegos.as_ref().map(Vec::len)),
+            ("--capacidad-juegos", self.capacidad_juegos.as_ref().map(Vec::len)),
+            ("--duracion-juegos", self.duracion_juegos.as_ref().map(Vec::len)),
+            ("--unidades-juegos", self.unidades_juegos.as_ref().map(Vec::len)),
+            ("--carga-juegos", self.carga_juegos.as_ref().map(Vec::len)),
+            ("--descarga-juegos", self.descarga_juegos.as_ref().map(Vec::len)),
+            ("desperfectos", self.probabilidad_desperfecto_juegos.as_ref().map(Vec::len)),
+            ("desperfectos", self.tiempo_arreglo_juegos.as_ref().map(Vec::len)),
+            ("--nombres-juegos", self.nombres_juegos.as_ref().map(Vec::len)),
+            ("fila_individual", self.fila_individual_juegos.as_ref().map(Vec::len)),
+            ("--altura-minima-juegos", self.altura_minima_juegos.as_ref().map(Vec::len)),
+            ("--altura-maxima-juegos", self.altura_maxima_juegos.as_ref().map(Vec::len)),
+            ("--edad-minima-juegos", self.edad_minima_juegos.as_ref().map(Vec::len)),
+            ("--accesibles-juegos", self.accesible_juegos.as_ref().map(Vec::len)),
+            ("--intensidad-juegos", self.intensidad_juegos.as_ref().map(Vec::len)),
+            ("--cubiertos-juegos", self.cubierto_juegos.as_ref().map(Vec::len)),
+            ("zona", self.zona_juegos.as_ref().map(Vec::len)),
+        ];
+
+        let (nombre_base, cantidad) = longitudes.iter()
+            .find_map(|(nombre, longitud)| longitud.map(|l| (*nombre, l)))
+            .unwrap_or(("", CANTIDAD_JUEGOS_POR_DEFECTO));
+
+        for (nombre, longitud) in longitudes.iter() {
+            if let Some(longitud) = longitud {
+                if *longitud != cantidad {
+                    return Err(format!(
+                        "Los parámetros {} y {} deben ser arreglos del mismo tamaño ({} != {})",
+                        nombre_base, nombre, cantidad, longitud
+                    ));
+                }
+            }
+        }
+
+        if let Some(nombres) = &self.nombres_personas {
+            if nombres.len() != self.presupuesto_personas.len() {
+                return Err(format!(
+                    "Los parámetros --personas y --nombres-personas deben ser arreglos del mismo tamaño ({} != {})",
+                    self.presupuesto_personas.len(), nombres.len()
+                ));
+            }
+        }
+        Self::validar_nombres("--nombres-personas", &self.nombres_personas)?;
+        Self::validar_nombres("--nombres-juegos", &self.nombres_juegos)?;
+
+        if let Some(tolerancias) = &self.tolerancia_personas {
+            if tolerancias.len() != self.presupuesto_personas.len() {
+                return Err(format!(
+                    "Los parámetros --personas y --tolerancia-personas deben ser arreglos del mismo tamaño ({} != {})",
+                    self.presupuesto_personas.len(), tolerancias.len()
+                ));
+            }
+        }
+
+        if let Some(mezcla) = &self.mezcla_visitantes {
+            if let Some((nombre, _)) = mezcla.iter().find(|(nombre, _)| self.arquetipos.iter().all(|a| &a.nombre != nombre)) {
+                let nombres: Vec<&str> = self.arquetipos.iter().map(|a| a.nombre.as_str()).collect();
+                return Err(format!(
+                    "[--mezcla-visitantes] Arquetipo desconocido '{}' ({})", nombre, nombres.join(", ")
+                ));
+            }
+        }
+        for arquetipo in &self.arquetipos {
+            let intensidades = [arquetipo.intensidad_preferida, arquetipo.intensidad_maxima];
+            if intensidades.iter().flatten().any(|i| *i > INTENSIDAD_MAXIMA) {
+                return Err(format!(
+                    "[arquetipos] Las intensidades del arquetipo {} deben estar entre 1 y {}", arquetipo.nombre, INTENSIDAD_MAXIMA
+                ));
+            }
+        }
+
+        let atributos = &self.atributos_personas;
+        if atributos.edad_minima > atributos.edad_maxima {
+            return Err(format!(
+                "[--edades] Rango inválido ({} > {})", atributos.edad_minima, atributos.edad_maxima
+            ));
+        }
+
+        self.validar_grupos()?;
+
+        let (costo, capacidad) = if self.costo_juegos.is_none() && self.capacidad_juegos.is_none() && self.duracion_juegos.is_some() {
+            (COSTO_Y_CAPACIDAD_SOLO_DURACION, COSTO_Y_CAPACIDAD_SOLO_DURACION)
         } else {
-            let duraciones = self.duracion_juegos.take().unwrap();
-            let costos = Self::igualar_arrays(
-                &duraciones, 
-                &mut self.costo_juegos,
-                25,
-                "--duracion-juegos",
-                "--costo-juegos"
-            )?;
-            let capacidad = Self::igualar_arrays(
-                &duraciones, 
-                &mut self.capacidad_juegos,
-                25,
-                "--duracion-juegos",
-                "--capacidad-juegos"
-            )?;
-            (costos, capacidad, duraciones)
+            (COSTO_JUEGO_POR_DEFECTO, CAPACIDAD_JUEGO_POR_DEFECTO)
         };
+        Self::completar(&mut self.costo_juegos, cantidad, costo);
+        Self::completar(&mut self.capacidad_juegos, cantidad, capacidad);
+        Self::completar(&mut self.duracion_juegos, cantidad, DURACION_JUEGO_POR_DEFECTO);
+        Self::completar(&mut self.unidades_juegos, cantidad, UNIDADES_JUEGO_POR_DEFECTO);
+        Self::completar(&mut self.carga_juegos, cantidad, CARGA_JUEGO_POR_DEFECTO);
+        Self::completar(&mut self.descarga_juegos, cantidad, DESCARGA_JUEGO_POR_DEFECTO);
+        if self.unidades_juegos.iter().flatten().any(|u| *u == 0) {
+            return Err("[--unidades-juegos] Cada juego debe tener al menos una unidad".into());
+        }
+        Self::completar(&mut self.probabilidad_desperfecto_juegos, cantidad, PROBABILIDAD_DE_DESPERFECTOS);
+        Self::completar(&mut self.tiempo_arreglo_juegos, cantidad, TIEMPO_MAXIMO_ARREGLO_DESPERFECTO);
+        Self::completar(&mut self.fila_individual_juegos, cantidad, false);
+        Self::completar(&mut self.altura_minima_juegos, cantidad, None);
+        Self::completar(&mut self.altura_maxima_juegos, cantidad, None);
+        Self::completar(&mut self.edad_minima_juegos, cantidad, None);
+        Self::completar(&mut self.accesible_juegos, cantidad, true);
+        Self::completar(&mut self.intensidad_juegos, cantidad, INTENSIDAD_JUEGO_POR_DEFECTO);
+        Self::completar(&mut self.cubierto_juegos, cantidad, false);
+        Self::completar(&mut self.zona_juegos, cantidad, None);
+        if self.intensidad_juegos.iter().flatten().any(|i| *i > INTENSIDAD_MAXIMA) {
+            return Err(format!("[--intensidad-juegos] La intensidad de cada juego debe estar entre 1 y {}", INTENSIDAD_MAXIMA));
+        }
+        let alturas = self.altura_minima_juegos.iter().flatten().zip(self.altura_maxima_juegos.iter().flatten());
+        for (i, (minima, maxima)) in alturas.enumerate() {
+            if let (Some(minima), Some(maxima)) = (minima, maxima) {
+                if minima > maxima {
+                    return Err(format!(
+                        "[--altura-minima-juegos] La altura mínima del juego {} supera la máxima ({} > {})",
+                        i, minima, maxima
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Si `lista` no se especificó la completa con `cantidad` valores
+    /// por defecto.
+    fn completar<T: Clone>(lista: &mut Option<Vec<T>>, cantidad: usize, defval: T) {
+        if lista.is_none() {
+            *lista = Some(vec![defval; cantidad]);
+        }
+    }
 
-        self.costo_juegos = Some(costos);
-        self.capacidad_juegos = Some(capacidad);
-        self.duracion_juegos = Some(duraciones);
+    /// Configuración de cada uno de los juegos. Requiere que se haya
+    /// llamado a `resolver`.
+    pub fn juegos(&self) -> Result<Vec<ConfiguracionJuego>, String> {
+        let costos = Self::resuelta(&self.costo_juegos)?;
+        let capacidades = Self::resuelta(&self.capacidad_juegos)?;
+        let duraciones = Self::resuelta(&self.duracion_juegos)?;
+        let unidades = Self::resuelta(&self.unidades_juegos)?;
+        let cargas = Self::resuelta(&self.carga_juegos)?;
+        let descargas = Self::resuelta(&self.descarga_juegos)?;
+        let probabilidades = Self::resuelta(&self.probabilidad_desperfecto_juegos)?;
+        let arreglos = Self::resuelta(&self.tiempo_arreglo_juegos)?;
+        let filas_individuales = Self::resuelta(&self.fila_individual_juegos)?;
+        let alturas_minimas = Self::resuelta(&self.altura_minima_juegos)?;
+        let alturas_maximas = Self::resuelta(&self.altura_maxima_juegos)?;
+        let edades_minimas = Self::resuelta(&self.edad_minima_juegos)?;
+        let accesibles = Self::resuelta(&self.accesible_juegos)?;
+        let intensidades = Self::resuelta(&self.intensidad_juegos)?;
+        let cubiertos = Self::resuelta(&self.cubierto_juegos)?;
 
-        Ok(())
+        Ok((0..costos.len())
+            .map(|i| ConfiguracionJuego {
+                nombre: Self::nombre(&self.nombres_juegos, i),
+                precio: costos[i],
+                capacidad: capacidades[i],
+                duracion_ms: duraciones[i],
+                unidades: unidades[i],
+                carga_ms: cargas[i],
+                descarga_ms: descargas[i],
+                fila_justa: !self.filas_injustas,
+                fila_individual: self.filas_individuales || filas_individuales[i],
+                restricciones: Restricciones {
+                    altura_minima_cm: alturas_minimas[i],
+                    altura_maxima_cm: alturas_maximas[i],
+                    edad_minima: edades_minimas[i],
+                    accesible: accesibles[i],
+                },
+                intensidad: intensidades[i],
+                cubierto: cubiertos[i],
+                reembolso_desperfectos: self.reembolsar_desperfectos,
+                probabilidad_desperfecto: probabilidades[i],
+                tiempo_maximo_arreglo_ms: arreglos[i],
+            })
+            .collect())
+    }
+
+    /// Cambios de clima de la simulación, si hay pronóstico. El aleatorio
+    /// se genera con la semilla hasta el cierre del parque.
+    pub fn pronostico(&self) -> Option<Vec<(u64, Clima)>> {
+        match self.clima.as_ref()? {
+            ConfiguracionClima::Aleatorio => {
+                let horizonte = self.cierre.map_or(HORIZONTE_CLIMA_MS, |ms| ms as u64);
+                Some(generar((self.semilla as u64).rotate_left(48), horizonte))
+            },
+            ConfiguracionClima::Fijo(cambios) => Some(cambios.clone()),
+        }
+    }
+
+    /// Mapa del parque, si las personas caminan entre los juegos.
+    /// Requiere que se haya llamado a `resolver`.
+    pub fn mapa(&self) -> Result<Option<Mapa>, String> {
+        if self.caminata.is_none() && self.caminos.is_empty() && self.zonas.is_empty() && self.puestos.is_empty() {
+            return Ok(None);
+        }
+        let zonas_juegos = Self::resuelta(&self.zona_juegos)?.to_vec();
+        let zonas_espectaculos: Vec<_> = self.espectaculos.iter().map(|e| e.zona).collect();
+        let mut caminos = self.caminos.clone();
+        if let Some(ms) = self.caminata {
+            caminos.extend(caminos_uniformes(zonas_juegos.len(), self.puestos.len(), zonas_espectaculos.len(), ms as u64));
+        }
+        Mapa::new(self.zonas.clone(), zonas_juegos, zonas_espectaculos, self.puestos.clone(), &caminos)
+            .map(Some)
+            .map_err(|e| format!("[caminos] {}", e))
+    }
+
+    fn resuelta<T>(lista: &Option<Vec<T>>) -> Result<&[T], String> {
+        lista.as_deref().ok_or_else(|| "Los parámetros de los juegos no fueron resueltos".into())
+    }
+
+    /// Configuración de cada una de las personas. Si hay intervalo de
+    /// llegadas, los tiempos entre llegadas siguen una distribución
+    /// exponencial derivada de la semilla. Los atributos y los
+    /// arquetipos de las personas se generan con otros generadores,
+    /// también derivados de la semilla, para que no cambien las
+    /// llegadas. Los integrantes de un grupo comparten el arquetipo, y la
+    /// paciencia del arquetipo es la tolerancia a la fila de entrada de
+    /// quienes no tienen una propia.
+    pub fn personas(&self) -> Vec<ConfiguracionPersona> {
+        let mut rng = StdRng::seed_from_u64(!(self.semilla as u64));
+        let mut rng_atributos = StdRng::seed_from_u64((self.semilla as u64).rotate_left(32));
+        let mut rng_arquetipos = StdRng::seed_from_u64((self.semilla as u64).rotate_left(16));
+        let mut anterior: Option<(Option<usize>, Option<&Arquetipo>)> = None;
+        let mut llegada = 0.0;
+        // los grupos de una persona van solas y no cuentan como grupo
+        let mut grupos: Vec<Option<usize>> = vec![];
+        let mut cantidad_grupos = 0;
+        for integrantes in self.grupos.iter().flatten() {
+            if *integrantes > 1 {
+                grupos.extend(vec![Some(cantidad_grupos); *integrantes as usize]);
+                cantidad_grupos += 1;
+            } else {
+                grupos.push(None);
+            }
+        }
+        self.presupuesto_personas.iter()
+            .enumerate()
+            .map(|(i, presupuesto)| {
+                let llegada_ms = llegada as u64;
+                if let Some(intervalo) = self.intervalo_llegadas {
+                    let u: f64 = rng.gen();
+                    llegada += -(1.0 - u).ln() * intervalo as f64;
+                }
+                let grupo = grupos.get(i).copied().flatten();
+                let arquetipo = match anterior {
+                    Some((anterior, arquetipo)) if grupo.is_some() && anterior == grupo => arquetipo,
+                    _ => self.mezcla_visitantes.as_ref().map(|mezcla| {
+                        let nombre = elegir_de_mezcla(mezcla, &mut rng_arquetipos);
+                        self.arquetipos.iter().find(|a| a.nombre == nombre).expect("arquetipo validado al resolver")
+                    }),
+                };
+                anterior = Some((grupo, arquetipo));
+                ConfiguracionPersona {
+                    nombre: Self::nombre(&self.nombres_personas, i),
+                    presupuesto: *presupuesto,
+                    llegada_ms,
+                    tolerancia_fila: self.tolerancia_personas.as_ref()
+                        .and_then(|t| t[i])
+                        .or_else(|| arquetipo.and_then(|a| a.paciencia)),
+                    grupo,
+                    atributos: self.atributos_personas.generar(&mut rng_atributos),
+                    arquetipo: arquetipo.cloned(),
+                    estadia_maxima_ms: [arquetipo.and_then(|a| a.tiempo_maximo_ms), self.estadia_maxima.map(u64::from)]
+                        .iter()
+                        .flatten()
+                        .min()
+                        .copied(),
+                    cansancio: self.cansancio_maximo.map(|maximo| Cansancio {
+                        maximo,
+                        descanso_ms: self.descanso as u64,
+                        probabilidad_irse: self.probabilidad_irse_cansado,
+                    }),
+                    satisfaccion_minima: self.satisfaccion_minima,
+                }
+            })
+            .collect()
     }
 
-    fn igualar_arrays(base: &[u32], dest: &mut Option<Vec<u32>>, defval: u32, nombre_base: &str, nombre_dest: &str) -> Result<Vec<u32>, String> {
-        if let Some(d) = dest.take() {
-            if base.len() != d.len() {
+    /// Verifica que los grupos tengan personas suficientes y que cada
+    /// uno entre en el parque. Lo hace `resolver`, pero hay que repetirlo
+    /// si después se cambia la capacidad del parque.
+    pub fn validar_grupos(&self) -> Result<(), String> {
+        if let Some(grupos) = &self.grupos {
+            let en_grupo: u32 = grupos.iter().sum();
+            if en_grupo as usize > self.presupuesto_personas.len() {
                 return Err(format!(
-                    "Los parámetros {} y {} deben ser arreglos del mismo tamaño ({} != {})",
-                    nombre_base, nombre_dest, base.len(), d.len()
+                    "[--grupos] Los grupos tienen {} personas pero hay {}",
+                    en_grupo, self.presupuesto_personas.len()
+                ));
+            }
+            if let Some(grande) = grupos.iter().find(|g| **g > self.capacidad_parque) {
+                return Err(format!(
+                    "[--grupos] Un grupo de {} personas no entra en un parque de capacidad {}",
+                    grande, self.capacidad_parque
                 ));
             }
-            Ok(d)
-        } else {
-            Ok(vec![defval; base.len()])
         }
+        Ok(())
+    }
+
+    /// Cantidad de integrantes del grupo más grande, 1 si no hay grupos.
+    pub fn mayor_grupo(&self) -> u32 {
+        self.grupos.iter().flatten().copied().max().unwrap_or(1).max(1)
+    }
+
+    /// Los nombres no pueden repetirse ni confundirse con otra etiqueta
+    /// del registro.
+    fn validar_nombres(opcion: &str, nombres: &Option<Vec<String>>) -> Result<(), String> {
+        let mut vistos = HashSet::new();
+        for nombre in nombres.iter().flatten().filter(|nombre| !nombre.is_empty()) {
+            let reservado = ["ADMIN", "PARQUE"].contains(&nombre.as_str())
+                || ["JUEGO ", "PERSONA ", "GRUPO "].iter().any(|prefijo| nombre.strip_prefix(prefijo)
+                    .is_some_and(|resto| resto.parse::<usize>().is_ok()));
+            if reservado || nombre.contains('|') {
+                return Err(format!("[{}] El nombre '{}' está reservado para el registro", opcion, nombre));
+            }
+            if !vistos.insert(nombre) {
+                return Err(format!("[{}] El nombre '{}' está repetido", opcion, nombre));
+            }
+        }
+        Ok(())
+    }
+
+    fn nombre(nombres: &Option<Vec<String>>, i: usize) -> Option<String> {
+        nombres.as_ref()
+            .and_then(|nombres| nombres.get(i))
+            .filter(|nombre| !nombre.is_empty())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapa::Lugar;
+
+    /// Ruta de un archivo de configuración de prueba, distinta para cada
+    /// test y cada proceso para que no se pisen al correr en paralelo.
+    fn ruta_temporal() -> std::path::PathBuf {
+        static CONTADOR: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let numero = CONTADOR.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        std::env::temp_dir().join(format!("parque_oxidado_test_{}_{}.toml", std::process::id(), numero))
+    }
+
+    fn parsear(argv: &[&str]) -> Result<Args, String> {
+        let argv: Vec<String> = argv.iter().map(|a| a.to_string()).collect();
+        match parse_args_desde(&argv) {
+            ParseArgsResult::Ok(args) => Ok(*args),
+            ParseArgsResult::MostrarAyuda => Err("ayuda".into()),
+            ParseArgsResult::Error(e) => Err(e),
+        }
+    }
+
+    #[test]
+    fn listas_aleatorias_reproducibles_con_la_semilla() {
+        let a = parsear(&["--personas=20:10:50", "--semilla=3"]).unwrap();
+        let b = parsear(&["--semilla=3", "--personas=20:10:50"]).unwrap();
+        assert_eq!(a.presupuesto_personas, b.presupuesto_personas);
+    }
+
+    #[test]
+    fn completa_los_parametros_de_juegos_no_especificados() {
+        // sólo con la duración, el costo y la capacidad son los originales
+        let args = parsear(&["--duracion-juegos=30,40"]).unwrap();
+        assert_eq!(args.costo_juegos, Some(vec![25, 25]));
+        assert_eq!(args.capacidad_juegos, Some(vec![25, 25]));
+        let args = parsear(&["--duracion-juegos=30,40", "--capacidad-juegos=3,4"]).unwrap();
+        assert_eq!(args.costo_juegos, Some(vec![10, 10]));
+        let args = parsear(&["--unidades-juegos=1,2"]).unwrap();
+        assert_eq!((args.costo_juegos, args.capacidad_juegos), (Some(vec![10, 10]), Some(vec![2, 2])));
+        assert!(parsear(&["--duracion-juegos=30,40", "--costo-juegos=1,2,3"]).is_err());
+    }
+
+    #[test]
+    fn las_opciones_pisan_el_archivo_de_configuracion() {
+        let ruta = ruta_temporal();
+        std::fs::write(&ruta, "capacidad = 3\nsemilla = 9\n\n[[juegos]]\nnombre = \"Carrusel\"\nprecio = 5\n\n[[juegos]]\nprecio = 8\ncapacidad = 4\n\n[[visitantes]]\ncantidad = 4\npresupuesto = 20\n").unwrap();
+        let config = format!("--config={}", ruta.display());
+
+        let args = parsear(&[&config]).unwrap();
+        assert_eq!(args.capacidad_parque, 3);
+        assert_eq!(args.semilla, 9);
+        assert_eq!(args.presupuesto_personas, vec![20; 4]);
+        assert_eq!(args.capacidad_juegos, Some(vec![2, 4]));
+        assert_eq!(args.nombres_juegos, Some(vec!["Carrusel".to_string(), String::new()]));
+        let juegos = args.juegos().unwrap();
+        assert_eq!(juegos[0].nombre.as_deref(), Some("Carrusel"));
+        assert_eq!(juegos[1].nombre, None);
+
+        let args = parsear(&["--capacidad=7", &config, "--costo-juegos=1,2", "--semilla=5"]).unwrap();
+        assert_eq!(args.capacidad_parque, 7);
+        assert_eq!(args.semilla, 5);
+        assert_eq!(args.costo_juegos, Some(vec![1, 2]));
+        assert_eq!(args.capacidad_juegos, Some(vec![2, 4]));
+
+        assert!(parsear(&[&config, "--costo-juegos=1,2,3"]).is_err());
+        std::fs::remove_file(ruta).unwrap();
+    }
+
+    #[test]
+    fn filas_individuales() {
+        let args = parsear(&["--capacidad-juegos=2,3"]).unwrap();
+        assert!(args.juegos().unwrap().iter().all(|j| !j.fila_individual));
+
+        let args = parsear(&["--capacidad-juegos=2,3", "--filas-individuales"]).unwrap();
+        assert!(args.juegos().unwrap().iter().all(|j| j.fila_individual));
+        assert!(args.as_str().contains("--filas-individuales"));
+
+        let ruta = ruta_temporal();
+        std::fs::write(&ruta, "[[juegos]]\nprecio = 5\n\n[[juegos]]\nprecio = 8\nfila_individual = true\n").unwrap();
+        let args = parsear(&[&format!("--config={}", ruta.display())]).unwrap();
+        let filas: Vec<bool> = args.juegos().unwrap().iter().map(|j| j.fila_individual).collect();
+        assert_eq!(filas, vec![false, true]);
+        std::fs::remove_file(ruta).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn zonas_y_caminos() {
+        let sin = parsear(&["--capacidad-juegos=2,3"]).unwrap();
+        assert!(sin.mapa().unwrap().is_none());
+        let args = parsear(&["--capacidad-juegos=2,3", "--caminata=40"]).unwrap();
+        let mapa = args.mapa().unwrap().unwrap();
+        assert_eq!(mapa.distancia(Lugar::Juego(0), Lugar::Juego(1)), 40);
+        assert!(args.as_str().contains("--caminata=40"));
+        assert!(parsear(&["--caminata=0"]).is_err());
+
+        let ruta = ruta_temporal();
+        let escribir = |texto: &str| std::fs::write(&ruta, texto).unwrap();
+        let config = format!("--config={}", ruta.display());
+        let juegos = "[[juegos]]\nnombre = \"Carrusel\"\nprecio = 5\n\n[[juegos]]\nprecio = 8\nzona = \"Extrema\"\n\n\
+                      [[zonas]]\nnombre = \"Extrema\"\ncapacidad = 4\n\n[[puestos]]\nnombre = \"Helados\"\nzona = \"Extrema\"\n\n\
+                      [[espectaculos]]\nnombre = \"Magia\"\nprecio = 3\ncapacidad = 10\nduracion = 200\nhorarios = [900, 300]\nzona = \"Extrema\"\n\n";
+        escribir(&format!("caminata = 30\n\n{}[[caminos]]\ndesde = \"entrada\"\nhasta = \"Carrusel\"\nms = 10\n\n\
+                           [[caminos]]\ndesde = \"Helados\"\nhasta = \"Carrusel\"\nms = 5\n", juegos));
+        let args = parsear(&[&config]).unwrap();
+        assert_eq!(args.zona_juegos, Some(vec![None, Some(0)]));
+        let mapa = args.mapa().unwrap().unwrap();
+        assert_eq!(mapa.distancia(Lugar::Entrada, Lugar::Puesto(0)), 15);
+        assert_eq!(mapa.distancia(Lugar::Entrada, Lugar::Juego(1)), 30);
+        assert_eq!(mapa.zona(Lugar::Puesto(0)), Some(0));
+        assert_eq!(mapa.zona(Lugar::Espectaculo(0)), Some(0));
+        assert_eq!(mapa.distancia(Lugar::Espectaculo(0), Lugar::Juego(0)), 30);
+        assert_eq!(args.espectaculos[0].horarios_ms, vec![300, 900]);
+        assert_eq!(args.espectaculos[0].interes, INTERES_ESPECTACULO_POR_DEFECTO);
+
+        // sin caminata todos los lugares necesitan un camino
+        escribir(&format!("{}[[caminos]]\ndesde = \"entrada\"\nhasta = \"Carrusel\"\nms = 10\n", juegos));
+        let error = parsear(&[&config]).unwrap().mapa().unwrap_err();
+        assert!(error.contains("No hay ningún camino"), "{}", error);
+        escribir(&format!("{}[[caminos]]\ndesde = \"entrada\"\nhasta = \"Tren\"\nms = 10\n", juegos));
+        assert!(parsear(&[&config]).unwrap_err().contains("Tren"));
+        escribir("[[juegos]]\nprecio = 5\nzona = \"Norte\"\n");
+        assert!(parsear(&[&config]).unwrap_err().contains("Norte"));
+        escribir("[[espectaculos]]\nnombre = \"Magia\"\nprecio = 3\ncapacidad = 10\nduracion = 200\nhorarios = []\n");
+        assert!(parsear(&[&config]).unwrap_err().contains("no tiene horarios"));
+        std::fs::remove_file(ruta).unwrap();
+    }
+
+    #[test]
+    fn restricciones_de_los_juegos() {
+        let args = parsear(&[
+            "--altura-minima-juegos=120,,90", "--altura-maxima-juegos=,,140",
+            "--edad-minima-juegos=,12,", "--accesibles-juegos=no,si,si",
+        ]).unwrap();
+        let juegos = args.juegos().unwrap();
+        assert_eq!(juegos.len(), 3);
+        assert_eq!(juegos[0].restricciones.altura_minima_cm, Some(120));
+        assert!(!juegos[0].restricciones.accesible);
+        assert_eq!(juegos[1].restricciones.edad_minima, Some(12));
+        assert_eq!(juegos[2].restricciones.altura_maxima_cm, Some(140));
+        let linea = args.as_str();
+        assert!(linea.contains("--altura-minima-juegos=120,,90 --altura-maxima-juegos=,,140"), "{}", linea);
+        assert!(linea.contains("--accesibles-juegos=no,si,si"), "{}", linea);
+
+        assert!(!parsear(&["--costo-juegos=5,5"]).unwrap().as_str().contains("altura"));
+        assert!(parsear(&["--altura-minima-juegos=150", "--altura-maxima-juegos=140"]).is_err());
+        assert!(parsear(&["--altura-minima-juegos=120", "--costo-juegos=5,5"]).is_err());
+        assert!(parsear(&["--accesibles-juegos=tal vez"]).is_err());
+    }
+
+    #[test]
+    fn clima_y_juegos_cubiertos() {
+        let args = parsear(&["--costo-juegos=5,5", "--cubiertos-juegos=no,si", "--clima=4000:tormenta,9000:soleado"]).unwrap();
+        let juegos = args.juegos().unwrap();
+        assert!(!juegos[0].cubierto && juegos[1].cubierto);
+        assert_eq!(args.pronostico().unwrap(), vec![(0, Clima::Soleado), (4000, Clima::Tormenta), (9000, Clima::Soleado)]);
+        let linea = args.as_str();
+        assert!(linea.contains("--cubiertos-juegos=no,si"), "{}", linea);
+        assert!(linea.contains("--clima=0:soleado,4000:tormenta,9000:soleado"), "{}", linea);
+
+        let aleatorio = parsear(&["--clima=aleatorio", "--cierre=30000", "--semilla=5"]).unwrap();
+        let pronostico = aleatorio.pronostico().unwrap();
+        assert_eq!(pronostico, aleatorio.pronostico().unwrap());
+        assert!(pronostico.iter().all(|(ms, _)| *ms < 30000));
+        assert!(aleatorio.as_str().contains("--clima=aleatorio"));
+
+        let sin_clima = parsear(&["--costo-juegos=5"]).unwrap();
+        assert!(sin_clima.pronostico().is_none());
+        assert!(!sin_clima.juegos().unwrap()[0].cubierto);
+        assert!(parsear(&["--clima=0:granizo"]).is_err());
+        assert!(parsear(&["--cubiertos-juegos=si", "--costo-juegos=5,5"]).is_err());
+    }
+
+    #[test]
+    fn atributos_de_las_personas() {
+        let args = parsear(&["--personas=50:40", "--edades=0..10", "--movilidad-reducida=1", "--semilla=3"]).unwrap();
+        let personas = args.personas();
+        assert!(personas.iter().all(|p| p.atributos.edad <= 10 && p.atributos.movilidad_reducida));
+        assert!(args.as_str().contains("--edades=0..10 --movilidad-reducida=1"));
+        // los atributos no cambian las llegadas
+        let sin = parsear(&["--personas=50:40", "--semilla=3", "--intervalo-llegadas=10"]).unwrap();
+        let con = parsear(&["--personas=50:40", "--semilla=3", "--intervalo-llegadas=10", "--edades=0..10"]).unwrap();
+        let llegadas = |args: &Args| args.personas().iter().map(|p| p.llegada_ms).collect::<Vec<_>>();
+        assert_eq!(llegadas(&sin), llegadas(&con));
+
+        assert!(parsear(&["--edades=10..5"]).is_err());
+        assert!(parsear(&["--edades=10"]).is_err());
+        assert!(parsear(&["--movilidad-reducida=1.5"]).is_err());
+    }
+
+    #[test]
+    fn mezcla_de_visitantes() {
+        let args = parsear(&[
+            "--personas=60:40", "--semilla=3", "--mezcla-visitantes=aventurero:1,familia:1",
+            "--intensidad-juegos=1,5",
+        ]).unwrap();
+        assert_eq!(args.juegos().unwrap()[1].intensidad, 5);
+        let personas = args.personas();
+        let nombres: Vec<&str> = personas.iter().map(|p| p.arquetipo.as_ref().unwrap().nombre.as_str()).collect();
+        assert!(nombres.contains(&"aventurero") && nombres.contains(&"familia"));
+        // sin tolerancia propia, esperan lo que les permite su paciencia
+        assert!(personas.iter().all(|p| p.tolerancia_fila == p.arquetipo.as_ref().unwrap().paciencia));
+        let linea = args.as_str();
+        assert!(linea.contains("--mezcla-visitantes=aventurero:1,familia:1"), "{}", linea);
+        assert!(linea.contains("--intensidad-juegos=1,5"), "{}", linea);
+
+        // los integrantes de un grupo comparten el arquetipo
+        let args = parsear(&["--personas=60:40", "--grupos=3,3,3", "--mezcla-visitantes=aventurero:1,casual:1"]).unwrap();
+        for grupo in args.personas()[..9].chunks(3) {
+            assert!(grupo.iter().all(|p| p.arquetipo == grupo[0].arquetipo));
+        }
+
+        assert!(parsear(&[]).unwrap().personas().iter().all(|p| p.arquetipo.is_none()));
+        assert!(parsear(&["--mezcla-visitantes=turista:1"]).is_err());
+        assert!(parsear(&["--intensidad-juegos=6"]).is_err());
+        assert!(parsear(&["--intensidad-juegos=0"]).is_err());
+    }
+
+    #[test]
+    fn estadia_y_cansancio() {
+        let args = parsear(&[
+            "--personas=50:4", "--mezcla-visitantes=casual:1", "--estadia-maxima=20000",
+            "--cansancio-maximo=10", "--irse-cansado=0.5", "--satisfaccion-minima=-5", "--cierre=3000",
+        ]).unwrap();
+        let persona = &args.personas()[0];
+        // la estadía del casual es más corta
+        assert_eq!(persona.estadia_maxima_ms, Some(10_000));
+        let cansancio = persona.cansancio.unwrap();
+        assert_eq!((cansancio.maximo, cansancio.descanso_ms, cansancio.probabilidad_irse), (10, 100, 0.5));
+        assert_eq!(persona.satisfaccion_minima, Some(-5.0));
+        let linea = args.as_str();
+        assert!(linea.contains("--estadia-maxima=20000 --cansancio-maximo=10 --irse-cansado=0.5 --satisfaccion-minima=-5 --cierre=3000"), "{}", linea);
+
+        let sin = parsear(&["--personas=50:4"]).unwrap();
+        assert!(sin.personas().iter().all(|p| p.cansancio.is_none() && p.estadia_maxima_ms.is_none()));
+        assert!(parsear(&["--irse-cansado=2"]).is_err());
+        assert!(parsear(&["--satisfaccion-minima=mucha"]).is_err());
+        assert!(parsear(&["--cierre=0"]).is_err());
+    }
+
+    #[test]
+    fn nombres_de_juegos_y_personas() {
+        let args = parsear(&["--personas=10,20,30", "--nombres-personas=Ana,,Bruno", "--nombres-juegos=Montaña Rusa,Carrusel"]).unwrap();
+        let personas = args.personas();
+        assert_eq!(personas[0].nombre.as_deref(), Some("Ana"));
+        assert_eq!(personas[1].nombre, None);
+        assert_eq!(personas[2].presupuesto, 30);
+        assert_eq!(args.juegos().unwrap()[0].nombre.as_deref(), Some("Montaña Rusa"));
+        assert_eq!(args.costo_juegos, Some(vec![10, 10]));
+
+        assert!(parsear(&["--personas=10,20", "--nombres-personas=Ana"]).is_err());
+        // los nombres repetidos o reservados confundirían el registro
+        assert!(parsear(&["--personas=10,20", "--nombres-personas=Ana,Ana"]).is_err());
+        assert!(parsear(&["--nombres-juegos=Carrusel,,Carrusel"]).is_err());
+        assert!(parsear(&["--personas=10,20", "--nombres-personas=ADMIN,"]).is_err());
+        assert!(parsear(&["--personas=10,20", "--nombres-personas=JUEGO 1,"]).is_err());
+        assert!(parsear(&["--nombres-juegos=Pista|2"]).is_err());
+
+        // el valor puede tener `=`
+        let args = parsear(&["--nombres-juegos=E=mc²,Carrusel"]).unwrap();
+        assert_eq!(args.juegos().unwrap()[0].nombre.as_deref(), Some("E=mc²"));
+        assert!(parsear(&["--personas"]).is_err());
+    }
+
+    #[test]
+    fn unidades_de_juegos() {
+        let args = parsear(&["--costo-juegos=5,10"]).unwrap();
+        assert!(args.juegos().unwrap().iter().all(|j| j.unidades == 1));
+
+        let args = parsear(&["--unidades-juegos=3,1"]).unwrap();
+        assert_eq!(args.juegos().unwrap()[0].unidades, 3);
+        assert!(args.as_str().contains("--unidades-juegos=3,1"));
+        assert!(parsear(&["--unidades-juegos=3,1", "--costo-juegos=1,2,3"]).is_err());
+        assert!(parsear(&["--unidades-juegos=0,1"]).is_err());
+    }
+
+    #[test]
+    fn tiempos_de_carga_y_descarga() {
+        let args = parsear(&["--costo-juegos=5,10"]).unwrap();
+        let juegos = args.juegos().unwrap();
+        assert!(juegos.iter().all(|j| j.carga_ms == 0 && j.descarga_ms == 0));
+        assert!(!args.as_str().contains("--carga-juegos"));
+
+        let args = parsear(&["--carga-juegos=3,1", "--descarga-juegos=4,4"]).unwrap();
+        let juegos = args.juegos().unwrap();
+        assert_eq!((juegos[0].carga_ms, juegos[0].descarga_ms), (3, 4));
+        assert!(args.as_str().contains("--carga-juegos=3,1 --descarga-juegos=4,4"));
+
+        // con algunos juegos instantáneos la línea de comandos se vuelve a
+        // poder leer
+        let args = parsear(&["--carga-juegos=0,5", "--descarga-juegos=2,0"]).unwrap();
+        let juegos = args.juegos().unwrap();
+        assert_eq!((juegos[0].carga_ms, juegos[1].carga_ms), (0, 5));
+        let linea = args.as_str();
+        assert!(linea.contains("--carga-juegos=0,5 --descarga-juegos=2,0"), "{}", linea);
+        let argv: Vec<&str> = linea.split_whitespace().filter(|arg| arg.starts_with("--")).collect();
+        let releidos = parsear(&argv).unwrap();
+        assert_eq!(releidos.carga_juegos, args.carga_juegos);
+        assert_eq!(releidos.descarga_juegos, args.descarga_juegos);
+
+        // no se confunden con los rangos de los presupuestos
+        assert!(parsear(&["--descarga-juegos=2:4"]).is_err());
+    }
+
+    #[test]
+    fn tolerancia_de_las_personas() {
+        let args = parsear(&["--personas=10,20,30"]).unwrap();
+        assert!(args.personas().iter().all(|p| p.tolerancia_fila.is_none()));
+
+        let args = parsear(&["--personas=10,20,30", "--tolerancia-personas=5,,0"]).unwrap();
+        let tolerancias: Vec<_> = args.personas().iter().map(|p| p.tolerancia_fila).collect();
+        assert_eq!(tolerancias, vec![Some(5), None, Some(0)]);
+        assert!(args.as_str().contains("--tolerancia-personas=5,,0"));
+        assert!(parsear(&["--personas=10,20", "--tolerancia-personas=5"]).is_err());
+        assert!(parsear(&["--personas=10", "--tolerancia-personas=x"]).is_err());
+    }
+
+    #[test]
+    fn grupos_de_visitantes() {
+        let args = parsear(&["--personas=10,20,30,40,50,60", "--grupos=2,1,3"]).unwrap();
+        let grupos: Vec<_> = args.personas().iter().map(|p| p.grupo).collect();
+        assert_eq!(grupos, vec![Some(0), Some(0), None, Some(1), Some(1), Some(1)]);
+        assert!(args.as_str().contains("--grupos=2,1,3"));
+        assert!(!args.as_str().contains("--presupuesto-compartido"));
+
+        let args = parsear(&["--personas=10,20,30", "--grupos=2", "--presupuesto-compartido"]).unwrap();
+        assert_eq!(args.personas()[2].grupo, None);
+        assert!(args.presupuesto_compartido);
+        assert!(parsear(&["--personas=10,20", "--grupos=3"]).is_err());
+        assert!(parsear(&["--personas=10,20,30", "--grupos=3", "--capacidad=2"]).is_err());
+    }
+
+    #[test]
+    fn intervalo_de_llegadas() {
+        let args = parsear(&["--personas=50:10", "--semilla=4"]).unwrap();
+        assert!(args.personas().iter().all(|p| p.llegada_ms == 0));
+
+        let args = parsear(&["--personas=50:10", "--semilla=4", "--intervalo-llegadas=100"]).unwrap();
+        let llegadas: Vec<u64> = args.personas().iter().map(|p| p.llegada_ms).collect();
+        assert_eq!(llegadas[0], 0);
+        assert!(llegadas.windows(2).all(|w| w[0] <= w[1]));
+        // 49 intervalos de 100 ms en promedio
+        assert!((2000..8000).contains(&llegadas[49]), "{:?}", llegadas);
+        assert_eq!(llegadas, args.personas().iter().map(|p| p.llegada_ms).collect::<Vec<u64>>());
+    }
+}
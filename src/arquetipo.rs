@@ -0,0 +1,250 @@
+//! Arquetipos de visitantes.
+//!
+//! Un arquetipo es un perfil de comportamiento: qué intensidad de juegos
+//! prefiere, cuánta fila está dispuesto a hacer, cuánto gasta en comida,
+//! cuánto tiempo se queda y cómo elige el próximo juego (al azar, el de
+//! la fila más corta, el más barato o el más cercano). Hay cuatro
+//! predefinidos (`aventurero`, `familia`, `ahorrativo` y `casual`) que
+//! se pueden modificar, o agregar otros, desde el archivo de
+//! configuración.
+
+use std::sync::Arc;
+
+use rand::{Rng, prelude::StdRng};
+use serde::Deserialize;
+
+use crate::juego::{INTENSIDAD_MAXIMA, Juego};
+
+/// Cómo elige una persona el próximo juego entre los que puede pagar y
+/// a los que puede subir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Estrategia {
+    /// Cualquiera, con la misma probabilidad.
+    Aleatoria,
+    /// Al azar, con más probabilidad los de la intensidad preferida.
+    Preferida,
+    /// El de la fila más corta.
+    FilaMasCorta,
+    /// El más barato.
+    MasBarato,
+    /// El más cercano a donde está, según el mapa del parque.
+    MasCercano,
+}
+
+/// Perfil de comportamiento de un tipo de visitante.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arquetipo {
+    pub nombre: String,
+    /// Intensidad de los juegos que prefiere, de 1 a 5. Sin preferencia
+    /// todos los juegos le dan igual.
+    pub intensidad_preferida: Option<u32>,
+    /// No sube a juegos más intensos que esta.
+    pub intensidad_maxima: Option<u32>,
+    /// Máxima cantidad de personas (o grupos) en una fila con la que
+    /// está dispuesta a esperar: en la fila de entrada desiste, y entre
+    /// los juegos evita los de filas más largas si puede.
+    pub paciencia: Option<u32>,
+    /// Probabilidad de comprar comida después de cada juego, y lo que
+    /// gasta cada vez.
+    pub probabilidad_comida: f64,
+    pub gasto_comida: u32,
+    /// Tiempo máximo que se queda en el parque, en milisegundos
+    /// simulados desde que entra.
+    pub tiempo_maximo_ms: Option<u64>,
+    pub estrategia: Estrategia,
+}
+
+impl Arquetipo {
+    /// Arquetipo sin preferencias, para los que se definen desde cero en
+    /// el archivo de configuración.
+    pub fn neutro(nombre: &str) -> Self {
+        Self {
+            nombre: nombre.into(),
+            intensidad_preferida: None,
+            intensidad_maxima: None,
+            paciencia: None,
+            probabilidad_comida: 0.0,
+            gasto_comida: 0,
+            tiempo_maximo_ms: None,
+            estrategia: Estrategia::Aleatoria,
+        }
+    }
+
+    pub fn predefinidos() -> Vec<Arquetipo> {
+        vec![
+            // busca los juegos más intensos y no le importa la fila
+            Arquetipo {
+                intensidad_preferida: Some(INTENSIDAD_MAXIMA),
+                paciencia: Some(30),
+                probabilidad_comida: 0.2,
+                gasto_comida: 5,
+                estrategia: Estrategia::Preferida,
+                ..Self::neutro("aventurero")
+            },
+            // juegos tranquilos, poca fila, come seguido y se va temprano
+            Arquetipo {
+                intensidad_preferida: Some(1),
+                intensidad_maxima: Some(3),
+                paciencia: Some(10),
+                probabilidad_comida: 0.5,
+                gasto_comida: 8,
+                tiempo_maximo_ms: Some(20_000),
+                estrategia: Estrategia::FilaMasCorta,
+                ..Self::neutro("familia")
+            },
+            // lo más barato, sin gastar en comida
+            Arquetipo {
+                paciencia: Some(20),
+                estrategia: Estrategia::MasBarato,
+                ..Self::neutro("ahorrativo")
+            },
+            // da una vuelta sin apuro y se va
+            Arquetipo {
+                paciencia: Some(5),
+                probabilidad_comida: 0.3,
+                gasto_comida: 5,
+                tiempo_maximo_ms: Some(10_000),
+                ..Self::neutro("casual")
+            },
+        ]
+    }
+
+    pub fn puede_subir(&self, juego: &Juego) -> bool {
+        self.intensidad_maxima.is_none_or(|maxima| juego.intensidad() <= maxima)
+    }
+
+    /// Peso de un juego de `intensidad` para la elección: se divide a la
+    /// mitad por cada nivel de distancia a la intensidad preferida.
+    pub fn peso(&self, intensidad: u32) -> f64 {
+        match self.intensidad_preferida {
+            Some(preferida) => 0.5f64.powi(preferida.abs_diff(intensidad) as i32),
+            None => 1.0,
+        }
+    }
+
+    /// Elige uno de `juegos`, que no puede estar vacío, según la
+    /// estrategia y la `distancia` hasta cada uno. Si la paciencia
+    /// alcanza para alguno, se descartan los de filas más largas.
+    pub fn elegir(&self, juegos: &[Arc<Juego>], distancia: impl Fn(&Juego) -> u64, rng: &mut StdRng) -> Arc<Juego> {
+        let tolerables: Vec<&Arc<Juego>> = juegos.iter()
+            .filter(|juego| self.paciencia.is_none_or(|paciencia| juego.largo_fila() <= paciencia as usize))
+            .collect();
+        let mut candidatos = if tolerables.is_empty() { juegos.iter().collect() } else { tolerables };
+
+        match self.estrategia {
+            Estrategia::Aleatoria => return candidatos[rng.gen_range(0..candidatos.len())].clone(),
+            Estrategia::Preferida => {}
+            Estrategia::FilaMasCorta => {
+                // las filas cambian mientras se elige: se mira cada una
+                // una sola vez para no descartar a todos los candidatos
+                let largos: Vec<usize> = candidatos.iter().map(|juego| juego.largo_fila()).collect();
+                let minimo = largos.iter().copied().min().unwrap_or(0);
+                candidatos = candidatos.into_iter()
+                    .zip(largos)
+                    .filter(|(_, largo)| *largo == minimo)
+                    .map(|(juego, _)| juego)
+                    .collect();
+            },
+            Estrategia::MasBarato => {
+                let minimo = candidatos.iter().map(|juego| juego.precio).min().unwrap_or(0);
+                candidatos.retain(|juego| juego.precio == minimo);
+            },
+            Estrategia::MasCercano => {
+                let minimo = candidatos.iter().map(|juego| distancia(juego)).min().unwrap_or(0);
+                candidatos.retain(|juego| distancia(juego) == minimo);
+            },
+        }
+
+        // entre los empatados, al azar según la intensidad preferida
+        let pesos: Vec<f64> = candidatos.iter().map(|juego| self.peso(juego.intensidad())).collect();
+        let mut valor = rng.gen::<f64>() * pesos.iter().sum::<f64>();
+        for (juego, peso) in candidatos.iter().zip(&pesos) {
+            if valor < *peso {
+                return (*juego).clone();
+            }
+            valor -= peso;
+        }
+        candidatos[candidatos.len() - 1].clone()
+    }
+
+    /// Lo que gasta en comida después de un juego, si compra algo y le
+    /// alcanza el `presupuesto`.
+    pub fn comida(&self, presupuesto: u32, rng: &mut StdRng) -> Option<u32> {
+        if self.gasto_comida == 0 || self.gasto_comida > presupuesto {
+            return None;
+        }
+        Some(self.gasto_comida).filter(|_| rng.gen_bool(self.probabilidad_comida))
+    }
+}
+
+/// Mezcla de arquetipos de la población, como `aventurero:30,familia:50`:
+/// el nombre de cada arquetipo y su peso.
+pub fn parse_mezcla(data: &str) -> Result<Vec<(String, u32)>, String> {
+    let mezcla = data.split(',')
+        .map(|parte| {
+            let (nombre, peso) = parte.split_once(':')
+                .ok_or_else(|| format!("'{}' no tiene la forma <ARQUETIPO>:<PESO>", parte))?;
+            let peso = peso.trim().parse::<u32>()
+                .map_err(|_| format!("'{}' no es un número entero no negativo", peso))?;
+            Ok((nombre.trim().to_string(), peso))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if mezcla.iter().all(|(_, peso)| *peso == 0) {
+        return Err("La mezcla de visitantes debe tener algún peso mayor a cero".into());
+    }
+    Ok(mezcla)
+}
+
+/// Elige al azar un arquetipo de la `mezcla` según su peso.
+pub fn elegir_de_mezcla<'a>(mezcla: &'a [(String, u32)], rng: &mut StdRng) -> &'a str {
+    let total: u32 = mezcla.iter().map(|(_, peso)| peso).sum();
+    let mut valor = rng.gen_range(0..total);
+    for (nombre, peso) in mezcla {
+        if valor < *peso {
+            return nombre;
+        }
+        valor -= peso;
+    }
+    unreachable!("el valor es menor al total de los pesos")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn mezcla_de_visitantes() {
+        let mezcla = parse_mezcla("aventurero:30, familia:0,casual:70").unwrap();
+        assert_eq!(mezcla[0], ("aventurero".to_string(), 30));
+        assert_eq!(mezcla[1].1, 0);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let elegidos: Vec<&str> = (0..1000).map(|_| elegir_de_mezcla(&mezcla, &mut rng)).collect();
+        assert!(!elegidos.contains(&"familia"));
+        let aventureros = elegidos.iter().filter(|nombre| **nombre == "aventurero").count();
+        assert!((200..400).contains(&aventureros), "{}", aventureros);
+
+        assert!(parse_mezcla("aventurero").is_err());
+        assert!(parse_mezcla("aventurero:x").is_err());
+        assert!(parse_mezcla("aventurero:0").is_err());
+    }
+
+    #[test]
+    fn la_preferencia_baja_con_la_distancia_a_la_intensidad() {
+        let aventurero = Arquetipo::predefinidos().remove(0);
+        assert_eq!(aventurero.peso(5), 1.0);
+        assert_eq!(aventurero.peso(3), 0.25);
+        assert_eq!(Arquetipo::neutro("x").peso(1), 1.0);
+    }
+
+    #[test]
+    fn la_comida_depende_del_presupuesto() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let glotona = Arquetipo { probabilidad_comida: 1.0, gasto_comida: 8, ..Arquetipo::neutro("x") };
+        assert_eq!(glotona.comida(10, &mut rng), Some(8));
+        assert_eq!(glotona.comida(7, &mut rng), None);
+        assert_eq!(Arquetipo::neutro("x").comida(100, &mut rng), None);
+    }
+}
@@ -0,0 +1,240 @@
+//! Barrido de parámetros: corre un lote de simulaciones por cada
+//! combinación de los valores de los parámetros barridos y escribe una
+//! tabla CSV con las métricas de cada punto.
+//!
+//! ```bash
+//! $ ./parque-oxidado barrido --barrer costo-juegos=5..20:5 --barrer capacidad=5,10,20 --corridas=10 --salida=barrido.csv
+//! ```
+
+use std::fs;
+
+use crate::{
+    args::Args,
+    lote::{OpcionesLote, correr_corridas, parse_opciones, resumir_metricas},
+    simulacion::METRICAS,
+};
+
+/// Parámetros que se pueden barrer. Los de los juegos se aplican a
+/// todos los juegos por igual.
+const PARAMETROS: [&str; 6] = ["capacidad", "costo-juegos", "capacidad-juegos", "duracion-juegos", "unidades-juegos", "presupuesto"];
+
+/// Parámetro barrido y los valores que toma.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Barrer {
+    pub parametro: String,
+    pub valores: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpcionesBarrido {
+    pub barrer: Vec<Barrer>,
+    /// Archivo CSV de salida. Si no se especifica se imprime la tabla.
+    pub salida: Option<String>,
+    pub lote: OpcionesLote,
+}
+
+/// Separa las opciones del barrido (`--barrer`, `--salida` y las del
+/// lote) del resto de los argumentos, que describen el escenario.
+/// `--barrer` acepta el valor tanto con `=` como en el argumento
+/// siguiente.
+pub fn parse_opciones_barrido(argv: &[String]) -> Result<(OpcionesBarrido, Vec<String>), String> {
+    let mut barrer = vec![];
+    let mut salida = None;
+    let mut resto = vec![];
+    let mut argumentos = argv.iter();
+    while let Some(arg) = argumentos.next() {
+        let valor = if arg == "--barrer" {
+            Some(argumentos.next().ok_or("[--barrer] Falta el parámetro a barrer")?.as_str())
+        } else {
+            arg.strip_prefix("--barrer=")
+        };
+        if let Some(valor) = valor {
+            barrer.push(parse_barrer(valor).map_err(|e| format!("[--barrer] {}", e))?);
+        } else if let Some(ruta) = arg.strip_prefix("--salida=") {
+            salida = Some(ruta.to_string());
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+    if barrer.is_empty() {
+        return Err("[--barrer] Se debe indicar al menos un parámetro a barrer".into());
+    }
+    let (lote, resto) = parse_opciones(&resto)?;
+    Ok((OpcionesBarrido { barrer, salida, lote }, resto))
+}
+
+/// `<parámetro>=<valores>`, donde los valores son una lista `A,B,C` o
+/// un rango inclusivo `A..B` o `A..B:PASO`.
+fn parse_barrer(data: &str) -> Result<Barrer, String> {
+    let (parametro, valores) = data.split_once('=')
+        .ok_or_else(|| format!("'{}' no tiene el formato <parámetro>=<valores>", data))?;
+    if !PARAMETROS.contains(&parametro) {
+        return Err(format!(
+            "No se puede barrer '{}', los parámetros posibles son: {}",
+            parametro, PARAMETROS.join(", ")
+        ));
+    }
+
+    Ok(Barrer { parametro: parametro.into(), valores: parse_valores(valores)? })
+}
+
+/// Valores como lista `A,B,C` o como rango inclusivo `A..B` o
+/// `A..B:PASO`.
+pub fn parse_valores(valores: &str) -> Result<Vec<u32>, String> {
+    let natural = |s: &str| match s.trim().parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{}' no es un número natural", s)),
+    };
+    if let Some((desde, resto)) = valores.split_once("..") {
+        let (hasta, paso) = match resto.split_once(':') {
+            Some((hasta, paso)) => (natural(hasta)?, natural(paso)?),
+            None => (natural(resto)?, 1),
+        };
+        let desde = natural(desde)?;
+        if desde > hasta {
+            return Err(format!("Rango inválido ({} > {})", desde, hasta));
+        }
+        Ok((desde..=hasta).step_by(paso as usize).collect())
+    } else {
+        valores.split(',').map(natural).collect()
+    }
+}
+
+/// Todas las combinaciones de valores de los parámetros barridos, en
+/// el orden de `barrer` (el último parámetro varía más rápido).
+pub fn producto(barrer: &[Barrer]) -> Vec<Vec<u32>> {
+    barrer.iter().fold(vec![vec![]], |puntos, barrido| {
+        puntos.iter()
+            .flat_map(|punto| barrido.valores.iter().map(move |valor| {
+                let mut punto = punto.clone();
+                punto.push(*valor);
+                punto
+            }))
+            .collect()
+    })
+}
+
+/// Fija `parametro` en `valor`. Requiere que se haya llamado a
+/// `Args::resolver`.
+fn aplicar(args: &mut Args, parametro: &str, valor: u32) {
+    let fijar = |lista: &mut Option<Vec<u32>>| {
+        if let Some(lista) = lista {
+            lista.iter_mut().for_each(|v| *v = valor);
+        }
+    };
+    match parametro {
+        "capacidad" => args.capacidad_parque = valor,
+        "costo-juegos" => fijar(&mut args.costo_juegos),
+        "capacidad-juegos" => fijar(&mut args.capacidad_juegos),
+        "duracion-juegos" => fijar(&mut args.duracion_juegos),
+        "unidades-juegos" => fijar(&mut args.unidades_juegos),
+        "presupuesto" => args.presupuesto_personas.iter_mut().for_each(|p| *p = valor),
+        _ => unreachable!("parámetro validado en parse_barrer"),
+    }
+}
+
+/// Corre el barrido sobre el escenario `args` y devuelve la tabla CSV:
+/// una fila por punto con el valor de cada parámetro barrido y la
+/// media, el desvío y el intervalo de confianza de cada métrica.
+pub fn barrer(opciones: &OpcionesBarrido, args: &Args) -> Result<String, String> {
+    let mut lote = opciones.lote.clone();
+    lote.resolver(args.semilla);
+
+    let mut csv = opciones.barrer.iter()
+        .map(|b| b.parametro.clone())
+        .chain(std::iter::once("corridas".to_string()))
+        .chain(METRICAS.iter().flat_map(|m| {
+            ["media", "desvio", "ic95_inf", "ic95_sup"].iter().map(move |s| format!("{}_{}", m, s))
+        }))
+        .collect::<Vec<String>>()
+        .join(",");
+    csv.push('\n');
+
+    for punto in producto(&opciones.barrer) {
+        let mut args = args.clone();
+        args.rapido = true;
+        for (barrido, valor) in opciones.barrer.iter().zip(&punto) {
+            aplicar(&mut args, &barrido.parametro, *valor);
+        }
+        let resultados = correr_corridas(&args, lote.semillas(), lote.hilos)?;
+
+        let mut fila: Vec<String> = punto.iter().map(u32::to_string).collect();
+        fila.push(resultados.len().to_string());
+        for (_, resumen) in resumir_metricas(&resultados) {
+            fila.extend([resumen.media, resumen.desvio, resumen.ic95.0, resumen.ic95.1]
+                .iter()
+                .map(|v| format!("{:.3}", v)));
+        }
+        csv += &fila.join(",");
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+/// Corre el barrido y escribe la tabla en el archivo de salida o, si
+/// no se especificó, en la salida estándar.
+pub fn correr(opciones: OpcionesBarrido, args: &Args) -> Result<(), String> {
+    let csv = barrer(&opciones, args)?;
+    match &opciones.salida {
+        Some(ruta) => fs::write(ruta, csv).map_err(|e| format!("{}: {}", ruta, e)),
+        None => {
+            print!("{}", csv);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn interpreta_rangos_y_listas() {
+        let (opciones, resto) = parse_opciones_barrido(&argv(&[
+            "--barrer", "costo-juegos=5..20:5", "--barrer=capacidad=5,10,20", "--corridas=2", "--personas=3:10"
+        ])).unwrap();
+        assert_eq!(opciones.barrer[0].valores, vec![5, 10, 15, 20]);
+        assert_eq!(opciones.barrer[1].valores, vec![5, 10, 20]);
+        assert_eq!(opciones.lote.corridas, 2);
+        assert_eq!(resto, argv(&["--personas=3:10"]));
+
+        assert!(parse_opciones_barrido(&argv(&["--barrer", "color=1,2"])).is_err());
+        assert!(parse_opciones_barrido(&argv(&["--barrer", "capacidad=0,2"])).is_err());
+        assert!(parse_opciones_barrido(&argv(&["--corridas=2"])).is_err());
+    }
+
+    #[test]
+    fn producto_cartesiano() {
+        let barrer = [
+            Barrer { parametro: "capacidad".into(), valores: vec![1, 2] },
+            Barrer { parametro: "costo-juegos".into(), valores: vec![5, 10, 15] },
+        ];
+        let puntos = producto(&barrer);
+        assert_eq!(puntos.len(), 6);
+        assert_eq!(puntos[0], vec![1, 5]);
+        assert_eq!(puntos[5], vec![2, 15]);
+    }
+
+    #[test]
+    fn una_fila_por_punto() {
+        let (opciones, _) = parse_opciones_barrido(&argv(&[
+            "--barrer", "costo-juegos=10,20", "--semillas=1,2", "--hilos=2"
+        ])).unwrap();
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![40; 3];
+        args.costo_juegos = Some(vec![5, 5]);
+        args.resolver().unwrap();
+
+        let csv = barrer(&opciones, &args).unwrap();
+        let filas: Vec<&str> = csv.lines().collect();
+        assert_eq!(filas.len(), 3);
+        assert!(filas[0].starts_with("costo-juegos,corridas,caja_media,caja_desvio,"));
+        // cada persona gasta todo su presupuesto en juegos de $ 10 o $ 20
+        assert!(filas[1].starts_with("10,2,120.000,0.000,"), "{}", filas[1]);
+        assert!(filas[2].starts_with("20,2,120.000,0.000,"), "{}", filas[2]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Clima del parque a lo largo del día.
+//!
+//! El pronóstico es una lista de cambios de clima (soleado, lluvia o
+//! tormenta), cada uno desde un momento del reloj simulado. Se puede
+//! indicar a mano o generar al azar como una cadena de Markov que cambia
+//! cada `INTERVALO_CLIMA_MS`. Un hilo sigue el pronóstico: durante las
+//! tormentas cierra los juegos al aire libre, cuyas filas se dispersan, y
+//! los vuelve a abrir cuando pasan. Con lluvia o tormenta las personas
+//! prefieren los juegos cubiertos y algunas se van antes.
+
+use std::{fmt, time::{Duration, Instant}};
+
+use rand::{Rng, SeedableRng, prelude::StdRng};
+
+use crate::sync::{Condvar, Mutex};
+
+/// Cada cuánto puede cambiar el clima generado al azar, en milisegundos
+/// simulados.
+pub const INTERVALO_CLIMA_MS: u64 = 5000;
+/// Hasta cuándo se genera el clima al azar si el parque no cierra, en
+/// milisegundos simulados. Después queda el último.
+pub const HORIZONTE_CLIMA_MS: u64 = 60_000;
+/// Cuánto espera una persona a que reabra algún juego cuando todos los
+/// que puede elegir están cerrados, en milisegundos simulados.
+pub const ESPERA_REFUGIO_MS: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clima {
+    Soleado,
+    Lluvia,
+    Tormenta,
+}
+
+impl Clima {
+    /// Si las personas prefieren los juegos cubiertos.
+    pub fn desfavorable(self) -> bool {
+        self != Clima::Soleado
+    }
+
+    /// Probabilidad de que una persona se vaya del parque cada vez que
+    /// elige un juego con este clima.
+    pub fn probabilidad_irse(self) -> f64 {
+        match self {
+            Clima::Soleado => 0.0,
+            Clima::Lluvia => 0.02,
+            Clima::Tormenta => 0.1,
+        }
+    }
+
+    /// Clima del próximo intervalo, según el actual.
+    fn siguiente(self, rng: &mut StdRng) -> Clima {
+        let (soleado, lluvia) = match self {
+            Clima::Soleado => (0.8, 0.15),
+            Clima::Lluvia => (0.3, 0.5),
+            Clima::Tormenta => (0.2, 0.4),
+        };
+        let valor: f64 = rng.gen();
+        if valor < soleado {
+            Clima::Soleado
+        } else if valor < soleado + lluvia {
+            Clima::Lluvia
+        } else {
+            Clima::Tormenta
+        }
+    }
+}
+
+impl fmt::Display for Clima {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Clima::Soleado => write!(f, "soleado"),
+            Clima::Lluvia => write!(f, "lluvia"),
+            Clima::Tormenta => write!(f, "tormenta"),
+        }
+    }
+}
+
+/// Cómo se arma el pronóstico.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfiguracionClima {
+    /// Generado al azar a partir de la semilla.
+    Aleatorio,
+    /// Cambios de clima, cada uno desde un momento en milisegundos
+    /// simulados desde el inicio, en orden.
+    Fijo(Vec<(u64, Clima)>),
+}
+
+/// Pronóstico como `aleatorio` o como una lista de `<MS>:<CLIMA>`, por
+/// ejemplo `0:soleado,8000:tormenta,12000:lluvia`. Antes del primer
+/// cambio está soleado.
+pub fn parse_clima(data: &str) -> Result<ConfiguracionClima, String> {
+    if data.trim() == "aleatorio" {
+        return Ok(ConfiguracionClima::Aleatorio);
+    }
+    let mut cambios = data.split(',')
+        .map(|parte| {
+            let (ms, clima) = parte.split_once(':')
+                .ok_or_else(|| format!("'{}' no tiene la forma <MS>:<CLIMA>", parte))?;
+            let ms = ms.trim().parse::<u64>()
+                .map_err(|_| format!("'{}' no es un número entero no negativo", ms))?;
+            let clima = match clima.trim() {
+                "soleado" => Clima::Soleado,
+                "lluvia" => Clima::Lluvia,
+                "tormenta" => Clima::Tormenta,
+                otro => return Err(format!("'{}' no es un clima (soleado, lluvia o tormenta)", otro)),
+            };
+            Ok((ms, clima))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    cambios.sort_by_key(|(ms, _)| *ms);
+    if cambios.first().is_some_and(|(ms, _)| *ms > 0) {
+        cambios.insert(0, (0, Clima::Soleado));
+    }
+    Ok(ConfiguracionClima::Fijo(cambios))
+}
+
+/// Genera con `semilla` un pronóstico hasta `horizonte_ms`, empezando
+/// soleado.
+pub fn generar(semilla: u64, horizonte_ms: u64) -> Vec<(u64, Clima)> {
+    let mut rng = StdRng::seed_from_u64(semilla);
+    let mut cambios = vec![(0, Clima::Soleado)];
+    let mut ms = INTERVALO_CLIMA_MS;
+    while ms < horizonte_ms {
+        let (_, actual) = cambios[cambios.len() - 1];
+        let siguiente = actual.siguiente(&mut rng);
+        if siguiente != actual {
+            cambios.push((ms, siguiente));
+        }
+        ms += INTERVALO_CLIMA_MS;
+    }
+    cambios
+}
+
+/// Pronóstico de la simulación y clima actual, que actualiza el hilo que
+/// lo sigue.
+pub struct Pronostico {
+    cambios: Vec<(u64, Clima)>,
+    actual: Mutex<Clima>,
+    terminado: Mutex<bool>,
+    cv: Condvar,
+}
+
+impl Pronostico {
+    pub fn new(cambios: Vec<(u64, Clima)>) -> Self {
+        Self {
+            cambios,
+            actual: Mutex::new(Clima::Soleado),
+            terminado: Mutex::new(false),
+            cv: Condvar::new(),
+        }
+    }
+
+    pub fn cambios(&self) -> &[(u64, Clima)] {
+        &self.cambios
+    }
+
+    pub fn actual(&self) -> Clima {
+        *self.actual.lock().expect("poisoned")
+    }
+
+    pub fn cambiar(&self, clima: Clima) {
+        *self.actual.lock().expect("poisoned") = clima;
+    }
+
+    /// Espera `duracion` de tiempo real. Devuelve falso si se terminó
+    /// la simulación mientras tanto.
+    pub fn esperar(&self, duracion: Duration) -> bool {
+        let limite = Instant::now() + duracion;
+        let mut terminado = self.terminado.lock().expect("poisoned");
+        while !*terminado {
+            let ahora = Instant::now();
+            if ahora >= limite {
+                break;
+            }
+            terminado = self.cv.wait_timeout(terminado, limite - ahora).expect("poisoned").0;
+        }
+        !*terminado
+    }
+
+    /// Despierta al hilo que sigue el pronóstico para que termine.
+    pub fn terminar(&self) {
+        *self.terminado.lock().expect("poisoned") = true;
+        self.cv.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pronosticos() {
+        assert_eq!(parse_clima("aleatorio").unwrap(), ConfiguracionClima::Aleatorio);
+        assert_eq!(
+            parse_clima("8000:tormenta, 3000:lluvia").unwrap(),
+            ConfiguracionClima::Fijo(vec![(0, Clima::Soleado), (3000, Clima::Lluvia), (8000, Clima::Tormenta)])
+        );
+        assert!(parse_clima("0:nieve").unwrap_err().contains("nieve"));
+        assert!(parse_clima("tormenta").is_err());
+
+        let generado = generar(7, 100_000);
+        assert_eq!(generado, generar(7, 100_000));
+        assert_eq!(generado[0], (0, Clima::Soleado));
+        assert!(generado.windows(2).all(|par| par[0].0 < par[1].0 && par[0].1 != par[1].1));
+        assert!(generado.iter().all(|(ms, _)| ms % INTERVALO_CLIMA_MS == 0 && *ms < 100_000));
+    }
+}
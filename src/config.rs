@@ -0,0 +1,830 @@
+//! Archivo de configuración de escenarios completos (`--config`).
+//!
+//! Se aceptan archivos TOML y JSON (según la extensión) con la misma
+//! estructura:
+//!
+//! ```toml
+//! capacidad = 10
+//! semilla = 42
+//! edad_min = 5
+//! edad_max = 60
+//! movilidad_reducida = 0.02
+//! mezcla_visitantes = { aventurero = 30, familia = 50, estudiante = 20 }
+//! estadia_maxima = 30000
+//! cansancio_maximo = 15
+//! descanso = 200
+//! irse_cansado = 0.2
+//! satisfaccion_minima = -20
+//! cierre = 60000
+//! caminata = 40
+//! clima = "0:soleado,20000:tormenta,26000:lluvia,32000:soleado"
+//!
+//! [[zonas]]
+//! nombre = "Extrema"
+//! capacidad = 30
+//!
+//! [[puestos]]
+//! nombre = "Pochoclos"
+//! zona = "Extrema"
+//!
+//! [[caminos]]
+//! desde = "entrada"
+//! hasta = "Montaña Rusa"
+//! ms = 15
+//!
+//! [[caminos]]
+//! desde = "Montaña Rusa"
+//! hasta = "Pochoclos"
+//! ms = 5
+//!
+//! [[espectaculos]]
+//! nombre = "Show de Magia"
+//! precio = 5
+//! capacidad = 40
+//! duracion = 600
+//! horarios = [5000, 20000, 35000]
+//! anticipacion = 400
+//! interes = 0.5
+//! zona = "Extrema"
+//!
+//! [[arquetipos]]
+//! nombre = "familia"
+//! gasto_comida = 12
+//!
+//! [[arquetipos]]
+//! nombre = "estudiante"
+//! intensidad_preferida = 4
+//! paciencia = 15
+//! probabilidad_comida = 0.4
+//! gasto_comida = 3
+//! tiempo_maximo = 15000
+//! estrategia = "mas_barato"
+//!
+//! [[juegos]]
+//! nombre = "Montaña Rusa"
+//! precio = 15
+//! capacidad = 8
+//! duracion = 120
+//! unidades = 2
+//! carga = 5
+//! descarga = 3
+//! fila_individual = true
+//! altura_minima = 120
+//! edad_minima = 8
+//! accesible = false
+//! intensidad = 5
+//! zona = "Extrema"
+//! desperfectos = { probabilidad = 0.1, tiempo_maximo_arreglo = 50 }
+//!
+//! [[juegos]]
+//! nombre = "Casa del Terror"
+//! precio = 8
+//! cubierto = true
+//!
+//! [[visitantes]]
+//! nombre = "Escuela"
+//! cantidad = 20
+//! presupuesto = 40
+//! integrantes = 4
+//!
+//! [[visitantes]]
+//! cantidad = 10
+//! presupuesto_min = 10
+//! presupuesto_max = 60
+//! tolerancia = 5
+//! ```
+//!
+//! Los valores se validan al leer el archivo, de forma que los errores
+//! indiquen la línea donde se encuentran.
+
+use std::{collections::BTreeMap, convert::TryFrom, fmt, fs, path::Path};
+
+use serde::{Deserialize, Deserializer, de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor}};
+
+use crate::{arquetipo::Estrategia, clima::{ConfiguracionClima, parse_clima}, mapa::Lugar};
+
+/// Número natural (mayor a cero).
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "u32")]
+pub struct Natural(pub u32);
+
+impl TryFrom<u32> for Natural {
+    type Error = String;
+
+    fn try_from(valor: u32) -> Result<Self, Self::Error> {
+        if valor == 0 {
+            Err("'0' no es un número natural".into())
+        } else {
+            Ok(Natural(valor))
+        }
+    }
+}
+
+/// Probabilidad, entre 0 y 1.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "f64")]
+pub struct Probabilidad(pub f64);
+
+impl TryFrom<f64> for Probabilidad {
+    type Error = String;
+
+    fn try_from(valor: f64) -> Result<Self, Self::Error> {
+        if (0.0..=1.0).contains(&valor) {
+            Ok(Probabilidad(valor))
+        } else {
+            Err(format!("La probabilidad debe estar entre 0 y 1 ({})", valor))
+        }
+    }
+}
+
+/// Horarios de las funciones de un espectáculo, ordenados. Tiene que
+/// haber al menos uno.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<u32>")]
+pub struct Horarios(pub Vec<u32>);
+
+impl TryFrom<Vec<u32>> for Horarios {
+    type Error = String;
+
+    fn try_from(mut horarios: Vec<u32>) -> Result<Self, Self::Error> {
+        if horarios.is_empty() {
+            return Err("El espectáculo no tiene horarios".into());
+        }
+        horarios.sort_unstable();
+        Ok(Horarios(horarios))
+    }
+}
+
+/// Pronóstico del clima, en el mismo formato que `--clima`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pronostico(pub ConfiguracionClima);
+
+impl TryFrom<String> for Pronostico {
+    type Error = String;
+
+    fn try_from(valor: String) -> Result<Self, Self::Error> {
+        parse_clima(&valor).map(Pronostico)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Configuracion {
+    /// Cantidad de personas que puede haber dentro del parque
+    /// simultáneamente.
+    pub capacidad: Option<Natural>,
+    pub semilla: Option<u32>,
+    pub rapido: Option<bool>,
+    pub watchdog: Option<Natural>,
+    /// Tiempo promedio entre llegadas de visitantes, en milisegundos.
+    pub intervalo_llegadas: Option<Natural>,
+    /// Tiempo máximo que se queda cada visitante, en milisegundos.
+    pub estadia_maxima: Option<Natural>,
+    /// Suma de intensidades de los juegos a partir de la cual un
+    /// visitante está cansado, lo que descansa, en milisegundos, y la
+    /// probabilidad de que se vaya en lugar de descansar.
+    pub cansancio_maximo: Option<Natural>,
+    pub descanso: Option<Natural>,
+    pub irse_cansado: Option<Probabilidad>,
+    /// Satisfacción debajo de la cual un visitante se va.
+    pub satisfaccion_minima: Option<f64>,
+    /// Momento en que cierra el parque, en milisegundos desde el inicio.
+    pub cierre: Option<Natural>,
+    /// Tiempo de caminata entre dos lugares del parque que no tienen un
+    /// camino más corto, en milisegundos.
+    pub caminata: Option<Natural>,
+    /// Clima a lo largo del día: `aleatorio` o una lista de cambios.
+    pub clima: Option<Pronostico>,
+    /// Si las personas de la fila de un juego suben en cualquier orden.
+    pub filas_injustas: Option<bool>,
+    /// Si los juegos devuelven la entrada a quienes esperaron durante un
+    /// desperfecto.
+    pub reembolsar_desperfectos: Option<bool>,
+    /// Si los grupos de visitantes juntan el presupuesto de sus
+    /// integrantes.
+    pub presupuesto_compartido: Option<bool>,
+    /// Rango de edades de los visitantes, incluyendo ambos extremos.
+    pub edad_min: Option<u32>,
+    pub edad_max: Option<u32>,
+    /// Probabilidad de que un visitante tenga movilidad reducida.
+    pub movilidad_reducida: Option<Probabilidad>,
+    /// Peso de cada arquetipo en la población de visitantes.
+    pub mezcla_visitantes: Option<BTreeMap<String, u32>>,
+    /// Arquetipos nuevos, o cambios a los predefinidos.
+    #[serde(default)]
+    pub arquetipos: Vec<Arquetipo>,
+    #[serde(default)]
+    pub juegos: Vec<Juego>,
+    #[serde(default)]
+    pub visitantes: Vec<GrupoVisitantes>,
+    #[serde(default)]
+    pub zonas: Vec<Zona>,
+    #[serde(default)]
+    pub puestos: Vec<Puesto>,
+    #[serde(default)]
+    pub caminos: Vec<Camino>,
+    #[serde(default)]
+    pub espectaculos: Vec<Espectaculo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Juego {
+    pub nombre: Option<String>,
+    pub precio: Natural,
+    pub capacidad: Option<Natural>,
+    /// Duración de la vuelta, en milisegundos.
+    pub duracion: Option<Natural>,
+    /// Cantidad de unidades idénticas que cargan de la misma fila.
+    pub unidades: Option<Natural>,
+    /// Tiempo que tarda en subir cada persona, en milisegundos.
+    pub carga: Option<u32>,
+    /// Tiempo que tarda en bajar cada persona, en milisegundos.
+    pub descarga: Option<u32>,
+    /// Si tiene una fila individual para ocupar los lugares libres.
+    pub fila_individual: Option<bool>,
+    /// Altura mínima y máxima para subir, en centímetros.
+    pub altura_minima: Option<Natural>,
+    pub altura_maxima: Option<Natural>,
+    /// Edad mínima para subir, en años.
+    pub edad_minima: Option<Natural>,
+    /// Si pueden subir personas con movilidad reducida (por defecto sí).
+    pub accesible: Option<bool>,
+    /// Qué tan intenso es, de 1 a 5.
+    pub intensidad: Option<Natural>,
+    /// Nombre de la zona del parque en la que está.
+    pub zona: Option<String>,
+    /// Si está bajo techo y no cierra con tormenta (por defecto no).
+    pub cubierto: Option<bool>,
+    pub desperfectos: Option<Desperfectos>,
+}
+
+/// Zona del parque, con una capacidad máxima de personas al mismo
+/// tiempo.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Zona {
+    pub nombre: String,
+    pub capacidad: Option<Natural>,
+}
+
+/// Puesto de comida, opcionalmente dentro de una zona.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Puesto {
+    pub nombre: String,
+    pub zona: Option<String>,
+}
+
+/// Camino entre dos lugares del parque: `entrada` o el nombre de un
+/// juego, de un puesto o de un espectáculo.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Camino {
+    pub desde: String,
+    pub hasta: String,
+    /// Tiempo que lleva recorrerlo, en milisegundos.
+    pub ms: u32,
+}
+
+/// Espectáculo con funciones en horarios fijos.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Espectaculo {
+    pub nombre: String,
+    pub precio: u32,
+    /// Butacas de la sala.
+    pub capacidad: Natural,
+    /// Duración de cada función, en milisegundos.
+    pub duracion: Natural,
+    /// Momento en que empieza cada función, en milisegundos desde el
+    /// inicio.
+    pub horarios: Horarios,
+    /// Cuánto antes del inicio está dispuesto a esperar en la sala un
+    /// visitante, en milisegundos.
+    pub anticipacion: Option<u32>,
+    /// Probabilidad de que un visitante vaya a una función que está por
+    /// empezar.
+    pub interes: Option<Probabilidad>,
+    /// Nombre de la zona del parque en la que está.
+    pub zona: Option<String>,
+}
+
+/// Perfil de comportamiento de un tipo de visitante. Si tiene el nombre
+/// de uno predefinido sólo cambia los valores indicados; si no, los que
+/// no se indican quedan sin preferencia.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Arquetipo {
+    pub nombre: String,
+    pub intensidad_preferida: Option<Natural>,
+    pub intensidad_maxima: Option<Natural>,
+    /// Máxima cantidad de personas en una fila con la que espera.
+    pub paciencia: Option<u32>,
+    /// Probabilidad de comprar comida después de cada juego, y lo que
+    /// gasta cada vez.
+    pub probabilidad_comida: Option<Probabilidad>,
+    pub gasto_comida: Option<u32>,
+    /// Tiempo máximo en el parque, en milisegundos.
+    pub tiempo_maximo: Option<Natural>,
+    pub estrategia: Option<Estrategia>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, try_from = "DesperfectosArchivo")]
+pub struct Desperfectos {
+    /// Probabilidad de un desperfecto antes de cada vuelta, entre 0 y 1.
+    pub probabilidad: f64,
+    /// Tiempo máximo de arreglo, en milisegundos.
+    pub tiempo_maximo_arreglo: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DesperfectosArchivo {
+    probabilidad: f64,
+    tiempo_maximo_arreglo: Natural,
+}
+
+impl TryFrom<DesperfectosArchivo> for Desperfectos {
+    type Error = String;
+
+    fn try_from(archivo: DesperfectosArchivo) -> Result<Self, Self::Error> {
+        if !(0.0..=1.0).contains(&archivo.probabilidad) {
+            return Err(format!(
+                "La probabilidad de desperfectos debe estar entre 0 y 1 ({})",
+                archivo.probabilidad
+            ));
+        }
+        Ok(Desperfectos {
+            probabilidad: archivo.probabilidad,
+            tiempo_maximo_arreglo: archivo.tiempo_maximo_arreglo.0,
+        })
+    }
+}
+
+/// Grupo de visitantes con el mismo presupuesto (`presupuesto`) o con
+/// presupuestos aleatorios en el rango `[presupuesto_min, presupuesto_max)`.
+/// Si el grupo tiene nombre sus integrantes se llaman `<nombre> 1`,
+/// `<nombre> 2`, etc. Si tiene `tolerancia` sus integrantes se van sin
+/// entrar si hay más de esa cantidad de personas en la fila de entrada.
+/// Si tiene `integrantes` visitan el parque en grupos de esa cantidad de
+/// personas (el último puede ser más chico).
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "GrupoVisitantesArchivo")]
+pub struct GrupoVisitantes {
+    pub nombre: Option<String>,
+    pub cantidad: u32,
+    pub presupuesto_min: u32,
+    pub presupuesto_max: u32,
+    pub tolerancia: Option<u32>,
+    pub integrantes: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GrupoVisitantesArchivo {
+    nombre: Option<String>,
+    cantidad: Natural,
+    presupuesto: Option<Natural>,
+    presupuesto_min: Option<Natural>,
+    presupuesto_max: Option<Natural>,
+    tolerancia: Option<u32>,
+    integrantes: Option<Natural>,
+}
+
+impl TryFrom<GrupoVisitantesArchivo> for GrupoVisitantes {
+    type Error = String;
+
+    fn try_from(archivo: GrupoVisitantesArchivo) -> Result<Self, Self::Error> {
+        let (min, max) = match (archivo.presupuesto, archivo.presupuesto_min, archivo.presupuesto_max) {
+            (Some(p), None, None) => (p.0, p.0),
+            (None, Some(min), Some(max)) => (min.0, max.0),
+            _ => return Err(
+                "Se debe indicar `presupuesto` o bien `presupuesto_min` y `presupuesto_max`".into()
+            ),
+        };
+        if min > max {
+            return Err(format!("Rango inválido ({} > {})", min, max));
+        }
+        Ok(GrupoVisitantes {
+            nombre: archivo.nombre,
+            cantidad: archivo.cantidad.0,
+            presupuesto_min: min,
+            presupuesto_max: max,
+            tolerancia: archivo.tolerancia,
+            integrantes: archivo.integrantes.map(|n| n.0),
+        })
+    }
+}
+
+impl Configuracion {
+    /// Lee y valida un archivo de configuración. Si la extensión es
+    /// `.json` se interpreta como JSON, en cualquier otro caso como TOML.
+    pub fn cargar(ruta: &str) -> Result<Self, String> {
+        let texto = fs::read_to_string(ruta)
+            .map_err(|e| format!("{}: {}", ruta, e))?;
+        let es_json = Path::new(ruta)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        Self::desde_texto(&texto, es_json).map_err(|e| format!("{}: {}", ruta, e))
+    }
+
+    fn desde_texto(texto: &str, es_json: bool) -> Result<Self, String> {
+        let config: Self = Self::leer(texto, es_json)?;
+        if let Err(desconocido) = config.validar_nombres() {
+            return Err(desconocido.ubicar(texto, es_json));
+        }
+        Ok(config)
+    }
+
+    /// Índice de la zona `nombre` entre las `[[zonas]]` del archivo.
+    pub fn zona(&self, nombre: &str) -> Option<usize> {
+        self.zonas.iter().position(|zona| zona.nombre == nombre)
+    }
+
+    /// Lugar del parque indicado por su nombre: `entrada` o el nombre de
+    /// un juego, de un puesto o de un espectáculo del archivo.
+    pub fn lugar(&self, nombre: &str) -> Option<Lugar> {
+        if nombre == "entrada" {
+            return Some(Lugar::Entrada);
+        }
+        self.juegos.iter().position(|j| j.nombre.as_deref() == Some(nombre)).map(Lugar::Juego)
+            .or_else(|| self.espectaculos.iter().position(|e| e.nombre == nombre).map(Lugar::Espectaculo))
+            .or_else(|| self.puestos.iter().position(|p| p.nombre == nombre).map(Lugar::Puesto))
+    }
+
+    /// Verifica que las zonas y los lugares mencionados estén declarados
+    /// en el archivo.
+    fn validar_nombres(&self) -> Result<(), Desconocido> {
+        let zona = |seccion, indice, nombre: &Option<String>| match nombre {
+            Some(nombre) if self.zona(nombre).is_none() => Err(Desconocido {
+                seccion,
+                indice,
+                campo: "zona",
+                mensaje: format!("Zona desconocida '{}'", nombre),
+            }),
+            _ => Ok(()),
+        };
+        for (indice, juego) in self.juegos.iter().enumerate() {
+            zona("juegos", indice, &juego.zona)?;
+        }
+        for (indice, puesto) in self.puestos.iter().enumerate() {
+            zona("puestos", indice, &puesto.zona)?;
+        }
+        for (indice, espectaculo) in self.espectaculos.iter().enumerate() {
+            zona("espectaculos", indice, &espectaculo.zona)?;
+        }
+        for (indice, camino) in self.caminos.iter().enumerate() {
+            let lugares = [("desde", &camino.desde), ("hasta", &camino.hasta)];
+            if let Some((campo, nombre)) = lugares.iter().find(|(_, nombre)| self.lugar(nombre).is_none()) {
+                return Err(Desconocido {
+                    seccion: "caminos",
+                    indice,
+                    campo,
+                    mensaje: format!(
+                        "Lugar desconocido '{}' (debe ser entrada o el nombre de un juego, un puesto o un espectáculo)", nombre
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn leer<T: for<'de> Deserialize<'de>>(texto: &str, es_json: bool) -> Result<T, String> {
+        if es_json {
+            serde_json::from_str(texto).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(texto).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Zona o lugar no declarado que menciona el campo `campo` del elemento
+/// `indice` de la sección `seccion` del archivo.
+#[derive(Debug)]
+struct Desconocido {
+    seccion: &'static str,
+    indice: usize,
+    campo: &'static str,
+    mensaje: String,
+}
+
+impl Desconocido {
+    /// Vuelve a leer el archivo hasta el campo que menciona el nombre
+    /// desconocido, para que el error indique su línea como los demás.
+    fn ubicar(self, texto: &str, es_json: bool) -> String {
+        let mensaje = self.mensaje.clone();
+        let ubicado = if es_json {
+            self.deserialize(&mut serde_json::Deserializer::from_str(texto)).map_err(|e| e.to_string())
+        } else {
+            self.deserialize(&mut toml::Deserializer::new(texto)).map_err(|e| e.to_string())
+        };
+        ubicado.err().unwrap_or(mensaje)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for Desconocido {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// Recorre las secciones del archivo hasta la del elemento desconocido.
+impl<'de> Visitor<'de> for Desconocido {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "la sección {}", self.seccion)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut mapa: A) -> Result<(), A::Error> {
+        while let Some(clave) = mapa.next_key::<String>()? {
+            if clave == self.seccion {
+                return mapa.next_value_seed(Elementos(self));
+            }
+            mapa.next_value::<IgnoredAny>()?;
+        }
+        Ok(())
+    }
+}
+
+/// Recorre los elementos de la sección hasta el desconocido.
+struct Elementos(Desconocido);
+
+impl<'de> DeserializeSeed<'de> for Elementos {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Elementos {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "los elementos de {}", self.0.seccion)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut elementos: A) -> Result<(), A::Error> {
+        for _ in 0..self.0.indice {
+            elementos.next_element::<IgnoredAny>()?;
+        }
+        elementos.next_element_seed(Campos(self.0))?;
+        Ok(())
+    }
+}
+
+/// Recorre los campos del elemento desconocido hasta el que menciona el
+/// nombre, y devuelve el error al leer su valor.
+struct Campos(Desconocido);
+
+impl<'de> DeserializeSeed<'de> for Campos {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Campos {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "un elemento de {}", self.0.seccion)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut mapa: A) -> Result<(), A::Error> {
+        while let Some(clave) = mapa.next_key::<String>()? {
+            if clave == self.0.campo {
+                return mapa.next_value_seed(Nombre(self.0.mensaje));
+            }
+            mapa.next_value::<IgnoredAny>()?;
+        }
+        Err(de::Error::custom(self.0.mensaje))
+    }
+}
+
+/// Nombre desconocido: leerlo es el error `0`.
+struct Nombre(String);
+
+impl<'de> DeserializeSeed<'de> for Nombre {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Nombre {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "un nombre")
+    }
+
+    fn visit_str<E: de::Error>(self, _: &str) -> Result<(), E> {
+        Err(E::custom(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clima::Clima;
+
+    const EJEMPLO: &str = r#"
+capacidad = 3
+semilla = 42
+edad_max = 12
+movilidad_reducida = 0.1
+mezcla_visitantes = { familia = 2, estudiante = 1 }
+cansancio_maximo = 12
+satisfaccion_minima = -20.5
+caminata = 30
+clima = "5000:tormenta"
+
+[[zonas]]
+nombre = "Extrema"
+capacidad = 10
+
+[[puestos]]
+nombre = "Pochoclos"
+
+[[caminos]]
+desde = "entrada"
+hasta = "Montaña Rusa"
+ms = 12
+
+[[espectaculos]]
+nombre = "Magia"
+precio = 4
+capacidad = 20
+duracion = 300
+horarios = [1000, 4000]
+zona = "Extrema"
+
+[[arquetipos]]
+nombre = "estudiante"
+intensidad_preferida = 4
+estrategia = "fila_mas_corta"
+
+[[juegos]]
+nombre = "Montaña Rusa"
+precio = 15
+capacidad = 8
+duracion = 120
+unidades = 2
+carga = 5
+fila_individual = true
+altura_minima = 120
+accesible = false
+intensidad = 5
+zona = "Extrema"
+desperfectos = { probabilidad = 0.1, tiempo_maximo_arreglo = 50 }
+
+[[juegos]]
+precio = 5
+cubierto = true
+
+[[visitantes]]
+nombre = "Escuela"
+cantidad = 2
+presupuesto = 40
+integrantes = 2
+
+[[visitantes]]
+cantidad = 3
+presupuesto_min = 10
+presupuesto_max = 60
+tolerancia = 0
+"#;
+
+    #[test]
+    fn lee_toml() {
+        let config = Configuracion::desde_texto(EJEMPLO, false).unwrap();
+        assert_eq!(config.capacidad.unwrap().0, 3);
+        assert_eq!(config.juegos.len(), 2);
+        assert_eq!(config.juegos[0].nombre.as_deref(), Some("Montaña Rusa"));
+        assert_eq!(config.juegos[0].desperfectos.as_ref().unwrap().tiempo_maximo_arreglo, 50);
+        assert!(config.juegos[1].capacidad.is_none());
+        assert_eq!(config.juegos[0].unidades.unwrap().0, 2);
+        assert!(config.juegos[1].unidades.is_none());
+        assert_eq!(config.juegos[0].carga, Some(5));
+        assert_eq!(config.juegos[0].descarga, None);
+        assert_eq!(config.juegos[0].fila_individual, Some(true));
+        assert_eq!(config.juegos[0].altura_minima.unwrap().0, 120);
+        assert_eq!(config.juegos[0].accesible, Some(false));
+        assert!(config.juegos[1].edad_minima.is_none());
+        assert_eq!((config.edad_min, config.edad_max), (None, Some(12)));
+        assert_eq!(config.movilidad_reducida.unwrap().0, 0.1);
+        assert_eq!(config.mezcla_visitantes.as_ref().unwrap()["familia"], 2);
+        assert_eq!(config.cansancio_maximo.unwrap().0, 12);
+        assert_eq!(config.satisfaccion_minima, Some(-20.5));
+        assert!(config.cierre.is_none());
+        assert_eq!(config.arquetipos[0].estrategia, Some(Estrategia::FilaMasCorta));
+        assert!(config.arquetipos[0].paciencia.is_none());
+        assert_eq!(config.juegos[0].intensidad.unwrap().0, 5);
+        assert_eq!(config.caminata.unwrap().0, 30);
+        assert_eq!(config.zonas[0].capacidad.unwrap().0, 10);
+        assert_eq!(config.juegos[0].zona.as_deref().and_then(|z| config.zona(z)), Some(0));
+        assert!(config.juegos[1].zona.is_none());
+        assert!(config.puestos[0].zona.is_none());
+        assert_eq!((config.lugar(&config.caminos[0].hasta), config.caminos[0].ms), (Some(Lugar::Juego(0)), 12));
+        assert_eq!(config.espectaculos[0].horarios, Horarios(vec![1000, 4000]));
+        assert_eq!(config.espectaculos[0].zona.as_deref(), Some("Extrema"));
+        assert!(config.espectaculos[0].interes.is_none());
+        assert_eq!(
+            config.clima.unwrap().0,
+            ConfiguracionClima::Fijo(vec![(0, Clima::Soleado), (5000, Clima::Tormenta)])
+        );
+        assert_eq!((config.juegos[0].cubierto, config.juegos[1].cubierto), (None, Some(true)));
+        assert_eq!(config.visitantes[0].nombre.as_deref(), Some("Escuela"));
+        assert_eq!(config.visitantes[1].presupuesto_max, 60);
+        assert_eq!(config.visitantes[0].tolerancia, None);
+        assert_eq!(config.visitantes[1].tolerancia, Some(0));
+        assert_eq!(config.visitantes[0].integrantes, Some(2));
+        assert_eq!(config.visitantes[1].integrantes, None);
+    }
+
+    #[test]
+    fn lee_json() {
+        let json = r#"{
+            "capacidad": 3,
+            "juegos": [{"nombre": "Carrusel", "precio": 5, "capacidad": 10}],
+            "visitantes": [{"cantidad": 4, "presupuesto": 20}]
+        }"#;
+        let config = Configuracion::desde_texto(json, true).unwrap();
+        assert_eq!(config.juegos[0].capacidad.unwrap().0, 10);
+        assert_eq!(config.visitantes[0].cantidad, 4);
+    }
+
+    #[test]
+    fn los_errores_indican_la_linea() {
+        let toml = "capacidad = 3\n\n[[juegos]]\nprecio = 5\n\n[[juegos]]\nprecio = 5\ncapacidad = 0\n";
+        let error = Configuracion::desde_texto(toml, false).unwrap_err();
+        assert!(error.contains("no es un número natural"), "{}", error);
+        assert!(error.contains("line 6"), "{}", error);
+
+        let json = "{\n\"juegos\": [\n{\"precio\": 5},\n{\"precio\": 5, \"desperfectos\": {\"probabilidad\": 2, \"tiempo_maximo_arreglo\": 1}}\n]}";
+        let error = Configuracion::desde_texto(json, true).unwrap_err();
+        assert!(error.contains("entre 0 y 1"), "{}", error);
+        assert!(error.contains("line 4"), "{}", error);
+    }
+
+    #[test]
+    fn las_zonas_y_lugares_desconocidos_indican_la_linea() {
+        let toml = "[[zonas]]\nnombre = \"Norte\"\n\n[[juegos]]\nnombre = \"Tren\"\nprecio = 5\nzona = \"Norte\"\n\n\
+                    [[juegos]]\nprecio = 5\nzona = \"Sur\"\n";
+        let error = Configuracion::desde_texto(toml, false).unwrap_err();
+        assert!(error.contains("Zona desconocida 'Sur'"), "{}", error);
+        assert!(error.contains("line 11"), "{}", error);
+
+        let toml = "[[juegos]]\nnombre = \"Tren\"\nprecio = 5\n\n[[caminos]]\ndesde = \"entrada\"\nhasta = \"Tren\"\nms = 5\n\n\
+                    [[caminos]]\ndesde = \"Tren\"\nhasta = \"Barco\"\nms = 5\n";
+        let error = Configuracion::desde_texto(toml, false).unwrap_err();
+        assert!(error.contains("Lugar desconocido 'Barco'"), "{}", error);
+        assert!(error.contains("line 12"), "{}", error);
+
+        let json = "{\n\"puestos\": [{\"nombre\": \"Helados\"}],\n\"caminos\": [\n{\"desde\": \"Helados\", \"hasta\": \"Tren\", \"ms\": 5}\n]}";
+        let error = Configuracion::desde_texto(json, true).unwrap_err();
+        assert!(error.contains("Lugar desconocido 'Tren'"), "{}", error);
+        assert!(error.contains("line 4"), "{}", error);
+    }
+
+    #[test]
+    fn los_espectaculos_sin_horarios_indican_la_linea() {
+        let toml = "capacidad = 3\n\n[[espectaculos]]\nnombre = \"Magia\"\nprecio = 3\ncapacidad = 10\nduracion = 200\nhorarios = []\n";
+        let error = Configuracion::desde_texto(toml, false).unwrap_err();
+        assert!(error.contains("no tiene horarios"), "{}", error);
+        assert!(error.contains("line 3"), "{}", error);
+    }
+
+    #[test]
+    fn el_tiempo_de_arreglo_no_puede_ser_cero() {
+        let toml = "[[juegos]]\nprecio = 5\ndesperfectos = { probabilidad = 0.1, tiempo_maximo_arreglo = 0 }\n";
+        let error = Configuracion::desde_texto(toml, false).unwrap_err();
+        assert!(error.contains("no es un número natural"), "{}", error);
+    }
+
+    #[test]
+    fn estrategias_desconocidas_son_error() {
+        let error = Configuracion::desde_texto("[[arquetipos]]\nnombre = \"x\"\nestrategia = \"cualquiera\"\n", false).unwrap_err();
+        assert!(error.contains("cualquiera"), "{}", error);
+    }
+
+    #[test]
+    fn campos_desconocidos_son_error() {
+        let error = Configuracion::desde_texto("[[juegos]]\nprecio = 5\ncosto = 3\n", false).unwrap_err();
+        assert!(error.contains("costo"), "{}", error);
+    }
+
+    #[test]
+    fn visitantes_sin_presupuesto_son_error() {
+        assert!(Configuracion::desde_texto("[[visitantes]]\ncantidad = 3\n", false).is_err());
+    }
+}
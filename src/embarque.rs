@@ -0,0 +1,1161 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::sync::{Barrera, Condvar, Mutex, RwLock, Semaforo};
+
+/// Momentos del recorrido de una persona por un juego, informados a
+/// quien llama a `Embarque::subir`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paso {
+    /// Esperando su turno en la fila (`cv_turno`, o `fila_mutex` si la
+    /// fila no es justa, o `cv_individual` en la fila individual).
+    EnFila,
+    /// Primera de la fila, espera que haya una unidad en el andén con
+    /// lugar libre (`cv_anden`).
+    ConTurno,
+    /// El grupo no entra en los lugares que le quedan a `unidad`, que
+    /// parte sin completarse; sigue esperando la siguiente.
+    DejaPasar { unidad: usize },
+    /// Ocupó un lugar en `unidad` y espera que termine la vuelta.
+    /// `adelantamientos` es la cantidad de personas que llegaron a la
+    /// fila después y subieron antes.
+    Arriba { unidad: usize, adelantamientos: u32 },
+    /// Terminó la vuelta y espera al resto para bajar (`salida_barrier`).
+    Bajando,
+    /// Esperando su turno para usar la salida (`salida_mutex`).
+    Saliendo,
+}
+
+/// Resultado de esperar a que suban personas a una unidad.
+pub enum Espera {
+    /// Se ocuparon todos los lugares.
+    Llena(Vuelta),
+    /// Se agotó el tiempo de espera con algunas personas arriba.
+    Incompleta(Vuelta),
+    /// La primera de la fila es un grupo que no entra en los lugares
+    /// libres, así que la unidad parte sin completarse.
+    Cedida(Vuelta),
+    /// Se agotó el tiempo de espera sin ninguna persona arriba.
+    Vacia,
+    /// Desbloqueo espurio de la condvar, hay que volver a esperar.
+    Espuria,
+}
+
+/// Vuelta de una unidad con personas arriba. Mientras no parta la
+/// unidad ocupa el andén.
+pub struct Vuelta {
+    pub unidad: usize,
+    pub personas: u32,
+    /// Personas que subieron por la fila individual.
+    pub individuales: u32,
+    /// Hilos que subieron: una persona sola o un grupo cuenta como uno.
+    pasajeros: u32,
+}
+
+/// Unidad que ocupa el andén, cuántos lugares le quedan y quiénes
+/// subieron.
+struct Anden {
+    unidad: Option<usize>,
+    espacio_libre: u32,
+    personas: u32,
+    individuales: u32,
+    pasajeros: u32,
+    /// Un grupo que no entraba la dejó partir sin completarse.
+    cedida: bool,
+    individual: FilaIndividual,
+    /// Las mismas `suspendido` y `epoca` de `Turnos`, para quienes
+    /// esperan con el mutex del andén.
+    suspendido: bool,
+    epoca: u64,
+    /// La unidad del andén (o la próxima que llegue) parte como si se le
+    /// hubiera agotado el tiempo de espera.
+    cerrado: bool,
+}
+
+impl Anden {
+    /// Deja el andén sin unidad (o con `unidad` vacía), sin tocar la fila
+    /// individual.
+    fn ocupar(&mut self, unidad: Option<usize>, espacio_libre: u32) {
+        self.unidad = unidad;
+        self.espacio_libre = espacio_libre;
+        self.personas = 0;
+        self.individuales = 0;
+        self.pasajeros = 0;
+        self.cedida = false;
+    }
+}
+
+/// Fila individual: personas solas que sólo suben a ocupar los lugares
+/// que dejan libres la fila principal y los grupos. Se protege con el
+/// mismo mutex que el andén.
+struct FilaIndividual {
+    siguiente: u64,
+    atendiendo: u64,
+    esperando: u32,
+    /// Lugares libres de la unidad del andén que se ofrecen a la fila.
+    convocados: u32,
+}
+
+/// Orden de llegada a la fila. Cada persona saca un número al llegar.
+struct Turnos {
+    siguiente: u64,
+    /// Número de la persona a la que le toca subir (sólo en filas
+    /// justas).
+    atendiendo: u64,
+    /// Personas en la fila y cuántas veces las adelantaron.
+    en_fila: BTreeMap<u64, u32>,
+    /// El juego está cerrado: no se puede hacer la fila.
+    suspendido: bool,
+    /// Cantidad de veces que se suspendió. Quien esperaba desde una época
+    /// anterior se va de la fila.
+    epoca: u64,
+}
+
+/// Sincronización propia de cada unidad del juego.
+struct Unidad {
+    sem_juego_en_curso: Semaforo,
+    salida_barrier: RwLock<Barrera>,
+    salida_mutex: Mutex<()>,
+}
+
+/// Protocolo de subida y bajada de las personas a un juego con una o
+/// más unidades idénticas que comparten la fila.
+///
+/// Las personas sacan un número al llegar a la fila (`turnos`). Si la
+/// fila es justa esperan en `cv_turno` a que les toque, en orden de
+/// llegada; si no, compiten por `fila_mutex` y puede pasar primero
+/// cualquiera. En ambos casos se cuentan los adelantamientos.
+///
+/// Un grupo ocupa todos sus lugares en la misma unidad y cuenta como un
+/// solo pasajero. Si es el primero de la fila y no entra en los lugares
+/// que le quedan a la unidad del andén, la hace partir.
+///
+/// Cuando una unidad va a partir con lugares libres (porque se agotó el
+/// tiempo de espera o porque un grupo no entraba) convoca por
+/// `cv_individual` a las primeras de la fila individual, que suben en
+/// orden de llegada, y espera en `cv_cero_espacio_libre` a que ocupen
+/// sus lugares.
+///
+/// Las unidades cargan de a una en el andén: la que lo ocupa espera en
+/// `cv_cero_espacio_libre` a que se llene (o a que se agote el tiempo),
+/// termina de cargar y al partir lo libera por `cv_anden` para la
+/// siguiente. Las personas
+/// hacen una única fila; la primera espera en
+/// `cv_anden` a que haya una unidad con lugar, lo ocupa y deja pasar a
+/// la siguiente. La última en ocupar un lugar avisa a la unidad. Al
+/// terminar la vuelta la unidad libera un permiso de su
+/// `sem_juego_en_curso` por persona y todos esperan en su
+/// `salida_barrier` antes de salir de a uno por su `salida_mutex`.
+///
+/// Al suspender el embarque se dispersan las filas: cambia la época y
+/// quienes esperaban se van sin subir, mientras que la unidad del andén
+/// parte con quienes ya estaban arriba.
+pub struct Embarque {
+    capacidad: u32,
+    justa: bool,
+
+    turnos: Mutex<Turnos>,
+    cv_turno: Condvar,
+    fila_mutex: Mutex<()>,
+    anden: Mutex<Anden>,
+    cv_anden: Condvar,
+    cv_individual: Condvar,
+    cv_cero_espacio_libre: Condvar,
+
+    unidades: Vec<Unidad>,
+}
+
+impl Embarque {
+    /// Crea el embarque de un juego con `unidades` unidades de
+    /// `capacidad` lugares. Si `justa` las personas suben en orden de
+    /// llegada a la fila.
+    pub fn new(capacidad: u32, unidades: u32, justa: bool) -> Self {
+        Self {
+            capacidad,
+            justa,
+
+            turnos: Mutex::new(Turnos {
+                siguiente: 0,
+                atendiendo: 0,
+                en_fila: BTreeMap::new(),
+                suspendido: false,
+                epoca: 0,
+            }),
+            cv_turno: Condvar::new(),
+            fila_mutex: Mutex::new(()),
+            anden: Mutex::new(Anden {
+                unidad: None,
+                espacio_libre: 0,
+                personas: 0,
+                individuales: 0,
+                pasajeros: 0,
+                cedida: false,
+                individual: FilaIndividual { siguiente: 0, atendiendo: 0, esperando: 0, convocados: 0 },
+                suspendido: false,
+                epoca: 0,
+                cerrado: false,
+            }),
+            cv_anden: Condvar::new(),
+            cv_individual: Condvar::new(),
+            cv_cero_espacio_libre: Condvar::new(),
+
+            unidades: (0..unidades).map(|_| Unidad {
+                sem_juego_en_curso: Semaforo::new(0),
+                salida_barrier: RwLock::new(Barrera::new(capacidad as usize + 1)), // +1 para esperar el de la unidad
+                salida_mutex: Mutex::new(()),
+            }).collect(),
+        }
+    }
+
+    /// Si las personas suben en orden de llegada a la fila.
+    pub fn es_justa(&self) -> bool {
+        self.justa
+    }
+
+    /// Lleva `unidad` al andén, esperando que lo libere otra unidad si
+    /// hace falta, y espera hasta `timeout` a que se ocupen todos sus
+    /// lugares.
+    pub fn esperar_pasajeros(&self, unidad: usize, timeout: Duration) -> Espera {
+        let mut anden = self.anden.lock().expect("poisoned");
+        while anden.unidad.is_some_and(|otra| otra != unidad) {
+            let (guard, timeout) = self.cv_anden.wait_timeout(anden, timeout).expect("poisoned");
+            anden = guard;
+            if timeout.timed_out() && anden.unidad.is_some_and(|otra| otra != unidad) {
+                // otra unidad sigue cargando, se vuelve a intentar
+                return Espera::Vacia;
+            }
+        }
+        if anden.unidad.is_none() {
+            anden.ocupar(Some(unidad), self.capacidad);
+            self.cv_anden.notify_all();
+        }
+
+        // Suspendido el embarque o cerrado el andén, la unidad parte como
+        // si se hubiera agotado el tiempo.
+        let mut timed_out = anden.suspendido || std::mem::take(&mut anden.cerrado);
+        // Si el último lugar se ocupó antes de empezar a esperar el aviso
+        // ya se perdió, así que sólo se espera si todavía queda espacio.
+        if anden.espacio_libre != 0 && !timed_out {
+            let (guard, timeout) = self.cv_cero_espacio_libre
+                .wait_timeout(anden, timeout)
+                .expect("poisoned");
+            anden = guard;
+            timed_out = timeout.timed_out() || anden.suspendido || std::mem::take(&mut anden.cerrado);
+        }
+
+        if !anden.cedida && anden.espacio_libre == 0 {
+            return Espera::Llena(self.vuelta(&anden, unidad));
+        } else if !anden.cedida && !timed_out {
+            return Espera::Espuria;
+        }
+
+        // la unidad va a partir con lugares libres: no sube nadie más de
+        // la fila principal y se ofrecen a la fila individual
+        anden.espacio_libre = 0;
+        let libres = self.capacidad - anden.personas;
+        anden.individual.convocados = libres.min(anden.individual.esperando);
+        if anden.individual.convocados > 0 {
+            self.cv_individual.notify_all();
+            while anden.individual.convocados > 0 {
+                anden = self.cv_cero_espacio_libre.wait(anden).expect("poisoned");
+            }
+        }
+
+        let vuelta = self.vuelta(&anden, unidad);
+        if anden.cedida {
+            Espera::Cedida(vuelta)
+        } else if anden.personas == 0 {
+            // la unidad deja el andén vacía, para que pruebe otra
+            anden.ocupar(None, 0);
+            self.cv_anden.notify_all();
+            Espera::Vacia
+        } else {
+            // la unidad sigue en el andén hasta partir
+            Espera::Incompleta(vuelta)
+        }
+    }
+
+    fn vuelta(&self, anden: &Anden, unidad: usize) -> Vuelta {
+        Vuelta { unidad, personas: anden.personas, individuales: anden.individuales, pasajeros: anden.pasajeros }
+    }
+
+    /// La unidad de la vuelta deja el andén, que queda libre para que
+    /// cargue la siguiente. Se llama cuando terminaron de subir las
+    /// personas.
+    pub fn partir(&self, vuelta: &Vuelta) {
+        let mut anden = self.anden.lock().expect("poisoned");
+        if anden.unidad == Some(vuelta.unidad) {
+            anden.ocupar(None, 0);
+            self.cv_anden.notify_all();
+        }
+    }
+
+    /// Hace bajar a las personas de la vuelta y espera a que todas dejen
+    /// sus lugares. Al volver la unidad queda vacía y lista para volver
+    /// al andén.
+    pub fn terminar_vuelta(&self, vuelta: Vuelta) {
+        let unidad = &self.unidades[vuelta.unidad];
+        // setear la cantidad de personas a esperar que usen la salida previo a avisar que dejen sus lugares
+        {
+            let mut salida_barrier = unidad.salida_barrier.write().expect("poison");
+            *salida_barrier = Barrera::new(vuelta.pasajeros as usize + 1); // +1 para esperar el de la unidad
+        }
+        // avisar que la vuelta terminó
+        for _pasajero in 0..vuelta.pasajeros {
+            unidad.sem_juego_en_curso.release();
+        }
+        let salida_barrier = unidad.salida_barrier.read().expect("poison");
+        salida_barrier.wait();
+    }
+
+    /// Sube una persona, o un grupo de `lugares` personas, a la unidad
+    /// que esté en el andén, bloqueando hasta que haya dado una vuelta y
+    /// haya salido. `avisar` se llama en cada paso del recorrido. El
+    /// grupo no puede ser más grande que la capacidad de las unidades.
+    /// Devuelve falso si no llegó a subir porque el embarque estaba
+    /// suspendido o se suspendió mientras esperaba.
+    pub fn subir(&self, lugares: u32, mut avisar: impl FnMut(Paso)) -> bool {
+        assert!(0 < lugares && lugares <= self.capacidad, "el grupo no entra en el juego");
+        let (numero, epoca) = {
+            let mut turnos = self.turnos.lock().expect("poisoned");
+            if turnos.suspendido {
+                return false;
+            }
+            let numero = turnos.siguiente;
+            turnos.siguiente += 1;
+            turnos.en_fila.insert(numero, 0);
+            (numero, turnos.epoca)
+        };
+        avisar(Paso::EnFila);
+        let fila = if self.justa {
+            let mut turnos = self.turnos.lock().expect("poisoned");
+            while turnos.atendiendo != numero {
+                if turnos.epoca != epoca {
+                    turnos.en_fila.remove(&numero);
+                    return false;
+                }
+                turnos = self.cv_turno.wait(turnos).expect("poisoned");
+            }
+            None
+        } else {
+            Some(self.fila_mutex.lock().expect("poisoned"))
+        };
+        avisar(Paso::ConTurno);
+        let mut anden = self.anden.lock().expect("poison");
+        let unidad = loop {
+            if anden.epoca != epoca {
+                drop(anden);
+                // la suspensión ya pasó el turno a quien llegue después
+                self.turnos.lock().expect("poisoned").en_fila.remove(&numero);
+                return false;
+            }
+            if let Some(unidad) = anden.unidad {
+                if anden.espacio_libre >= lugares {
+                    break unidad;
+                }
+                if anden.espacio_libre > 0 && anden.personas > 0 {
+                    // el grupo no entra: la unidad parte así y se espera
+                    // la siguiente
+                    anden.espacio_libre = 0;
+                    anden.cedida = true;
+                    self.cv_cero_espacio_libre.notify_one();
+                    avisar(Paso::DejaPasar { unidad });
+                }
+            }
+            anden = self.cv_anden.wait(anden).expect("poisoned");
+        };
+        anden.espacio_libre -= lugares;
+        anden.personas += lugares;
+        anden.pasajeros += 1;
+        if anden.espacio_libre == 0 {
+            self.cv_cero_espacio_libre.notify_one();
+        }
+        drop(anden);
+
+        // ya tiene lugar: adelantó a los que siguen en la fila habiendo
+        // llegado antes, y pasa la siguiente
+        let adelantamientos = {
+            let mut turnos = self.turnos.lock().expect("poisoned");
+            let adelantamientos = turnos.en_fila.remove(&numero).unwrap_or_default();
+            for (_, veces) in turnos.en_fila.range_mut(..numero) {
+                *veces += 1;
+            }
+            if self.justa && turnos.epoca == epoca {
+                turnos.atendiendo += 1;
+                self.cv_turno.notify_all();
+            }
+            adelantamientos
+        };
+        drop(fila);
+        avisar(Paso::Arriba { unidad, adelantamientos });
+        self.viajar(unidad, avisar);
+        true
+    }
+
+    /// Sube una persona por la fila individual: espera en orden de
+    /// llegada a que una unidad que va a partir con lugares libres la
+    /// convoque, y sigue como `subir`.
+    pub fn subir_individual(&self, mut avisar: impl FnMut(Paso)) -> bool {
+        let mut anden = self.anden.lock().expect("poisoned");
+        if anden.suspendido {
+            return false;
+        }
+        let epoca = anden.epoca;
+        let numero = anden.individual.siguiente;
+        anden.individual.siguiente += 1;
+        anden.individual.esperando += 1;
+        avisar(Paso::EnFila);
+        while anden.individual.convocados == 0 || anden.individual.atendiendo != numero {
+            if anden.epoca != epoca {
+                return false;
+            }
+            anden = self.cv_individual.wait(anden).expect("poisoned");
+        }
+        let unidad = anden.unidad.expect("unidad en el andén");
+        anden.individual.atendiendo += 1;
+        anden.individual.esperando -= 1;
+        anden.individual.convocados -= 1;
+        anden.personas += 1;
+        anden.individuales += 1;
+        anden.pasajeros += 1;
+        if anden.individual.convocados == 0 {
+            self.cv_cero_espacio_libre.notify_one();
+        }
+        // pasa la siguiente de la fila individual
+        self.cv_individual.notify_all();
+        drop(anden);
+        avisar(Paso::Arriba { unidad, adelantamientos: 0 });
+        self.viajar(unidad, avisar);
+        true
+    }
+
+    /// Cierra el embarque: dispersa las filas y no deja que nadie más
+    /// las haga hasta `reanudar`. La unidad que está cargando parte con
+    /// quienes ya subieron.
+    pub fn suspender(&self) {
+        {
+            let mut turnos = self.turnos.lock().expect("poisoned");
+            turnos.suspendido = true;
+            turnos.epoca += 1;
+            turnos.atendiendo = turnos.siguiente;
+            self.cv_turno.notify_all();
+        }
+        let mut anden = self.anden.lock().expect("poisoned");
+        anden.suspendido = true;
+        anden.epoca += 1;
+        let siguiente = anden.individual.siguiente;
+        anden.individual.atendiendo = siguiente;
+        anden.individual.esperando = 0;
+        anden.individual.convocados = 0;
+        self.cv_anden.notify_all();
+        self.cv_individual.notify_all();
+        self.cv_cero_espacio_libre.notify_all();
+    }
+
+    /// Hace partir a la unidad que está cargando en el andén, o a la
+    /// próxima que llegue, como si se le hubiera agotado el tiempo de
+    /// espera. Los tests con `loom` lo usan en lugar del timeout, que ahí
+    /// nunca vence.
+    #[cfg(all(test, loom))]
+    fn cerrar_anden(&self) {
+        self.anden.lock().expect("poisoned").cerrado = true;
+        self.cv_cero_espacio_libre.notify_all();
+    }
+
+    /// Vuelve a abrir las filas después de `suspender`.
+    pub fn reanudar(&self) {
+        self.turnos.lock().expect("poisoned").suspendido = false;
+        self.anden.lock().expect("poisoned").suspendido = false;
+    }
+
+    /// Cantidad de personas y grupos en la fila principal y en la fila
+    /// individual.
+    pub fn largos(&self) -> (usize, usize) {
+        let principal = self.turnos.lock().expect("poisoned").en_fila.len();
+        let individual = self.anden.lock().expect("poisoned").individual.esperando as usize;
+        (principal, individual)
+    }
+
+    /// Espera arriba de `unidad` a que termine la vuelta y sale.
+    fn viajar(&self, unidad: usize, mut avisar: impl FnMut(Paso)) {
+        let unidad = &self.unidades[unidad];
+        unidad.sem_juego_en_curso.acquire();
+
+        avisar(Paso::Bajando);
+        let barrier = unidad.salida_barrier.read().expect("poisoned");
+        barrier.wait();
+        avisar(Paso::Saliendo);
+        // lockear el mutex de la salida para salir de a uno
+        let _mutex = unidad.salida_mutex.lock().expect("poison");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::{Arc, atomic::{AtomicU32, Ordering}}, thread};
+
+    /// Corre `vueltas` vueltas con `personas` personas subiendo al juego y
+    /// devuelve la máxima cantidad de personas arriba en simultáneo.
+    fn correr(capacidad: u32, personas: u32, vueltas: u32, timeout: Duration) -> u32 {
+        let embarque = Arc::new(Embarque::new(capacidad, 1, true));
+        let arriba = Arc::new(AtomicU32::new(0));
+        let maximo = Arc::new(AtomicU32::new(0));
+
+        let hilos: Vec<_> = (0..personas).map(|_| {
+            let (embarque, arriba, maximo) = (embarque.clone(), arriba.clone(), maximo.clone());
+            thread::spawn(move || {
+                embarque.subir(1, |paso| match paso {
+                    Paso::Arriba { .. } => {
+                        let n = arriba.fetch_add(1, Ordering::SeqCst) + 1;
+                        maximo.fetch_max(n, Ordering::SeqCst);
+                    },
+                    Paso::Bajando => { arriba.fetch_sub(1, Ordering::SeqCst); },
+                    _ => {}
+                });
+            })
+        }).collect();
+
+        let mut hechas = 0;
+        while hechas < vueltas {
+            match embarque.esperar_pasajeros(0, timeout) {
+                Espera::Llena(vuelta) | Espera::Incompleta(vuelta) | Espera::Cedida(vuelta) => {
+                    embarque.partir(&vuelta);
+                    embarque.terminar_vuelta(vuelta);
+                    hechas += 1;
+                },
+                Espera::Vacia | Espera::Espuria => continue
+            }
+        }
+
+        for hilo in hilos {
+            hilo.join().expect("no se pudo joinear hilo de persona");
+        }
+        maximo.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn todas_las_personas_bajan_sin_superar_la_capacidad() {
+        for _ in 0..50 {
+            assert!(correr(2, 6, 3, Duration::from_secs(5)) <= 2);
+        }
+    }
+
+    #[test]
+    fn vuelta_incompleta_al_agotarse_el_tiempo() {
+        assert_eq!(correr(3, 1, 1, Duration::from_millis(1)), 1);
+    }
+
+    #[test]
+    fn sin_personas_la_espera_queda_vacia() {
+        let embarque = Embarque::new(2, 1, true);
+        assert!(matches!(embarque.esperar_pasajeros(0, Duration::from_millis(1)), Espera::Vacia));
+    }
+
+    /// Hace llegar `personas` personas a la fila de a una, en orden, a un
+    /// juego de un lugar. Devuelve el orden en que subieron y cuántas
+    /// veces adelantaron a cada una.
+    fn subir_en_orden(justa: bool, personas: usize) -> Vec<(usize, u32)> {
+        let embarque = Arc::new(Embarque::new(1, 1, justa));
+        let subidas = Arc::new(std::sync::Mutex::new(vec![]));
+        let (en_fila, llego) = std::sync::mpsc::channel();
+
+        let hilos: Vec<_> = (0..personas).map(|persona| {
+            let (embarque, subidas, en_fila) = (embarque.clone(), subidas.clone(), en_fila.clone());
+            let hilo = thread::spawn(move || {
+                embarque.subir(1, |paso| match paso {
+                    Paso::EnFila => en_fila.send(()).unwrap(),
+                    Paso::Arriba { adelantamientos, .. } => {
+                        subidas.lock().unwrap().push((persona, adelantamientos));
+                    },
+                    _ => {}
+                });
+            });
+            // la siguiente llega cuando esta ya tiene su número
+            llego.recv().unwrap();
+            hilo
+        }).collect();
+
+        let mut hechas = 0;
+        while hechas < personas {
+            if let Espera::Llena(vuelta) | Espera::Incompleta(vuelta) =
+                embarque.esperar_pasajeros(0, Duration::from_secs(5)) {
+                embarque.partir(&vuelta);
+                embarque.terminar_vuelta(vuelta);
+                hechas += 1;
+            }
+        }
+        for hilo in hilos {
+            hilo.join().expect("no se pudo joinear hilo de persona");
+        }
+        let subidas = subidas.lock().unwrap().clone();
+        subidas
+    }
+
+    #[test]
+    fn la_fila_justa_respeta_el_orden_de_llegada() {
+        for _ in 0..10 {
+            let subidas = subir_en_orden(true, 8);
+            assert_eq!(subidas, (0..8).map(|persona| (persona, 0)).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn los_adelantamientos_cuentan_quienes_llegaron_despues() {
+        for _ in 0..10 {
+            let subidas = subir_en_orden(false, 8);
+            for (posicion, (persona, adelantamientos)) in subidas.iter().enumerate() {
+                let despues = subidas[..posicion].iter().filter(|(otra, _)| otra > persona).count();
+                assert_eq!(*adelantamientos as usize, despues, "{:?}", subidas);
+            }
+        }
+    }
+
+    #[test]
+    fn el_grupo_que_no_entra_deja_pasar_la_unidad() {
+        let embarque = Arc::new(Embarque::new(3, 1, true));
+        let unidad = {
+            let embarque = embarque.clone();
+            thread::spawn(move || {
+                let mut vueltas = vec![];
+                while vueltas.len() < 2 {
+                    let (tipo, vuelta) = match embarque.esperar_pasajeros(0, Duration::from_secs(5)) {
+                        Espera::Llena(vuelta) => ("llena", vuelta),
+                        Espera::Incompleta(vuelta) => ("incompleta", vuelta),
+                        Espera::Cedida(vuelta) => ("cedida", vuelta),
+                        Espera::Vacia | Espera::Espuria => continue
+                    };
+                    vueltas.push((tipo, vuelta.personas));
+                    embarque.partir(&vuelta);
+                    embarque.terminar_vuelta(vuelta);
+                }
+                vueltas
+            })
+        };
+
+        let (arriba, subio) = std::sync::mpsc::channel();
+        let sola = {
+            let embarque = embarque.clone();
+            thread::spawn(move || embarque.subir(1, |paso| if let Paso::Arriba { .. } = paso {
+                arriba.send(()).unwrap();
+            }))
+        };
+        subio.recv().unwrap();
+        let mut cedidas = 0;
+        embarque.subir(3, |paso| if let Paso::DejaPasar { .. } = paso {
+            cedidas += 1;
+        });
+
+        sola.join().unwrap();
+        assert_eq!(unidad.join().unwrap(), vec![("cedida", 1), ("llena", 3)]);
+        assert_eq!(cedidas, 1);
+    }
+
+    #[test]
+    fn la_fila_individual_ocupa_los_lugares_cedidos() {
+        let embarque = Arc::new(Embarque::new(3, 1, true));
+        let individuales: Vec<_> = (0..2).map(|_| {
+            let embarque = embarque.clone();
+            thread::spawn(move || embarque.subir_individual(|_| {}))
+        }).collect();
+        while embarque.largos() != (0, 2) {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        let unidad = {
+            let embarque = embarque.clone();
+            thread::spawn(move || {
+                let mut vueltas = vec![];
+                while vueltas.len() < 2 {
+                    let (tipo, vuelta) = match embarque.esperar_pasajeros(0, Duration::from_secs(5)) {
+                        Espera::Llena(vuelta) => ("llena", vuelta),
+                        Espera::Incompleta(vuelta) => ("incompleta", vuelta),
+                        Espera::Cedida(vuelta) => ("cedida", vuelta),
+                        Espera::Vacia | Espera::Espuria => continue
+                    };
+                    vueltas.push((tipo, vuelta.personas, vuelta.individuales));
+                    embarque.partir(&vuelta);
+                    embarque.terminar_vuelta(vuelta);
+                }
+                vueltas
+            })
+        };
+
+        let (arriba, subio) = std::sync::mpsc::channel();
+        let sola = {
+            let embarque = embarque.clone();
+            thread::spawn(move || embarque.subir(1, |paso| if let Paso::Arriba { .. } = paso {
+                arriba.send(()).unwrap();
+            }))
+        };
+        subio.recv().unwrap();
+        // el grupo no entra y los dos lugares libres van a la fila individual
+        embarque.subir(3, |_| {});
+
+        sola.join().unwrap();
+        for individual in individuales {
+            individual.join().unwrap();
+        }
+        assert_eq!(unidad.join().unwrap(), vec![("cedida", 3, 2), ("llena", 3, 0)]);
+        assert_eq!(embarque.largos(), (0, 0));
+    }
+
+    #[test]
+    fn al_suspender_se_dispersan_las_filas() {
+        for justa in [true, false] {
+            let embarque = Arc::new(Embarque::new(2, 1, justa));
+            let esperando: Vec<_> = (0..3).map(|_| {
+                let embarque = embarque.clone();
+                thread::spawn(move || embarque.subir(1, |_| {}))
+            }).chain((0..2).map(|_| {
+                let embarque = embarque.clone();
+                thread::spawn(move || embarque.subir_individual(|_| {}))
+            })).collect();
+            while embarque.largos() != (3, 2) {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            embarque.suspender();
+            for hilo in esperando {
+                assert!(!hilo.join().unwrap());
+            }
+            assert_eq!(embarque.largos(), (0, 0));
+            assert!(!embarque.subir(1, |_| {}));
+            assert!(matches!(embarque.esperar_pasajeros(0, Duration::from_secs(5)), Espera::Vacia));
+
+            // al reabrir la fila vuelve a funcionar
+            embarque.reanudar();
+            let sola = {
+                let embarque = embarque.clone();
+                thread::spawn(move || embarque.subir(2, |_| {}))
+            };
+            loop {
+                if let Espera::Llena(vuelta) = embarque.esperar_pasajeros(0, Duration::from_secs(5)) {
+                    embarque.partir(&vuelta);
+                    embarque.terminar_vuelta(vuelta);
+                    break;
+                }
+            }
+            assert!(sola.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn varias_unidades_comparten_la_fila() {
+        const CAPACIDAD: u32 = 2;
+        const UNIDADES: usize = 3;
+        const PERSONAS: u32 = 12;
+        let embarque = Arc::new(Embarque::new(CAPACIDAD, UNIDADES as u32, true));
+        let arriba: Arc<Vec<AtomicU32>> = Arc::new((0..UNIDADES).map(|_| AtomicU32::new(0)).collect());
+        let maximo = Arc::new(AtomicU32::new(0));
+        let transportadas = Arc::new(AtomicU32::new(0));
+
+        let personas: Vec<_> = (0..PERSONAS).map(|_| {
+            let (embarque, arriba, maximo) = (embarque.clone(), arriba.clone(), maximo.clone());
+            thread::spawn(move || {
+                let mut mi_unidad = 0;
+                embarque.subir(1, |paso| match paso {
+                    Paso::Arriba { unidad, .. } => {
+                        mi_unidad = unidad;
+                        let n = arriba[unidad].fetch_add(1, Ordering::SeqCst) + 1;
+                        maximo.fetch_max(n, Ordering::SeqCst);
+                    },
+                    Paso::Bajando => { arriba[mi_unidad].fetch_sub(1, Ordering::SeqCst); },
+                    _ => {}
+                });
+            })
+        }).collect();
+
+        let unidades: Vec<_> = (0..UNIDADES).map(|unidad| {
+            let (embarque, transportadas) = (embarque.clone(), transportadas.clone());
+            thread::spawn(move || {
+                while transportadas.load(Ordering::SeqCst) < PERSONAS {
+                    match embarque.esperar_pasajeros(unidad, Duration::from_millis(20)) {
+                        Espera::Llena(vuelta) | Espera::Incompleta(vuelta) | Espera::Cedida(vuelta) => {
+                            assert_eq!(vuelta.unidad, unidad);
+                            embarque.partir(&vuelta);
+                            thread::sleep(Duration::from_millis(50));
+                            transportadas.fetch_add(vuelta.personas, Ordering::SeqCst);
+                            embarque.terminar_vuelta(vuelta);
+                        },
+                        Espera::Vacia | Espera::Espuria => continue
+                    }
+                }
+            })
+        }).collect();
+
+        for hilo in personas.into_iter().chain(unidades) {
+            hilo.join().expect("no se pudo joinear hilo");
+        }
+        assert_eq!(transportadas.load(Ordering::SeqCst), PERSONAS);
+        assert!(maximo.load(Ordering::SeqCst) <= CAPACIDAD);
+    }
+
+    /// Espera a que `unidad` se llene, sin límite práctico de tiempo.
+    fn esperar_llena(embarque: &Embarque, unidad: usize) -> Vuelta {
+        loop {
+            match embarque.esperar_pasajeros(unidad, Duration::from_secs(60)) {
+                Espera::Llena(vuelta) => return vuelta,
+                Espera::Espuria => continue,
+                _ => panic!("la unidad {} partió sin llenarse", unidad)
+            }
+        }
+    }
+
+    #[test]
+    fn mientras_una_unidad_da_la_vuelta_otra_carga_de_la_fila() {
+        let embarque = Arc::new(Embarque::new(2, 2, true));
+        let personas: Vec<_> = (0..4).map(|_| {
+            let embarque = embarque.clone();
+            thread::spawn(move || {
+                let mut unidad = None;
+                embarque.subir(1, |paso| if let Paso::Arriba { unidad: numero, .. } = paso {
+                    unidad = Some(numero);
+                });
+                unidad
+            })
+        }).collect();
+
+        // la primera unidad parte llena y, sin que termine su vuelta, la
+        // segunda se llena con el resto de la fila
+        let primera = esperar_llena(&embarque, 0);
+        embarque.partir(&primera);
+        let segunda = esperar_llena(&embarque, 1);
+        embarque.partir(&segunda);
+        assert_eq!((primera.personas, segunda.personas), (2, 2));
+
+        embarque.terminar_vuelta(primera);
+        embarque.terminar_vuelta(segunda);
+        let mut unidades: Vec<_> = personas.into_iter().map(|hilo| hilo.join().unwrap()).collect();
+        unidades.sort();
+        assert_eq!(unidades, vec![Some(0), Some(0), Some(1), Some(1)]);
+        assert_eq!(embarque.largos(), (0, 0));
+    }
+}
+
+/// Tests con `loom`: exploran las intercalaciones de los hilos con a lo
+/// sumo dos o tres cambios de hilo forzados (`preemption_bound`), no
+/// todas las posibles.
+///
+/// Se corren con `RUSTFLAGS="--cfg loom" cargo test --release loom`.
+/// Como en `loom` las esperas con timeout nunca vencen, los escenarios
+/// que prueban la partida con lugares libres cierran el andén con
+/// `cerrar_anden`.
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::{sync::{Arc, Condvar, Mutex, atomic::{AtomicU32, Ordering}}, thread};
+
+    /// Explora las intercalaciones de `modelo` con a lo sumo
+    /// `desalojos` cambios de hilo forzados. Con cuatro hilos o más hay
+    /// que acotarlos más para que termine en un tiempo razonable.
+    fn explorar(desalojos: usize, modelo: impl Fn() + Sync + Send + 'static) {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(desalojos);
+        builder.check(modelo);
+    }
+
+    /// Espera pasajeros en `unidad` hasta que parta y da la vuelta.
+    /// Devuelve cuántas personas llevó y cuántas de la fila individual.
+    /// En loom `wait_timeout` nunca se agota, así que la unidad sólo
+    /// parte incompleta si se cerró el andén.
+    fn dar_vuelta(embarque: &Embarque, unidad: usize) -> (u32, u32) {
+        loop {
+            match embarque.esperar_pasajeros(unidad, Duration::from_secs(5)) {
+                Espera::Llena(vuelta) | Espera::Incompleta(vuelta) | Espera::Cedida(vuelta) => {
+                    let llevo = (vuelta.personas, vuelta.individuales);
+                    embarque.partir(&vuelta);
+                    embarque.terminar_vuelta(vuelta);
+                    return llevo;
+                },
+                Espera::Vacia | Espera::Espuria => {}
+            }
+        }
+    }
+
+    /// Aviso de un hilo a otro de que algo ya pasó. Se usa en lugar de
+    /// esperar activamente, que loom no puede explorar.
+    #[derive(Default)]
+    struct Senal {
+        dada: Mutex<bool>,
+        cv: Condvar,
+    }
+
+    impl Senal {
+        fn dar(&self) {
+            *self.dada.lock().unwrap() = true;
+            self.cv.notify_all();
+        }
+
+        fn esperar(&self) {
+            let mut dada = self.dada.lock().unwrap();
+            while !*dada {
+                dada = self.cv.wait(dada).unwrap();
+            }
+        }
+    }
+
+    fn modelo(capacidad: u32, vueltas: u32) {
+        explorar(3, move || {
+            let embarque = Arc::new(Embarque::new(capacidad, 1, true));
+            let arriba = Arc::new(AtomicU32::new(0));
+            let bajaron = Arc::new(AtomicU32::new(0));
+
+            let hilos: Vec<_> = (0..capacidad * vueltas).map(|_| {
+                let (embarque, arriba, bajaron) = (embarque.clone(), arriba.clone(), bajaron.clone());
+                thread::spawn(move || {
+                    embarque.subir(1, |paso| match paso {
+                        Paso::Arriba { .. } => {
+                            let n = arriba.fetch_add(1, Ordering::SeqCst) + 1;
+                            assert!(n <= capacidad, "se superó la capacidad del juego");
+                        },
+                        Paso::Bajando => { arriba.fetch_sub(1, Ordering::SeqCst); },
+                        _ => {}
+                    });
+                    bajaron.fetch_add(1, Ordering::SeqCst);
+                })
+            }).collect();
+
+            for _ in 0..vueltas {
+                assert_eq!(dar_vuelta(&embarque, 0), (capacidad, 0));
+            }
+
+            for hilo in hilos {
+                hilo.join().unwrap();
+            }
+            assert_eq!(bajaron.load(Ordering::SeqCst), capacidad * vueltas);
+        });
+    }
+
+    #[test]
+    fn loom_una_persona_una_vuelta() {
+        modelo(1, 1);
+    }
+
+    #[test]
+    fn loom_una_persona_por_vuelta_dos_vueltas() {
+        modelo(1, 2);
+    }
+
+    #[test]
+    fn loom_dos_personas_una_vuelta() {
+        modelo(2, 1);
+    }
+
+    #[test]
+    fn loom_dos_unidades_comparten_la_fila() {
+        explorar(2, || {
+            let embarque = Arc::new(Embarque::new(1, 2, true));
+            // personas arriba de cada unidad
+            let arriba = Arc::new([AtomicU32::new(0), AtomicU32::new(0)]);
+
+            let personas: Vec<_> = (0..2).map(|_| {
+                let (embarque, arriba) = (embarque.clone(), arriba.clone());
+                thread::spawn(move || {
+                    let mut en = None;
+                    assert!(embarque.subir(1, |paso| match paso {
+                        Paso::Arriba { unidad, .. } => {
+                            en = Some(unidad);
+                            let n = arriba[unidad].fetch_add(1, Ordering::SeqCst) + 1;
+                            assert!(n <= 1, "se superó la capacidad de la unidad {}", unidad);
+                        },
+                        Paso::Bajando => { arriba[en.unwrap()].fetch_sub(1, Ordering::SeqCst); },
+                        _ => {}
+                    }));
+                })
+            }).collect();
+            let segunda = {
+                let embarque = embarque.clone();
+                thread::spawn(move || dar_vuelta(&embarque, 1))
+            };
+
+            // cada unidad carga en el andén de a una y lleva a una persona
+            assert_eq!(dar_vuelta(&embarque, 0), (1, 0));
+            assert_eq!(segunda.join().unwrap(), (1, 0));
+            for persona in personas {
+                persona.join().unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn loom_la_fila_justa_respeta_el_orden_de_llegada() {
+        explorar(2, || {
+            let embarque = Arc::new(Embarque::new(1, 1, true));
+            let orden = Arc::new(Mutex::new(vec![]));
+
+            let en_fila = Arc::new(Senal::default());
+
+            let subir = |id: u32| {
+                let (embarque, orden, en_fila) = (embarque.clone(), orden.clone(), en_fila.clone());
+                thread::spawn(move || {
+                    if id > 0 {
+                        en_fila.esperar();
+                    }
+                    embarque.subir(1, |paso| match paso {
+                        Paso::EnFila => en_fila.dar(),
+                        Paso::Arriba { adelantamientos, .. } => {
+                            assert_eq!(adelantamientos, 0);
+                            orden.lock().unwrap().push(id);
+                        },
+                        _ => {}
+                    });
+                })
+            };
+            // la segunda llega cuando la primera ya sacó su número
+            let primera = subir(0);
+            let segunda = subir(1);
+
+            dar_vuelta(&embarque, 0);
+            dar_vuelta(&embarque, 0);
+            primera.join().unwrap();
+            segunda.join().unwrap();
+            assert_eq!(*orden.lock().unwrap(), vec![0, 1]);
+        });
+    }
+
+    #[test]
+    fn loom_la_fila_individual_ocupa_los_lugares_libres() {
+        explorar(2, || {
+            let embarque = Arc::new(Embarque::new(3, 1, true));
+            let sola_arriba = Arc::new(Senal::default());
+            let individual_en_fila = Arc::new(Senal::default());
+
+            // una persona sola sube por la fila principal
+            let sola = {
+                let (embarque, sola_arriba) = (embarque.clone(), sola_arriba.clone());
+                thread::spawn(move || {
+                    assert!(embarque.subir(1, |paso| {
+                        if let Paso::Arriba { .. } = paso {
+                            sola_arriba.dar();
+                        }
+                    }));
+                })
+            };
+            // otra espera en la fila individual cuando la primera ya subió
+            let individual = {
+                let (embarque, individual_en_fila) = (embarque.clone(), individual_en_fila.clone());
+                thread::spawn(move || {
+                    sola_arriba.esperar();
+                    assert!(embarque.subir_individual(|paso| {
+                        if let Paso::EnFila = paso {
+                            individual_en_fila.dar();
+                        }
+                    }));
+                })
+            };
+            // un grupo de 3 no entra en los 2 lugares libres y cede la
+            // unidad, que convoca a la fila individual
+            let grupo = {
+                let embarque = embarque.clone();
+                thread::spawn(move || {
+                    individual_en_fila.esperar();
+                    assert!(embarque.subir(3, |_| {}));
+                })
+            };
+
+            assert_eq!(dar_vuelta(&embarque, 0), (2, 1));
+            assert_eq!(dar_vuelta(&embarque, 0), (3, 0));
+            sola.join().unwrap();
+            individual.join().unwrap();
+            grupo.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_al_cerrar_el_anden_la_unidad_parte_incompleta() {
+        explorar(3, || {
+            let embarque = Arc::new(Embarque::new(2, 1, true));
+            let arriba = Arc::new(Senal::default());
+
+            let persona = {
+                let (embarque, arriba) = (embarque.clone(), arriba.clone());
+                thread::spawn(move || {
+                    assert!(embarque.subir(1, |paso| {
+                        if let Paso::Arriba { .. } = paso {
+                            arriba.dar();
+                        }
+                    }));
+                })
+            };
+            // se cierra el andén cuando ya subió, en lugar del timeout
+            let cierre = {
+                let embarque = embarque.clone();
+                thread::spawn(move || {
+                    arriba.esperar();
+                    embarque.cerrar_anden();
+                })
+            };
+
+            assert_eq!(dar_vuelta(&embarque, 0), (1, 0));
+            persona.join().unwrap();
+            cierre.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_la_unidad_que_parte_vacia_deja_el_anden_a_otra() {
+        explorar(2, || {
+            let embarque = Arc::new(Embarque::new(1, 2, true));
+            // la primera unidad en ocupar el andén parte vacía
+            embarque.cerrar_anden();
+
+            let unidades: Vec<_> = (0..2).map(|unidad| {
+                let embarque = embarque.clone();
+                thread::spawn(move || match embarque.esperar_pasajeros(unidad, Duration::from_secs(5)) {
+                    Espera::Llena(vuelta) => {
+                        let personas = vuelta.personas;
+                        embarque.partir(&vuelta);
+                        embarque.terminar_vuelta(vuelta);
+                        Some(personas)
+                    },
+                    Espera::Vacia => None,
+                    _ => panic!("la unidad {} no debería partir con lugares libres", unidad),
+                })
+            }).collect();
+            let persona = {
+                let embarque = embarque.clone();
+                thread::spawn(move || assert!(embarque.subir(1, |_| {})))
+            };
+
+            let mut vueltas: Vec<_> = unidades.into_iter().map(|hilo| hilo.join().unwrap()).collect();
+            vueltas.sort();
+            assert_eq!(vueltas, vec![None, Some(1)]);
+            persona.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn loom_al_suspender_se_vacian_la_fila_y_el_anden() {
+        explorar(3, || {
+            let embarque = Arc::new(Embarque::new(2, 1, true));
+
+            let persona = {
+                let embarque = embarque.clone();
+                thread::spawn(move || embarque.subir(1, |_| {}))
+            };
+            // la unidad da vueltas hasta que suspendido el embarque parte vacía
+            let unidad = {
+                let embarque = embarque.clone();
+                thread::spawn(move || {
+                    let mut llevo = 0;
+                    loop {
+                        match embarque.esperar_pasajeros(0, Duration::from_secs(5)) {
+                            Espera::Llena(vuelta) | Espera::Incompleta(vuelta) | Espera::Cedida(vuelta) => {
+                                llevo += vuelta.personas;
+                                embarque.partir(&vuelta);
+                                embarque.terminar_vuelta(vuelta);
+                            },
+                            Espera::Vacia => return llevo,
+                            Espera::Espuria => {}
+                        }
+                    }
+                })
+            };
+
+            embarque.suspender();
+            // la persona viajó o se fue de la fila sin subir
+            let subio = persona.join().unwrap();
+            assert_eq!(unidad.join().unwrap(), u32::from(subio));
+        });
+    }
+}
@@ -0,0 +1,186 @@
+//! Fila de entrada al parque.
+//!
+//! Reemplaza al semáforo de capacidad: las personas entran en orden de
+//! llegada a medida que se liberan lugares, el largo de la fila se puede
+//! consultar en cualquier momento y quien llega a una fila más larga de
+//! lo que tolera desiste sin entrar. Los grupos hacen la fila y entran
+//! juntos.
+
+use crate::sync::{Condvar, Mutex};
+
+/// Motivo por el que no se pudo entrar al parque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinEntrada {
+    /// Había más personas esperando de las que se toleraban; guarda el
+    /// largo de la fila.
+    FilaLarga(usize),
+    /// El grupo tiene más personas que la capacidad del parque.
+    NoEntra,
+}
+
+struct Estado {
+    lugares_libres: usize,
+    /// Número que le toca a la próxima persona que llegue.
+    siguiente: u64,
+    /// Número de la próxima persona en entrar.
+    atendiendo: u64,
+    /// Personas esperando, contando a todos los integrantes de cada
+    /// grupo.
+    largo: usize,
+    largo_maximo: usize,
+}
+
+/// Fila de entrada de un parque con `capacidad` lugares.
+pub struct Entrada {
+    capacidad: usize,
+    estado: Mutex<Estado>,
+    cv: Condvar,
+}
+
+impl Entrada {
+    pub fn new(capacidad: usize) -> Self {
+        Self {
+            capacidad,
+            estado: Mutex::new(Estado {
+                lugares_libres: capacidad,
+                siguiente: 0,
+                atendiendo: 0,
+                largo: 0,
+                largo_maximo: 0,
+            }),
+            cv: Condvar::new(),
+        }
+    }
+
+    /// Hace la fila hasta que sea el turno de las `personas` (una sola o
+    /// un grupo) y haya lugar para todas en el parque. Si al llegar hay
+    /// más de `tolerancia` personas esperando, desisten sin hacer la
+    /// fila. Un grupo más grande que el parque nunca entraría, así que
+    /// se rechaza en lugar de trabar a los que vienen detrás.
+    pub fn ingresar(&self, personas: usize, tolerancia: Option<u32>) -> Result<(), SinEntrada> {
+        if personas > self.capacidad {
+            return Err(SinEntrada::NoEntra);
+        }
+        let mut estado = self.estado.lock().expect("poisoned");
+        let largo = estado.largo;
+        if tolerancia.is_some_and(|tolerancia| largo > tolerancia as usize) {
+            return Err(SinEntrada::FilaLarga(largo));
+        }
+        let numero = estado.siguiente;
+        estado.siguiente += 1;
+        estado.largo += personas;
+        estado.largo_maximo = estado.largo_maximo.max(estado.largo);
+        while estado.atendiendo != numero || estado.lugares_libres < personas {
+            estado = self.cv.wait(estado).expect("poisoned");
+        }
+        estado.atendiendo += 1;
+        estado.largo -= personas;
+        estado.lugares_libres -= personas;
+        // puede haber lugar también para la siguiente
+        self.cv.notify_all();
+        Ok(())
+    }
+
+    /// Libera el lugar de una persona que salió del parque.
+    pub fn salir(&self) {
+        self.estado.lock().expect("poisoned").lugares_libres += 1;
+        self.cv.notify_all();
+    }
+
+    /// Cantidad de personas esperando para entrar.
+    pub fn largo(&self) -> usize {
+        self.estado.lock().expect("poisoned").largo
+    }
+
+    /// Máxima cantidad de personas que hubo esperando para entrar en
+    /// simultáneo.
+    pub fn largo_maximo(&self) -> usize {
+        self.estado.lock().expect("poisoned").largo_maximo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::{Arc, mpsc}, thread, time::Duration};
+
+    /// Espera a que haya `largo` personas en la fila.
+    fn esperar_largo(entrada: &Entrada, largo: usize) {
+        while entrada.largo() != largo {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn entran_en_orden_de_llegada() {
+        let entrada = Arc::new(Entrada::new(1));
+        entrada.ingresar(1, None).unwrap();
+        let (entro, orden) = mpsc::channel();
+
+        let hilos: Vec<_> = (0..5).map(|persona| {
+            let (entrada_hilo, entro) = (entrada.clone(), entro.clone());
+            let hilo = thread::spawn(move || {
+                entrada_hilo.ingresar(1, None).unwrap();
+                entro.send(persona).unwrap();
+            });
+            esperar_largo(&entrada, persona + 1);
+            hilo
+        }).collect();
+        assert_eq!(entrada.largo_maximo(), 5);
+
+        for persona in 0..5 {
+            entrada.salir();
+            assert_eq!(orden.recv().unwrap(), persona);
+        }
+        for hilo in hilos {
+            hilo.join().unwrap();
+        }
+        assert_eq!(entrada.largo(), 0);
+    }
+
+    #[test]
+    fn los_grupos_entran_juntos() {
+        let entrada = Arc::new(Entrada::new(3));
+        entrada.ingresar(1, None).unwrap();
+        entrada.ingresar(1, None).unwrap();
+
+        let grupo = {
+            let entrada = entrada.clone();
+            thread::spawn(move || entrada.ingresar(3, None))
+        };
+        esperar_largo(&entrada, 3);
+        // con un lugar libre no alcanza para el grupo
+        entrada.salir();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(entrada.largo(), 3);
+
+        entrada.salir();
+        assert_eq!(grupo.join().unwrap(), Ok(()));
+        assert_eq!(entrada.largo(), 0);
+    }
+
+    #[test]
+    fn desiste_si_la_fila_supera_su_tolerancia() {
+        let entrada = Arc::new(Entrada::new(1));
+        assert_eq!(entrada.ingresar(1, Some(0)), Ok(()));
+
+        let esperando = {
+            let entrada = entrada.clone();
+            thread::spawn(move || entrada.ingresar(1, Some(0)))
+        };
+        esperar_largo(&entrada, 1);
+        assert_eq!(entrada.ingresar(1, Some(0)), Err(SinEntrada::FilaLarga(1)));
+
+        entrada.salir();
+        assert_eq!(esperando.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn rechaza_grupos_mas_grandes_que_el_parque() {
+        let entrada = Entrada::new(2);
+        assert_eq!(entrada.ingresar(3, None), Err(SinEntrada::NoEntra));
+        assert_eq!(entrada.largo(), 0);
+        // no deja trabada la fila
+        assert_eq!(entrada.ingresar(2, None), Ok(()));
+    }
+}
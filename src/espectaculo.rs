@@ -0,0 +1,220 @@
+//! Espectáculos con funciones en horarios fijos.
+//!
+//! Un espectáculo no tiene hilo propio: sus funciones empiezan en
+//! momentos fijos del reloj simulado y las personas deciden ir cuando
+//! falta poco para alguna. Llegan caminando, ocupan una butaca, pagan la
+//! entrada y esperan a que empiece; si la sala está llena o llegan tarde
+//! se quedan afuera. Al terminar la función todo el público sale a la
+//! vez y busca un juego en la zona del espectáculo, lo que alarga de
+//! golpe las filas de los juegos cercanos.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{logger::describir, sync::Mutex};
+
+/// Parámetros de un espectáculo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfiguracionEspectaculo {
+    pub nombre: String,
+    pub precio: u32,
+    /// Butacas de la sala.
+    pub capacidad: u32,
+    pub duracion_ms: u64,
+    /// Momentos en que empieza cada función, en milisegundos simulados
+    /// desde el inicio y en orden.
+    pub horarios_ms: Vec<u64>,
+    /// Cuánto está dispuesta a esperar una persona en la sala a que
+    /// empiece la función, en milisegundos simulados.
+    pub anticipacion_ms: u64,
+    /// Probabilidad de que a una persona le interese ir a una función
+    /// que está por empezar.
+    pub interes: f64,
+    pub zona: Option<usize>,
+}
+
+/// Por qué una persona no pudo entrar a una función.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rechazo {
+    /// No quedaban butacas.
+    Llena,
+    /// La función ya había empezado.
+    Tarde,
+}
+
+impl fmt::Display for Rechazo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rechazo::Llena => write!(f, "no quedaban butacas"),
+            Rechazo::Tarde => write!(f, "ya había empezado"),
+        }
+    }
+}
+
+/// Público de una función.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Funcion {
+    pub inicio_ms: u64,
+    pub asistentes: u32,
+    /// Personas que llegaron con la sala llena.
+    pub sin_lugar: u32,
+    /// Personas que llegaron cuando ya había empezado.
+    pub tarde: u32,
+}
+
+/// Estadísticas de un espectáculo al terminar la simulación.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EstadisticasEspectaculo {
+    pub id: usize,
+    pub nombre: String,
+    pub precio: u32,
+    pub capacidad: u32,
+    pub funciones: Vec<Funcion>,
+    /// Cuántas personas del público fueron a cada juego al salir, por id
+    /// del juego.
+    pub publico_en_juegos: BTreeMap<usize, u32>,
+}
+
+impl EstadisticasEspectaculo {
+    pub fn asistentes(&self) -> u32 {
+        self.funciones.iter().map(|funcion| funcion.asistentes).sum()
+    }
+
+    pub fn recaudado(&self) -> u32 {
+        self.precio * self.asistentes()
+    }
+}
+
+#[derive(Debug)]
+pub struct Espectaculo {
+    pub id: usize,
+    pub nombre: String,
+    pub precio: u32,
+    pub capacidad: u32,
+    pub duracion_ms: u64,
+    pub anticipacion_ms: u64,
+    pub interes: f64,
+    funciones: Mutex<Vec<Funcion>>,
+    publico_en_juegos: Mutex<BTreeMap<usize, u32>>,
+}
+
+impl Espectaculo {
+    pub fn new(id: usize, configuracion: ConfiguracionEspectaculo) -> Self {
+        Self {
+            id,
+            nombre: configuracion.nombre,
+            precio: configuracion.precio,
+            capacidad: configuracion.capacidad,
+            duracion_ms: configuracion.duracion_ms,
+            anticipacion_ms: configuracion.anticipacion_ms,
+            interes: configuracion.interes,
+            funciones: Mutex::new(configuracion.horarios_ms.into_iter()
+                .map(|inicio_ms| Funcion { inicio_ms, ..Funcion::default() })
+                .collect()),
+            publico_en_juegos: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Id del espectáculo seguido de su nombre.
+    pub fn descripcion(&self) -> String {
+        describir(self.id, Some(&self.nombre))
+    }
+
+    pub fn inicio_ms(&self, funcion: usize) -> u64 {
+        self.funciones.lock().expect("poisoned")[funcion].inicio_ms
+    }
+
+    /// Las funciones a las que llega a tiempo alguien que está a
+    /// `caminata_ms` de la sala en el momento `ahora` sin tener que
+    /// esperar en la sala más que la anticipación, en orden.
+    pub fn proximas_funciones(&self, ahora: u64, caminata_ms: u64) -> Vec<usize> {
+        let llegada = ahora + caminata_ms;
+        self.funciones.lock().expect("poisoned")
+            .iter()
+            .enumerate()
+            .filter(|(_, funcion)| funcion.inicio_ms >= llegada && funcion.inicio_ms - llegada <= self.anticipacion_ms)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Hace entrar a `personas` a `funcion` en el momento `ahora`, si
+    /// no empezó y quedan butacas para todas.
+    pub fn entrar(&self, funcion: usize, personas: u32, ahora: u64) -> Result<(), Rechazo> {
+        let mut funciones = self.funciones.lock().expect("poisoned");
+        let funcion = &mut funciones[funcion];
+        if ahora > funcion.inicio_ms {
+            funcion.tarde += personas;
+            Err(Rechazo::Tarde)
+        } else if funcion.asistentes + personas > self.capacidad {
+            funcion.sin_lugar += personas;
+            Err(Rechazo::Llena)
+        } else {
+            funcion.asistentes += personas;
+            Ok(())
+        }
+    }
+
+    /// Registra que `personas` del público fueron al juego `juego` al
+    /// salir de una función.
+    pub fn registrar_publico(&self, juego: usize, personas: u32) {
+        *self.publico_en_juegos.lock().expect("poisoned").entry(juego).or_default() += personas;
+    }
+
+    pub fn estadisticas(&self) -> EstadisticasEspectaculo {
+        EstadisticasEspectaculo {
+            id: self.id,
+            nombre: self.nombre.clone(),
+            precio: self.precio,
+            capacidad: self.capacidad,
+            funciones: self.funciones.lock().expect("poisoned").clone(),
+            publico_en_juegos: self.publico_en_juegos.lock().expect("poisoned").clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn espectaculo() -> Espectaculo {
+        Espectaculo::new(0, ConfiguracionEspectaculo {
+            nombre: "Magia".into(),
+            precio: 4,
+            capacidad: 3,
+            duracion_ms: 500,
+            horarios_ms: vec![1000, 3000],
+            anticipacion_ms: 300,
+            interes: 1.0,
+            zona: None,
+        })
+    }
+
+    #[test]
+    fn se_va_a_las_funciones_a_las_que_se_llega_a_tiempo() {
+        let espectaculo = espectaculo();
+        assert!(espectaculo.proximas_funciones(0, 0).is_empty());
+        assert_eq!(espectaculo.proximas_funciones(700, 0), vec![0]);
+        assert_eq!(espectaculo.proximas_funciones(500, 200), vec![0]);
+        assert!(espectaculo.proximas_funciones(900, 200).is_empty());
+        assert_eq!(espectaculo.proximas_funciones(2800, 0), vec![1]);
+        assert!(espectaculo.proximas_funciones(3100, 0).is_empty());
+
+        let larga = Espectaculo { anticipacion_ms: 5000, ..espectaculo };
+        assert_eq!(larga.proximas_funciones(0, 0), vec![0, 1]);
+    }
+
+    #[test]
+    fn quienes_no_entran_quedan_afuera() {
+        let espectaculo = espectaculo();
+        assert_eq!(espectaculo.entrar(0, 2, 900), Ok(()));
+        assert_eq!(espectaculo.entrar(0, 2, 950), Err(Rechazo::Llena));
+        assert_eq!(espectaculo.entrar(0, 1, 1000), Ok(()));
+        assert_eq!(espectaculo.entrar(0, 1, 1001), Err(Rechazo::Tarde));
+        espectaculo.registrar_publico(2, 3);
+
+        let estadisticas = espectaculo.estadisticas();
+        assert_eq!(estadisticas.funciones[0], Funcion { inicio_ms: 1000, asistentes: 3, sin_lugar: 2, tarde: 1 });
+        assert_eq!(estadisticas.funciones[1].asistentes, 0);
+        assert_eq!(estadisticas.recaudado(), 12);
+        assert_eq!(estadisticas.publico_en_juegos[&2], 3);
+    }
+}
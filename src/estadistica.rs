@@ -0,0 +1,84 @@
+/// Valores críticos de la t de Student para un intervalo de confianza
+/// del 95% (dos colas), indexados por grados de libertad - 1. A partir
+/// de 30 grados de libertad se usa la aproximación normal.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+const Z_95: f64 = 1.96;
+
+/// Resumen estadístico de una muestra.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resumen {
+    pub n: usize,
+    pub media: f64,
+    /// Desvío estándar muestral.
+    pub desvio: f64,
+    /// Intervalo de confianza del 95% para la media.
+    pub ic95: (f64, f64),
+}
+
+/// Calcula media, desvío estándar e intervalo de confianza del 95% de
+/// `valores`. Con menos de dos valores el desvío es 0 y el intervalo se
+/// reduce a la media.
+pub fn resumir(valores: &[f64]) -> Resumen {
+    let n = valores.len();
+    if n == 0 {
+        return Resumen { n, media: 0.0, desvio: 0.0, ic95: (0.0, 0.0) };
+    }
+    let media = valores.iter().sum::<f64>() / n as f64;
+    if n == 1 {
+        return Resumen { n, media, desvio: 0.0, ic95: (media, media) };
+    }
+    let varianza = valores.iter().map(|v| (v - media).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let desvio = varianza.sqrt();
+    let t = T_95.get(n - 2).copied().unwrap_or(Z_95);
+    let margen = t * desvio / (n as f64).sqrt();
+    Resumen { n, media, desvio, ic95: (media - margen, media + margen) }
+}
+
+/// Cuantil `q` (entre 0 y 1) de `ordenados`, que no puede estar vacío,
+/// interpolando linealmente entre los dos valores más cercanos.
+pub fn cuantil(ordenados: &[f64], q: f64) -> f64 {
+    let posicion = q * (ordenados.len() - 1) as f64;
+    let (abajo, arriba) = (posicion.floor() as usize, posicion.ceil() as usize);
+    ordenados[abajo] + (ordenados[arriba] - ordenados[abajo]) * (posicion - abajo as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuantiles_interpolados() {
+        let valores = [1.0, 2.0, 4.0, 8.0];
+        assert_eq!(cuantil(&valores, 0.0), 1.0);
+        assert_eq!(cuantil(&valores, 0.5), 3.0);
+        assert_eq!(cuantil(&valores, 1.0), 8.0);
+        assert_eq!(cuantil(&[5.0], 0.25), 5.0);
+    }
+
+    #[test]
+    fn muestra_constante() {
+        let resumen = resumir(&[4.0; 10]);
+        assert_eq!(resumen.media, 4.0);
+        assert_eq!(resumen.desvio, 0.0);
+        assert_eq!(resumen.ic95, (4.0, 4.0));
+    }
+
+    #[test]
+    fn intervalo_con_t_de_student() {
+        let resumen = resumir(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(resumen.media, 3.0);
+        assert!((resumen.desvio - 2.5f64.sqrt()).abs() < 1e-9);
+        let margen = 2.776 * resumen.desvio / 5f64.sqrt();
+        assert!((resumen.ic95.1 - (3.0 + margen)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn muestras_vacias_o_de_un_elemento() {
+        assert_eq!(resumir(&[]).n, 0);
+        assert_eq!(resumir(&[7.0]).ic95, (7.0, 7.0));
+    }
+}
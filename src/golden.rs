@@ -0,0 +1,196 @@
+//! Tests de regresión contra el registro de eventos.
+//!
+//! Cada escenario se corre en modo rápido con semilla fija y su registro
+//! normalizado se compara contra `tests/golden/<escenario>.log`. Para
+//! regenerar los archivos después de un cambio de comportamiento
+//! intencional:
+//!
+//! ```bash
+//! $ PARQUE_BLESS=1 cargo test golden
+//! ```
+//!
+//! y revisar el diff de `tests/golden/` antes de commitear.
+
+use std::{collections::BTreeMap, fs, path::PathBuf, sync::Arc};
+
+use crate::{args::Args, arquetipo::Arquetipo, logger::{Logger, etiqueta}, simulacion::{Resultado, simular}};
+
+
+/// Normaliza el registro de eventos para que no dependa del orden en
+/// que corrieron los hilos: se descartan los tiempos, se agrupan los
+/// mensajes por etiqueta (respetando el orden dentro de cada una) y se
+/// descartan las secuencias que dependen de los tiempos de ejecución
+/// (las vueltas de los juegos y sus unidades, que dependen de quién llega
+/// a tiempo, y la caja que la administración informa periódicamente).
+/// De la administración sólo quedan los argumentos de la simulación.
+/// De cada unidad de un juego se conservan, en orden, los desperfectos
+/// hasta su última vuelta (que salen de su propio generador, sorteado una
+/// vez por vuelta) y el cierre, y de cada juego quiénes entraron,
+/// ordenado. Al final se
+/// agrega el resumen de lo que no depende de los tiempos, incluido lo que
+/// se sorteó para cada persona, para que un cambio en los generadores
+/// aleatorios cambie el registro.
+fn normalizar(log: &str, args: &Args, resultado: &Resultado) -> String {
+    let juegos: Vec<String> = resultado.juegos.iter()
+        .map(|juego| etiqueta("JUEGO", juego.id, juego.nombre.as_deref()))
+        .collect();
+    let mut por_tag: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut ingresos: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    let mut unidades: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for linea in log.lines() {
+        let mut partes = linea.splitn(3, "| ");
+        let (tag, mensaje) = match (partes.next(), partes.next(), partes.next()) {
+            (Some(_tiempo), Some(tag), Some(mensaje)) => (tag.trim(), mensaje),
+            _ => continue
+        };
+        let es_juego = |juego: &&String| tag == *juego || tag.strip_prefix(juego.as_str()).is_some_and(|resto| resto.starts_with('.'));
+        if let Some(juego) = juegos.iter().find(es_juego) {
+            if mensaje.contains("logró entrar al juego") {
+                ingresos.entry(juego).or_default().push(mensaje);
+            } else {
+                unidades.entry(tag).or_default().push(mensaje);
+            }
+            continue;
+        }
+        if tag == "ADMIN" {
+            // de la administración sólo se conservan los argumentos, sin
+            // la ruta del ejecutable, que cambia con cada compilación
+            if let Some(argumentos) = mensaje.strip_prefix("Iniciando simulación con: ") {
+                por_tag.entry(tag).or_default().push(argumentos.split_once(' ').map_or("", |(_exe, resto)| resto));
+            }
+            continue;
+        }
+        por_tag.entry(tag).or_default().push(mensaje);
+    }
+
+    for (tag, mensajes) in unidades {
+        // un desperfecto sorteado después de la última vuelta compite con
+        // el cierre del parque, así que sólo cuentan los anteriores
+        let vueltas = mensajes.iter()
+            .rposition(|mensaje| mensaje.starts_with("Arrancando la vuelta"))
+            .map_or(0, |ultima| ultima + 1);
+        let eventos = por_tag.entry(tag).or_default();
+        eventos.extend(mensajes[..vueltas].iter().filter(|mensaje| mensaje.starts_with("Desperfecto")));
+        eventos.extend(mensajes.iter().filter(|mensaje| **mensaje == "Cerrado"));
+    }
+
+    let mut normalizado = String::new();
+    for (tag, mensajes) in por_tag {
+        normalizado += &format!("== {}\n", tag);
+        for mensaje in mensajes {
+            normalizado += &format!("{}\n", mensaje);
+        }
+    }
+    for (juego, mut mensajes) in ingresos {
+        mensajes.sort_unstable();
+        normalizado += &format!("== {} (ingresos)\n", juego);
+        for mensaje in mensajes {
+            normalizado += &format!("{}\n", mensaje);
+        }
+    }
+
+    normalizado += &format!("== RESUMEN\ncaja: $ {}\n", resultado.caja);
+    for juego in &resultado.juegos {
+        normalizado += &format!("juego {}: {} personas\n", juego.id, juego.personas_transportadas);
+    }
+    let configuraciones = args.personas();
+    for persona in &resultado.personas {
+        let configuracion = &configuraciones[persona.id];
+        let atributos = &configuracion.atributos;
+        normalizado += &format!(
+            "persona {}: gastó $ {} de $ {} en {} juegos; llegó a los {} ms, grupo {:?}, arquetipo {:?}, {} años, {} cm{}\n",
+            persona.id, persona.gastado, persona.presupuesto_inicial, persona.juegos_jugados,
+            configuracion.llegada_ms, configuracion.grupo, configuracion.arquetipo.as_ref().map(|a| &a.nombre),
+            atributos.edad, atributos.altura_cm,
+            if atributos.movilidad_reducida { ", movilidad reducida" } else { "" }
+        );
+    }
+    normalizado
+}
+
+fn ruta_golden(nombre: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.log", nombre))
+}
+
+/// Corre el escenario y lo compara contra su archivo golden, o lo
+/// reescribe si está definida la variable de entorno `PARQUE_BLESS`.
+fn verificar_golden(nombre: &str, args: &Args) {
+    let logger = Arc::new(Logger::new_to_memory());
+    let resultado = simular(args, logger.clone()).expect("la simulación falló");
+    let actual = normalizar(&logger.contenido(), args, &resultado);
+    let ruta = ruta_golden(nombre);
+
+    if std::env::var_os("PARQUE_BLESS").is_some() {
+        fs::create_dir_all(ruta.parent().unwrap()).expect("no se pudo crear tests/golden");
+        fs::write(&ruta, &actual).expect("no se pudo escribir el golden");
+        return;
+    }
+
+    let esperado = fs::read_to_string(&ruta).unwrap_or_else(|_| panic!(
+        "no existe {}, generarlo con PARQUE_BLESS=1 cargo test golden", ruta.display()
+    ));
+    if esperado != actual {
+        let diferencia = esperado.lines().zip(actual.lines())
+            .enumerate()
+            .find(|(_, (e, a))| e != a)
+            .map(|(i, (e, a))| format!("línea {}:\n  esperado: {}\n  obtenido: {}", i + 1, e, a))
+            .unwrap_or_else(|| format!(
+                "distinta cantidad de líneas ({} != {})",
+                esperado.lines().count(), actual.lines().count()
+            ));
+        panic!(
+            "el registro de '{}' no coincide con {}\n{}\nSi el cambio es intencional correr PARQUE_BLESS=1 cargo test golden",
+            nombre, ruta.display(), diferencia
+        );
+    }
+}
+
+fn escenario(presupuestos: Vec<u32>, capacidad_parque: u32, semilla: u32) -> Args {
+    let mut args = Args::default();
+    args.presupuesto_personas = presupuestos;
+    args.capacidad_parque = capacidad_parque;
+    args.semilla = semilla;
+    args.rapido = true;
+    args
+}
+
+#[test]
+fn golden_parque_por_defecto() {
+    let mut args = escenario(vec![40; 5], 10, 1);
+    args.resolver().unwrap();
+    verificar_golden("parque_por_defecto", &args);
+}
+
+#[test]
+fn golden_precios_y_capacidades_distintas() {
+    let mut args = escenario(vec![7, 33, 18, 1, 50, 25, 12, 40], 3, 7);
+    args.costo_juegos = Some(vec![3, 8, 11]);
+    args.capacidad_juegos = Some(vec![1, 2, 4]);
+    args.duracion_juegos = Some(vec![10, 25, 40]);
+    args.resolver().unwrap();
+    verificar_golden("precios_y_capacidades_distintas", &args);
+}
+
+#[test]
+fn golden_grupos_restricciones_llegadas_y_arquetipos() {
+    let mut args = escenario(vec![30, 30, 30, 20, 20, 45, 10, 35, 25], 6, 11);
+    args.grupos = Some(vec![3, 2]);
+    args.intervalo_llegadas = Some(15);
+    args.atributos_personas.probabilidad_movilidad_reducida = 0.3;
+    args.costo_juegos = Some(vec![5, 5, 5]);
+    args.capacidad_juegos = Some(vec![3, 2, 3]);
+    args.altura_minima_juegos = Some(vec![Some(130), None, None]);
+    args.edad_minima_juegos = Some(vec![None, Some(12), None]);
+    args.accesible_juegos = Some(vec![true, true, false]);
+    // arquetipos que eligen sin mirar las filas, para que su recorrido no
+    // dependa de los tiempos
+    args.intensidad_juegos = Some(vec![1, 3, 5]);
+    args.arquetipos.push(Arquetipo { intensidad_maxima: Some(3), ..Arquetipo::neutro("tranquilo") });
+    args.arquetipos.push(Arquetipo::neutro("audaz"));
+    args.mezcla_visitantes = Some(vec![("tranquilo".into(), 1), ("audaz".into(), 1)]);
+    args.resolver().unwrap();
+    verificar_golden("grupos_restricciones_llegadas_y_arquetipos", &args);
+}
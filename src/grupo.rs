@@ -0,0 +1,331 @@
+//! Grupos de visitantes (familias, amigos) que recorren el parque
+//! juntos.
+//!
+//! Un grupo es un único hilo que actúa por todos sus integrantes: hacen
+//! juntos la fila de entrada, eligen juntos cada juego, suben a la misma
+//! unidad y se van juntos. Las estadísticas se siguen llevando por
+//! persona. Una persona que va sola es un grupo de uno, que habla en
+//! primera persona del singular y decide con su propio generador.
+
+use std::sync::Arc;
+
+use rand::{Rng, SeedableRng, prelude::StdRng};
+
+use crate::{
+    logger::TaggedLogger,
+    mapa::Lugar,
+    parque::{Parque, SinJuego},
+    persona::{EstadisticasPersona, Persona},
+    restricciones::Atributos,
+    salida::MotivoSalida,
+};
+
+pub struct Grupo {
+    integrantes: Vec<Persona>,
+    /// Si juntan el presupuesto de todos los integrantes. Si no, cada uno
+    /// paga con el suyo y sólo eligen juegos que todos pueden pagar.
+    presupuesto_compartido: bool,
+    /// Generador con el que decide el grupo. Una persona sola no tiene,
+    /// decide con el suyo.
+    rng: Option<StdRng>,
+    log: TaggedLogger,
+}
+
+impl Grupo {
+    pub fn new(log: TaggedLogger, integrantes: Vec<Persona>, presupuesto_compartido: bool, semilla: u64) -> Self {
+        Self {
+            integrantes,
+            presupuesto_compartido,
+            rng: Some(StdRng::seed_from_u64(semilla)),
+            log,
+        }
+    }
+
+    /// Una persona que visita el parque sola, con su misma etiqueta.
+    pub fn solo(persona: Persona) -> Self {
+        Self {
+            log: persona.log().con_tag(persona.log().tag()),
+            integrantes: vec![persona],
+            presupuesto_compartido: false,
+            rng: None,
+        }
+    }
+
+    pub fn estadisticas(&self) -> Vec<EstadisticasPersona> {
+        self.integrantes.iter().map(Persona::estadisticas).collect()
+    }
+
+    fn es_solo(&self) -> bool {
+        self.rng.is_none()
+    }
+
+    fn rng(&mut self) -> &mut StdRng {
+        match &mut self.rng {
+            Some(rng) => rng,
+            None => self.integrantes[0].rng(),
+        }
+    }
+
+    /// Escribe `solo` en el log si es una persona sola, o `grupo` si no.
+    fn decir(&self, solo: &str, grupo: &str) {
+        self.log.write(if self.es_solo() { solo } else { grupo });
+    }
+
+    /// Informa al watchdog del parque qué está haciendo cada integrante.
+    fn reportar(&self, parque: &Parque, estado: String) {
+        for persona in &self.integrantes {
+            persona.reportar(parque, estado.clone());
+        }
+    }
+
+    /// Camina con todos los integrantes hasta `hasta` por el mapa del
+    /// parque.
+    fn caminar(&mut self, parque: &Parque, hasta: Lugar) {
+        let desde = self.integrantes[0].ubicacion();
+        let ms = parque.caminar(self.integrantes.len() as u32, desde, hasta, &self.log, |estado| self.reportar(parque, estado));
+        for persona in self.integrantes.iter_mut() {
+            persona.registrar_caminata(ms, hasta);
+        }
+    }
+
+    /// Si a quien encabeza el grupo le interesa alguna función que está
+    /// por empezar y todos la pueden pagar, van a verla juntos. Devuelve
+    /// si fueron.
+    fn ir_a_espectaculo(&mut self, parque: &Parque) -> bool {
+        let presupuesto = self.presupuesto_por_persona();
+        let (espectaculo, funcion) = match parque.elegir_funcion(presupuesto, self.integrantes[0].ubicacion(), |e, f| self.integrantes[0].funcion_considerada(e, f)) {
+            Some(elegida) => elegida,
+            None => return false
+        };
+        for persona in self.integrantes.iter_mut() {
+            persona.considerar_funcion(espectaculo.id, funcion);
+        }
+        if !self.rng().gen_bool(espectaculo.interes) {
+            return false;
+        }
+        self.caminar(parque, Lugar::Espectaculo(espectaculo.id));
+        match espectaculo.entrar(funcion, self.integrantes.len() as u32, parque.reloj().ahora()) {
+            Ok(()) => {
+                for persona in self.integrantes.iter_mut() {
+                    persona.pagar_espectaculo(parque, espectaculo);
+                }
+                parque.ver_funcion(espectaculo, funcion, &self.log, |estado| self.reportar(parque, estado));
+                for persona in self.integrantes.iter_mut() {
+                    persona.registrar_funcion(espectaculo);
+                }
+            },
+            Err(rechazo) => {
+                if !self.es_solo() {
+                    self.log.write(&format!("No pudimos entrar al espectáculo {}: {}", espectaculo.descripcion(), rechazo));
+                }
+                for persona in self.integrantes.iter_mut() {
+                    persona.registrar_rechazo(espectaculo, rechazo);
+                }
+            },
+        }
+        true
+    }
+
+    /// Lo que puede pagar cada integrante por un juego.
+    fn presupuesto_por_persona(&mut self) -> u32 {
+        if self.presupuesto_compartido {
+            Persona::repartir_presupuesto(&mut self.integrantes);
+        }
+        self.integrantes.iter().map(Persona::presupuesto).min().unwrap_or(0)
+    }
+
+    pub fn visitar_parque(&mut self, parque: Arc<Parque>) {
+        let cantidad = self.integrantes.len();
+        let llegada_ms = self.integrantes.iter().map(Persona::llegada_ms).min().unwrap_or(0);
+        if llegada_ms > 0 {
+            parque.reloj().dormir(llegada_ms);
+        }
+        // decide el más impaciente
+        let tolerancia = self.integrantes.iter().filter_map(Persona::tolerancia_fila).min();
+        let largo = parque.largo_fila_entrada();
+        if self.es_solo() {
+            self.log.write("Esperando para entrar al parque");
+            self.reportar(&parque, format!("en la fila de entrada al parque ({} personas)", largo));
+        } else {
+            self.log.write(&format!("Esperando para entrar al parque con {} personas", cantidad));
+            self.reportar(&parque, format!("en la fila de entrada al parque con su grupo ({} personas)", largo));
+        }
+        let ingreso = parque.ingresar_grupo(cantidad, tolerancia);
+        for persona in self.integrantes.iter_mut() {
+            persona.registrar_entrada(ingreso);
+        }
+        if ingreso.is_err() {
+            if !self.es_solo() {
+                self.log.write("Nos fuimos sin entrar");
+            }
+            let estado = if self.es_solo() { "fuera del parque (desistió)" } else { "fuera del parque (su grupo desistió)" };
+            for persona in &self.integrantes {
+                persona.reportar_salida(&parque, estado);
+            }
+            return;
+        }
+        self.decir(&format!("Entre al parque con $ {}", self.integrantes[0].presupuesto()), "Entramos al parque");
+        let atributos: Vec<Atributos> = self.integrantes.iter().map(|p| *p.atributos()).collect();
+        // elige los juegos según el arquetipo de quien encabeza el grupo
+        let arquetipo = self.integrantes[0].arquetipo().cloned();
+        for persona in self.integrantes.iter_mut() {
+            persona.registrar_ingreso(&parque);
+        }
+
+        // se van cuando cualquiera de los integrantes quiere irse, y
+        // descansan cuando cualquiera está cansado
+        let motivo = loop {
+            if self.integrantes.iter().all(|p| p.presupuesto() == 0) {
+                break MotivoSalida::Dinero;
+            }
+            if let Some(motivo) = self.integrantes.iter().filter_map(|p| p.motivo_para_irse(&parque)).max() {
+                break motivo;
+            }
+            if parque.irse_por_el_clima(self.rng()) {
+                break MotivoSalida::Clima;
+            }
+            if self.ir_a_espectaculo(&parque) {
+                continue;
+            }
+            let presupuesto = self.presupuesto_por_persona();
+            if self.es_solo() {
+                self.reportar(&parque, format!("eligiendo juego con $ {}", presupuesto));
+            } else {
+                self.reportar(&parque, format!("eligiendo juego con su grupo, $ {} cada uno", presupuesto));
+            }
+            let desde = self.integrantes[0].ubicacion();
+            let juego = match parque.elegir_juego_para_grupo(presupuesto, &atributos, arquetipo.as_ref(), desde, self.rng()) {
+                Ok(juego) => juego,
+                Err(SinJuego::Cerrados) => {
+                    parque.esperar_reapertura(&self.log, |estado| self.reportar(&parque, estado));
+                    continue;
+                },
+                Err(SinJuego::Ninguno) => break MotivoSalida::Dinero
+            };
+            let publico: Vec<Option<usize>> = self.integrantes.iter_mut().map(Persona::salir_del_publico).collect();
+            if let Some(espectaculo) = publico[0] {
+                parque.espectaculo(espectaculo).registrar_publico(juego.id, publico.len() as u32);
+            }
+            self.caminar(&parque, Lugar::Juego(juego.id));
+            self.log.write(&format!("Entrando a la fila del juego {}.", juego.descripcion()));
+            if juego.agregar_a_la_fila(&mut self.integrantes) {
+                self.decir(&format!("Jugué al juego {} y salí.", juego.descripcion()),
+                           &format!("Jugamos al juego {} y salimos.", juego.descripcion()));
+            } else {
+                self.decir(&format!("El juego {} cerró por la tormenta, dejé la fila.", juego.descripcion()),
+                           &format!("El juego {} cerró por la tormenta, dejamos la fila.", juego.descripcion()));
+            }
+            // si alguno compra comida van todos juntos al puesto
+            let comidas: Vec<Option<u32>> = self.integrantes.iter_mut().map(Persona::decidir_comida).collect();
+            if comidas.iter().any(Option::is_some) {
+                if let Some(puesto) = parque.puesto_mas_cercano(self.integrantes[0].ubicacion()) {
+                    self.caminar(&parque, Lugar::Puesto(puesto));
+                }
+            }
+            for (persona, monto) in self.integrantes.iter_mut().zip(comidas) {
+                if let Some(monto) = monto {
+                    persona.comprar_comida(&parque, monto);
+                }
+            }
+            if let Some(cansancio) = self.integrantes.iter().find_map(Persona::cansada) {
+                if self.rng().gen_bool(cansancio.probabilidad_irse) {
+                    break MotivoSalida::Cansancio;
+                }
+                if self.es_solo() {
+                    self.log.write(&format!("Necesito descansar, descanso {} ms", cansancio.descanso_ms));
+                    self.reportar(&parque, "descansando".into());
+                } else {
+                    self.log.write(&format!("Descansamos {} ms", cansancio.descanso_ms));
+                    self.reportar(&parque, "descansando con su grupo".into());
+                }
+                parque.reloj().dormir(cansancio.descanso_ms);
+                for persona in self.integrantes.iter_mut() {
+                    persona.registrar_descanso();
+                }
+            }
+        };
+
+        if self.es_solo() {
+            let presupuesto = self.integrantes[0].presupuesto();
+            self.log.write(&match motivo {
+                MotivoSalida::Dinero => format!("No me alcanza para ningun juego (me quedaron $ {})", presupuesto),
+                MotivoSalida::Tiempo => format!("Se me terminó el tiempo (me quedaron $ {})", presupuesto),
+                MotivoSalida::Cansancio => format!("Me cansé, me voy (me quedaron $ {})", presupuesto),
+                MotivoSalida::Insatisfaccion => format!("No la estoy pasando bien, me voy (me quedaron $ {})", presupuesto),
+                MotivoSalida::Cierre => format!("Cerró el parque (me quedaron $ {})", presupuesto),
+                MotivoSalida::Clima => format!("Con este clima prefiero irme (me quedaron $ {})", presupuesto),
+            });
+        } else {
+            self.log.write(match motivo {
+                MotivoSalida::Dinero => "No hay ningún juego que podamos pagar y al que podamos subir todos",
+                MotivoSalida::Tiempo => "Se nos terminó el tiempo",
+                MotivoSalida::Cansancio => "Nos cansamos, nos vamos",
+                MotivoSalida::Insatisfaccion => "No la estamos pasando bien, nos vamos",
+                MotivoSalida::Cierre => "Cerró el parque",
+                MotivoSalida::Clima => "Con este clima preferimos irnos",
+            });
+        }
+        self.caminar(&parque, Lugar::Entrada);
+        for persona in self.integrantes.iter_mut() {
+            persona.registrar_salida(&parque, motivo);
+        }
+        for persona in &self.integrantes {
+            parque.salir_persona();
+            persona.reportar_salida(&parque, "fuera del parque");
+        }
+        self.decir("Me fui del parque", "Nos fuimos del parque");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        espectaculo::{ConfiguracionEspectaculo, Funcion},
+        logger::Logger,
+        persona::ConfiguracionPersona,
+        reloj::Reloj,
+        satisfaccion::{PUNTOS_POR_ESPECTACULO, PUNTOS_SIN_LUGAR},
+    };
+
+    fn crear_logger() -> TaggedLogger {
+        TaggedLogger::new("PERSONA 0", Arc::new(Logger::new_to_null()))
+    }
+
+    #[test]
+    fn sin_butacas_prueba_la_funcion_siguiente_y_no_repite() {
+        let parque = Parque::new(crear_logger(), 10, Reloj::real()).con_espectaculos(vec![ConfiguracionEspectaculo {
+            nombre: "Magia".into(),
+            precio: 5,
+            capacidad: 2,
+            duracion_ms: 10,
+            horarios_ms: vec![200, 400],
+            anticipacion_ms: 1000,
+            interes: 1.0,
+            zona: None,
+        }]);
+        // la primera función ya está llena
+        parque.espectaculo(0).entrar(0, 2, 0).unwrap();
+        let persona = Persona::new(crear_logger(), 0, ConfiguracionPersona { presupuesto: 20, ..Default::default() }, 0);
+        let mut solo = Grupo::solo(persona);
+
+        assert!(solo.ir_a_espectaculo(&parque));
+        assert_eq!(solo.estadisticas()[0].sin_lugar, 1);
+        assert_eq!(solo.estadisticas()[0].gastado, 0);
+
+        // descarta la función en la que no entró y va a la siguiente
+        assert!(solo.ir_a_espectaculo(&parque));
+        let estadisticas = &solo.estadisticas()[0];
+        assert_eq!((estadisticas.espectaculos, estadisticas.gastado), (1, 5));
+        assert_eq!(estadisticas.satisfaccion.espectaculos, PUNTOS_POR_ESPECTACULO - PUNTOS_SIN_LUGAR);
+        assert!(parque.reloj().ahora() >= 410);
+        assert_eq!(parque.obtener_caja(), 5);
+
+        // nadie ve dos veces el mismo espectáculo
+        assert!(!solo.ir_a_espectaculo(&parque));
+        let funciones = parque.espectaculo(0).estadisticas().funciones;
+        assert_eq!(funciones[0], Funcion { inicio_ms: 200, asistentes: 2, sin_lugar: 1, tarde: 0 });
+        assert_eq!(funciones[1].asistentes, 1);
+        assert_eq!(solo.integrantes[0].salir_del_publico(), Some(0));
+    }
+}
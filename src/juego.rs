@@ -1,109 +1,433 @@
-use std::{sync::Arc, sync::Barrier, sync::Condvar, sync::Mutex, sync::RwLock, sync::atomic::AtomicBool, sync::atomic::{AtomicU32, Ordering}, time::Duration};
-use std::thread;
+use std::{sync::Arc, sync::Mutex, sync::atomic::AtomicBool, sync::atomic::{AtomicU32, AtomicU64, Ordering}};
 
 use rand::{Rng, SeedableRng, prelude::StdRng};
-use std_semaphore::Semaphore;
 
-use crate::{logger::TaggedLogger, parque::Parque, persona::Persona};
+use crate::{embarque::{Embarque, Espera, Paso}, logger::{TaggedLogger, describir}, parque::Parque, persona::Persona, restricciones::Restricciones};
 
-const PROBABILIDAD_DE_DESPERFECTOS: f64 = 0.05; // 5%
-const TIEMPO_MAXIMO_ARREGLO_DESPERFECTO: u64 = 25;
+pub const PROBABILIDAD_DE_DESPERFECTOS: f64 = 0.05; // 5%
+pub const TIEMPO_MAXIMO_ARREGLO_DESPERFECTO: u32 = 25;
+/// Tiempo máximo que se espera a que se llene el juego antes de
+/// arrancar la vuelta, en milisegundos.
+pub const TIEMPO_ESPERA_PERSONAS: u64 = 5000;
+/// Intensidad de los juegos más extremos; la más tranquila es 1.
+pub const INTENSIDAD_MAXIMA: u32 = 5;
+/// Cada cuánto se fija una unidad cerrada por el clima si volvió a abrir,
+/// en milisegundos.
+pub const ESPERA_REAPERTURA: u64 = 50;
+
+/// Parámetros de un juego del parque.
+#[derive(Debug, Clone)]
+pub struct ConfiguracionJuego {
+    pub nombre: Option<String>,
+    pub precio: u32,
+    /// Cantidad máxima de personas por vuelta.
+    pub capacidad: u32,
+    /// Duración de la vuelta, en milisegundos.
+    pub duracion_ms: u32,
+    /// Cantidad de unidades idénticas que cargan de la misma fila.
+    pub unidades: u32,
+    /// Tiempo que tarda en subir cada persona, en milisegundos.
+    pub carga_ms: u32,
+    /// Tiempo que tarda en bajar cada persona, en milisegundos.
+    pub descarga_ms: u32,
+    /// Si las personas suben en orden de llegada a la fila. Si no,
+    /// cualquiera de la fila puede pasar primero.
+    pub fila_justa: bool,
+    /// Si tiene una fila individual para ocupar los lugares que quedan
+    /// libres.
+    pub fila_individual: bool,
+    /// Requisitos de altura, edad y accesibilidad para subir.
+    pub restricciones: Restricciones,
+    /// Qué tan intenso es, de 1 a `INTENSIDAD_MAXIMA`.
+    pub intensidad: u32,
+    /// Probabilidad de que ocurra un desperfecto antes de cada vuelta.
+    pub probabilidad_desperfecto: f64,
+    /// Tiempo máximo que lleva arreglar un desperfecto, en milisegundos.
+    pub tiempo_maximo_arreglo_ms: u32,
+    /// Si devuelve la entrada a quienes esperaron en la fila mientras
+    /// ocurría un desperfecto.
+    pub reembolso_desperfectos: bool,
+    /// Si está bajo techo. Los juegos al aire libre cierran durante las
+    /// tormentas.
+    pub cubierto: bool,
+}
+
+/// Un juego como los del parque por defecto: precio 10, dos lugares,
+/// vueltas de 25 ms, una unidad y fila justa, sin restricciones.
+impl Default for ConfiguracionJuego {
+    fn default() -> Self {
+        Self {
+            nombre: None,
+            precio: 10,
+            capacidad: 2,
+            duracion_ms: 25,
+            unidades: 1,
+            carga_ms: 0,
+            descarga_ms: 0,
+            fila_justa: true,
+            fila_individual: false,
+            restricciones: Restricciones::default(),
+            intensidad: 3,
+            probabilidad_desperfecto: PROBABILIDAD_DE_DESPERFECTOS,
+            tiempo_maximo_arreglo_ms: TIEMPO_MAXIMO_ARREGLO_DESPERFECTO,
+            reembolso_desperfectos: false,
+            cubierto: false,
+        }
+    }
+}
+
+/// Estadísticas de una unidad de un juego al terminar la simulación.
+#[derive(Debug, Clone)]
+pub struct EstadisticasUnidad {
+    pub vueltas: u32,
+    pub personas_transportadas: u32,
+    /// Tiempo total que pasó la unidad subiendo personas, dando vueltas
+    /// y bajando personas, en milisegundos simulados.
+    pub carga_ms: u64,
+    pub vuelta_ms: u64,
+    pub descarga_ms: u64,
+}
+
+/// Estadísticas de un juego al terminar la simulación. Las vueltas y
+/// las personas son el total de todas las unidades.
+#[derive(Debug, Clone)]
+pub struct EstadisticasJuego {
+    pub id: usize,
+    pub nombre: Option<String>,
+    /// Capacidad de cada unidad.
+    pub capacidad: u32,
+    /// Duración de la vuelta, en milisegundos.
+    pub duracion_ms: u32,
+    pub vueltas: u32,
+    pub personas_transportadas: u32,
+    pub desperfectos: u32,
+    /// Máxima cantidad de personas arriba de una misma unidad en
+    /// simultáneo.
+    pub ocupacion_maxima: u32,
+    /// Suma de los tiempos que esperaron en la fila las personas que
+    /// subieron, en milisegundos simulados.
+    pub espera_total_ms: u64,
+    /// Máxima espera en la fila de una persona, en milisegundos
+    /// simulados.
+    pub espera_maxima_ms: u64,
+    /// Veces que una persona subió antes que otra que había llegado
+    /// antes a la fila.
+    pub adelantamientos: u64,
+    /// Vueltas que partieron sin completarse porque el grupo primero en
+    /// la fila no entraba en los lugares libres.
+    pub vueltas_cedidas: u32,
+    /// Si el juego tiene fila individual.
+    pub fila_individual: bool,
+    pub restricciones: Restricciones,
+    /// Personas que subieron por la fila individual, ocupando lugares
+    /// que hubieran quedado libres.
+    pub individuales: u32,
+    /// Vueltas que salieron llenas gracias a la fila individual.
+    pub vueltas_completadas_por_individuales: u32,
+    /// Entradas devueltas por desperfectos durante la espera.
+    pub reembolsos: u32,
+    pub cubierto: bool,
+    /// Veces que cerró por tormenta.
+    pub cierres: u32,
+    /// Personas que estaban en la fila cuando cerró por tormenta y se
+    /// fueron sin subir.
+    pub dispersados: u32,
+    /// Tiempos totales de carga, vuelta y descarga de todas las
+    /// unidades, en milisegundos simulados.
+    pub carga_ms: u64,
+    pub vuelta_ms: u64,
+    pub descarga_ms: u64,
+    pub unidades: Vec<EstadisticasUnidad>,
+}
+
+impl EstadisticasUnidad {
+    /// Fracción de `duracion_simulacion_ms` que la unidad pasó dando
+    /// vueltas de `duracion_ms`.
+    pub fn utilizacion(&self, duracion_ms: u32, duracion_simulacion_ms: u64) -> f64 {
+        (self.vueltas as u64 * duracion_ms as u64) as f64 / duracion_simulacion_ms.max(1) as f64
+    }
+}
+
+impl EstadisticasJuego {
+    /// Duración promedio de un ciclo (carga, vuelta y descarga), y de
+    /// cada una de sus partes, en milisegundos simulados.
+    pub fn ciclo_promedio_ms(&self) -> (f64, f64, f64) {
+        let vueltas = self.vueltas.max(1) as f64;
+        (self.carga_ms as f64 / vueltas, self.vuelta_ms as f64 / vueltas, self.descarga_ms as f64 / vueltas)
+    }
+
+    /// Utilización promedio de las unidades del juego.
+    pub fn utilizacion(&self, duracion_simulacion_ms: u64) -> f64 {
+        let unidades = self.unidades.len().max(1) as u64;
+        (self.vueltas as u64 * self.duracion_ms as u64) as f64 / (unidades * duracion_simulacion_ms.max(1)) as f64
+    }
+
+    /// Fracción promedio de los lugares ocupados en cada vuelta. Si
+    /// `con_individuales` es falso, sin contar a quienes subieron por la
+    /// fila individual.
+    pub fn ocupacion_promedio(&self, con_individuales: bool) -> f64 {
+        let lugares = self.vueltas as u64 * self.capacidad as u64;
+        if lugares == 0 {
+            return 0.0;
+        }
+        let mut personas = self.personas_transportadas;
+        if !con_individuales {
+            personas -= self.individuales;
+        }
+        personas as f64 / lugares as f64
+    }
+
+    /// Tiempo promedio de espera en la fila, en milisegundos simulados.
+    pub fn espera_promedio_ms(&self) -> f64 {
+        if self.personas_transportadas == 0 {
+            0.0
+        } else {
+            self.espera_total_ms as f64 / self.personas_transportadas as f64
+        }
+    }
+}
+
+/// Estado propio de cada unidad de un juego: cada una corre en su
+/// propio hilo con su propio generador de desperfectos.
+struct Unidad {
+    log: TaggedLogger,
+    rng: Mutex<StdRng>,
+    vueltas: AtomicU32,
+    personas_transportadas: AtomicU32,
+    a_bordo: AtomicU32,
+    carga_ms: AtomicU64,
+    vuelta_ms: AtomicU64,
+    descarga_ms: AtomicU64,
+}
 
 pub struct Juego {
     pub id: usize,
+    pub nombre: Option<String>,
     parque: Arc<Parque>,
     pub precio: u32,
     tiempo: u32,
+    carga: u32,
+    descarga: u32,
     capacidad: u32,
+    probabilidad_desperfecto: f64,
+    tiempo_maximo_arreglo: u32,
+    reembolso_desperfectos: bool,
+    restricciones: Restricciones,
+    intensidad: u32,
+    cubierto: bool,
 
-    cant_espacio_libre: Mutex<u32>,
-    hay_espacio_mutex: Mutex<()>,
-    cv_cero_espacio_libre: Condvar,
-
-    sem_juego_en_curso: Semaphore,
-
-    salida_barrier: RwLock<Barrier>,
-    salida_mutex: Mutex<()>,
+    embarque: Embarque,
+    fila_individual: bool,
+    unidades: Vec<Unidad>,
 
     cerrado: AtomicBool,
-    log: TaggedLogger,
+    /// Cerrado por el clima hasta que lo reabran.
+    suspendido: AtomicBool,
 
-    rng: Mutex<StdRng>,
     cantidad_desperfectos: AtomicU32,
+    cierres: AtomicU32,
+    dispersados: AtomicU32,
+
+    ocupacion_maxima: AtomicU32,
+    espera_total_ms: AtomicU64,
+    espera_maxima_ms: AtomicU64,
+    adelantamientos: AtomicU64,
+    vueltas_cedidas: AtomicU32,
+    individuales: AtomicU32,
+    vueltas_completadas_por_individuales: AtomicU32,
+    reembolsos: AtomicU32,
 }
 
 impl Juego {
+    /// Crea el juego. Si tiene más de una unidad, cada una registra sus
+    /// eventos con la etiqueta del juego seguida de `.<unidad>`. La
+    /// primera unidad usa `semilla` y las demás semillas derivadas.
     pub fn new(log: TaggedLogger,
                id: usize,
                parque: Arc<Parque>,
-               precio: u32,
-               capacidad: u32,
-               duracion_ms: u32,
+               configuracion: ConfiguracionJuego,
                semilla: u64) -> Self {
+        let cantidad_unidades = configuracion.unidades.max(1);
+        let unidades = (0..cantidad_unidades as u64)
+            .map(|unidad| Unidad {
+                log: if cantidad_unidades == 1 {
+                    log.con_tag(log.tag())
+                } else {
+                    log.con_tag(&format!("{}.{}", log.tag(), unidad))
+                },
+                rng: Mutex::new(StdRng::seed_from_u64(semilla ^ (unidad << 32))),
+                vueltas: AtomicU32::new(0),
+                personas_transportadas: AtomicU32::new(0),
+                a_bordo: AtomicU32::new(0),
+                carga_ms: AtomicU64::new(0),
+                vuelta_ms: AtomicU64::new(0),
+                descarga_ms: AtomicU64::new(0),
+            })
+            .collect();
         Self {
             id,
+            nombre: configuracion.nombre,
             parque,
-            precio,
-            tiempo: duracion_ms,
-            capacidad,
-
-            cant_espacio_libre: Mutex::new(capacidad),
-            hay_espacio_mutex: Mutex::new(()),
-            cv_cero_espacio_libre: Condvar::new(),
+            precio: configuracion.precio,
+            tiempo: configuracion.duracion_ms,
+            carga: configuracion.carga_ms,
+            descarga: configuracion.descarga_ms,
+            capacidad: configuracion.capacidad,
+            probabilidad_desperfecto: configuracion.probabilidad_desperfecto,
+            tiempo_maximo_arreglo: configuracion.tiempo_maximo_arreglo_ms,
+            reembolso_desperfectos: configuracion.reembolso_desperfectos,
 
-            sem_juego_en_curso: Semaphore::new(0),
-
-            salida_barrier: RwLock::new(Barrier::new(capacidad as usize + 1)), // +1 para esperar el del juego
-            salida_mutex: Mutex::new(()),
+            embarque: Embarque::new(configuracion.capacidad, cantidad_unidades, configuracion.fila_justa),
+            fila_individual: configuracion.fila_individual,
+            restricciones: configuracion.restricciones,
+            intensidad: configuracion.intensidad,
+            cubierto: configuracion.cubierto,
+            unidades,
 
             cerrado: AtomicBool::new(false),
-            log,
+            suspendido: AtomicBool::new(false),
 
-            rng: Mutex::new(StdRng::seed_from_u64(semilla)),
             cantidad_desperfectos: AtomicU32::new(0),
+            cierres: AtomicU32::new(0),
+            dispersados: AtomicU32::new(0),
+
+            ocupacion_maxima: AtomicU32::new(0),
+            espera_total_ms: AtomicU64::new(0),
+            espera_maxima_ms: AtomicU64::new(0),
+            adelantamientos: AtomicU64::new(0),
+            vueltas_cedidas: AtomicU32::new(0),
+            individuales: AtomicU32::new(0),
+            vueltas_completadas_por_individuales: AtomicU32::new(0),
+            reembolsos: AtomicU32::new(0),
         }
     }
 
-    pub fn iniciar_funcionamiento(&self) {
-        let mut rng = self.rng.lock().expect("posioned rng");
+    /// Cantidad máxima de personas por vuelta de cada unidad.
+    pub fn capacidad(&self) -> u32 {
+        self.capacidad
+    }
+
+    pub fn restricciones(&self) -> &Restricciones {
+        &self.restricciones
+    }
+
+    pub fn intensidad(&self) -> u32 {
+        self.intensidad
+    }
+
+    pub fn cubierto(&self) -> bool {
+        self.cubierto
+    }
+
+    /// Si está cerrado por el clima.
+    pub fn suspendido(&self) -> bool {
+        self.suspendido.load(Ordering::SeqCst)
+    }
+
+    /// Cierra el juego por una tormenta: quienes esperaban en las filas se
+    /// van sin pagar y las unidades no cargan más hasta `reanudar`.
+    pub fn suspender(&self) {
+        if !self.suspendido.swap(true, Ordering::SeqCst) {
+            self.cierres.fetch_add(1, Ordering::SeqCst);
+            self.unidades[0].log.write("Cerrado por tormenta, se dispersa la fila");
+            self.embarque.suspender();
+        }
+    }
+
+    /// Vuelve a abrir el juego después de una tormenta.
+    pub fn reanudar(&self) {
+        if self.suspendido.swap(false, Ordering::SeqCst) {
+            self.embarque.reanudar();
+            self.unidades[0].log.write("Pasó la tormenta, vuelve a abrir");
+        }
+    }
+
+    /// Personas y grupos esperando en la fila principal y en la
+    /// individual.
+    pub fn largo_fila(&self) -> usize {
+        let (principal, individual) = self.embarque.largos();
+        principal + individual
+    }
+
+    /// Cantidad de unidades del juego. Cada una se hace funcionar desde
+    /// su propio hilo con `iniciar_funcionamiento`.
+    pub fn cantidad_unidades(&self) -> usize {
+        self.unidades.len()
+    }
+
+    pub fn iniciar_funcionamiento(&self, numero: usize) {
+        let unidad = &self.unidades[numero];
+        let log = &unidad.log;
+        let mut rng = unidad.rng.lock().expect("posioned rng");
+        // el desperfecto se sortea una vez por vuelta: una espera sin
+        // personas o una pausa por tormenta no vuelven a sortear, así los
+        // desperfectos de cada unidad dependen de sus vueltas y no de
+        // cuánto tiempo estuvo parada
+        let mut sorteo: Option<f64> = None;
         while !self.cerrado.load(Ordering::SeqCst) {
-            let hubo_desperfecto: f64 = rng.gen();
-            if hubo_desperfecto < PROBABILIDAD_DE_DESPERFECTOS {
+            if self.suspendido() {
+                self.reportar(log, "cerrado por tormenta".into());
+                self.parque.reloj().dormir(ESPERA_REAPERTURA);
+                continue;
+            }
+            let hubo_desperfecto = *sorteo.get_or_insert_with(|| rng.gen());
+            if hubo_desperfecto < self.probabilidad_desperfecto {
+                sorteo = None;
                 // desperfecto generado
-                self.log.write("Desperfecto generado");
+                log.write("Desperfecto generado");
+                self.reportar(log, "reparando desperfecto".into());
                 self.cantidad_desperfectos.fetch_add(1, Ordering::SeqCst);
                 // simular tiempo de reparacion del desperfecto
-                thread::sleep(
-                    Duration::from_millis(
-                        rng.gen_range(0..TIEMPO_MAXIMO_ARREGLO_DESPERFECTO) as u64
-                    )
+                self.parque.reloj().dormir(
+                    rng.gen_range(0..self.tiempo_maximo_arreglo) as u64
                 );
-                self.log.write("Desperfecto arreglado, iniciando una nueva vuelta");
+                log.write("Desperfecto arreglado, iniciando una nueva vuelta");
             } else {
                 // funcionamiento correcto, dar una vuelta del juego
 
                 // *** Esperar a que entre la gente ***
-                self.log.write("Esperando personas para iniciar la vuelta");
-                let (mut espacio_libre, timeout) =
-                    self.cv_cero_espacio_libre.wait_timeout(
-                        self.cant_espacio_libre.lock().expect("poisoned"),
-                        Duration::from_secs(5)
-                    ).expect("poisoned");
-
-                if timeout.timed_out() {
-                    // Timed out -> ver si hay gente y correr el juego.
-                    if *espacio_libre == self.capacidad {
-                        self.log.write("Tiempo de espera de personas agotado sin ninguna persona lista para jugar, reiniciando espera de personas");
+                log.write("Esperando personas para iniciar la vuelta");
+                self.reportar(log, "esperando personas (cv_cero_espacio_libre)".into());
+                let vuelta = match self.embarque.esperar_pasajeros(
+                    numero,
+                    self.parque.reloj().duracion(TIEMPO_ESPERA_PERSONAS)
+                ) {
+                    Espera::Llena(vuelta) => vuelta,
+                    Espera::Incompleta(vuelta) => {
+                        log.write("Tiempo de espera de personas agotado con personas listas para jugar, iniciando vuelta");
+                        vuelta
+                    },
+                    Espera::Cedida(vuelta) => {
+                        log.write("El grupo siguiente no entra en los lugares libres, iniciando vuelta");
+                        vuelta
+                    },
+                    Espera::Vacia => {
+                        log.write("Tiempo de espera de personas agotado sin ninguna persona lista para jugar, reiniciando espera de personas");
                         continue
-                    } else {
-                        self.log.write("Tiempo de espera de personas agotado con personas listas para jugar, iniciando vuelta");
+                    },
+                    Espera::Espuria => continue
+                };
+                sorteo = None;
+
+                let gente_adentro = vuelta.personas;
+                if vuelta.individuales > 0 {
+                    log.write(&format!("Suben {} personas de la fila individual", vuelta.individuales));
+                    self.individuales.fetch_add(vuelta.individuales, Ordering::SeqCst);
+                    if gente_adentro == self.capacidad {
+                        self.vueltas_completadas_por_individuales.fetch_add(1, Ordering::SeqCst);
                     }
-                } else if *espacio_libre != 0 { // desbloqueo espurio de la condvar
-                    continue
                 }
+                let reloj = self.parque.reloj();
 
-                let gente_adentro = self.capacidad - *espacio_libre;
-                self.log.write(
+                // *** Subir a las personas, ocupando el andén ***
+                let inicio_carga = reloj.ahora();
+                if self.carga > 0 {
+                    self.reportar(log, format!("subiendo {} personas (ocupa el andén)", gente_adentro));
+                    reloj.dormir(self.carga as u64 * gente_adentro as u64);
+                }
+                self.embarque.partir(&vuelta);
+                let inicio_vuelta = reloj.ahora();
+                log.write(
                 &format!(
                         "Arrancando la vuelta del juego con {}/{} personas",
                         gente_adentro, self.capacidad
@@ -111,48 +435,122 @@ impl Juego {
                 );
 
                 // *** Arrancar el juego ***
-                thread::sleep(Duration::from_millis(self.tiempo as u64));
+                self.reportar(log, format!("en vuelta con {} personas", gente_adentro));
+                reloj.dormir(self.tiempo as u64);
 
-                self.terminar_vuelta(gente_adentro);
+                // *** Bajar a las personas ***
+                let inicio_descarga = reloj.ahora();
+                if self.descarga > 0 {
+                    self.reportar(log, format!("bajando {} personas", gente_adentro));
+                    reloj.dormir(self.descarga as u64 * gente_adentro as u64);
+                }
+                let fin = reloj.ahora();
 
-                // Marcar todo el espacio como libre para que puedan entrar nuevas personas al juego en la siguiente vuelta
-                *espacio_libre = self.capacidad;
+                log.write("Vuelta terminada, esperando que las personas dejen sus lugares");
+                self.reportar(log, format!("esperando que salgan {} personas (salida_barrier)", gente_adentro));
+                self.embarque.terminar_vuelta(vuelta);
+                unidad.vueltas.fetch_add(1, Ordering::SeqCst);
+                unidad.personas_transportadas.fetch_add(gente_adentro, Ordering::SeqCst);
+                unidad.carga_ms.fetch_add(inicio_vuelta - inicio_carga, Ordering::SeqCst);
+                unidad.vuelta_ms.fetch_add(inicio_descarga - inicio_vuelta, Ordering::SeqCst);
+                unidad.descarga_ms.fetch_add(fin - inicio_descarga, Ordering::SeqCst);
+                log.write("Todas las personas salieron del juego, iniciando una nueva vuelta");
+                self.parque.watchdog().registrar_progreso();
             }
         }
 
-        self.log.write("Cerrado");
+        log.write("Cerrado");
+        self.parque.watchdog().estado_juego(log.tag(), "cerrado".into(), true);
     }
 
-    fn terminar_vuelta(&self, gente_adentro: u32) {
-        // setear la cantidad de personas a esperar que usen la salida previo a avisar que dejen sus lugares
-        {
-            let mut salida_barrier = self.salida_barrier.write().expect("poison");
-            *salida_barrier = Barrier::new(gente_adentro as usize + 1); // +1 para esperar el del juego
-        }
-        self.log.write("Vuelta terminada, esperando que las personas dejen sus lugares");
-        // avisar que el juego terminó
-        for _persona in 0..gente_adentro {
-            self.sem_juego_en_curso.release();
-        }
-        self.log.write("Esperando que las personas salgan del juego");
-        let salida_barrier = self.salida_barrier.read().expect("poison");
-        salida_barrier.wait();
-        self.log.write("Todas las personas salieron del juego, iniciando una nueva vuelta");
-    }
-
-    pub fn agregar_a_la_fila(&self, persona: &mut Persona) {
-        let hay_espacio = self.hay_espacio_mutex.lock().expect("poisoned");
-        let mut espacio_libre = self.cant_espacio_libre.lock().expect("poison");
-        *espacio_libre -= 1;
-        if *espacio_libre == 0 {
-            self.cv_cero_espacio_libre.notify_one();
+    /// Informa al watchdog del parque qué está haciendo el juego.
+    fn reportar(&self, log: &TaggedLogger, estado: String) {
+        self.parque.watchdog().estado_juego(log.tag(), estado, false);
+    }
+
+    /// Hace la fila del juego con `personas`, que son una persona sola o
+    /// un grupo que sube junto a la misma unidad, y las sube a dar una
+    /// vuelta. Si el juego tiene fila individual, una persona sola la
+    /// elige cuando es más corta que la principal. Los desperfectos que
+    /// ocurren mientras esperan se les descuentan de la satisfacción y,
+    /// si el juego los reembolsa, se les devuelve la entrada. Devuelve
+    /// falso si no llegaron a subir porque el juego cerró por tormenta.
+    pub fn agregar_a_la_fila(&self, personas: &mut [Persona]) -> bool {
+        let llegada = self.parque.reloj().ahora();
+        let desperfectos_al_llegar = self.obtener_desperfectos();
+        let lugares = personas.len() as u32;
+        let mut unidad_actual = 0;
+        let justa = self.embarque.es_justa();
+        let individual = self.fila_individual && lugares == 1 && {
+            let (principal, individual) = self.embarque.largos();
+            individual < principal
+        };
+        let avisar = |paso| {
+            let estado = match paso {
+                Paso::EnFila if individual => "en la fila individual (esperando cv_individual)".to_string(),
+                Paso::EnFila if justa => "en la fila (esperando su turno en cv_turno)".to_string(),
+                Paso::EnFila => "en la fila (esperando fila_mutex)".to_string(),
+                Paso::ConTurno if justa => "primera en la fila (esperando cv_anden)".to_string(),
+                Paso::ConTurno => "primera en la fila (retiene fila_mutex, esperando cv_anden)".to_string(),
+                Paso::DejaPasar { unidad: numero } => {
+                    self.vueltas_cedidas.fetch_add(1, Ordering::SeqCst);
+                    self.unidades[numero].log.write(&format!(
+                        "El grupo de {} personas no entra en los lugares libres, la unidad parte sin completarse", lugares
+                    ));
+                    for persona in personas.iter_mut() {
+                        persona.registrar_vuelta_cedida();
+                    }
+                    format!("primera en la fila, dejó pasar la unidad {} (esperando cv_anden)", numero)
+                },
+                Paso::Arriba { unidad: numero, adelantamientos } => {
+                    unidad_actual = numero;
+                    let unidad = &self.unidades[numero];
+                    let a_bordo = unidad.a_bordo.fetch_add(lugares, Ordering::SeqCst) + lugares;
+                    self.ocupacion_maxima.fetch_max(a_bordo, Ordering::SeqCst);
+                    let espera = self.parque.reloj().ahora() - llegada;
+                    self.espera_total_ms.fetch_add(espera * lugares as u64, Ordering::SeqCst);
+                    self.espera_maxima_ms.fetch_max(espera, Ordering::SeqCst);
+                    self.adelantamientos.fetch_add(adelantamientos as u64 * lugares as u64, Ordering::SeqCst);
+                    let desperfectos = self.obtener_desperfectos() - desperfectos_al_llegar;
+                    for persona in personas.iter_mut() {
+                        persona.registrar_espera(espera, adelantamientos);
+                        self.cobrar_entrada(persona);
+                        if desperfectos > 0 {
+                            persona.registrar_desperfectos(desperfectos);
+                            if self.reembolso_desperfectos {
+                                self.reembolsar_entrada(persona);
+                            }
+                        }
+                        if individual {
+                            unidad.log.write(&format!("Persona {} logró entrar al juego por la fila individual", persona.descripcion()));
+                        } else {
+                            unidad.log.write(&format!("Persona {} logró entrar al juego", persona.descripcion()));
+                        }
+                    }
+                    format!("arriba de la unidad {} (esperando sem_juego_en_curso)", numero)
+                },
+                Paso::Bajando => {
+                    self.unidades[unidad_actual].a_bordo.fetch_sub(lugares, Ordering::SeqCst);
+                    "bajando (esperando salida_barrier)".to_string()
+                },
+                Paso::Saliendo => "bajando (esperando salida_mutex)".to_string(),
+            };
+            for persona in personas.iter() {
+                persona.reportar(&self.parque, format!("juego {}: {}", self.descripcion(), estado));
+            }
+        };
+        let subieron = if individual {
+            self.embarque.subir_individual(avisar)
         } else {
-            // Si todavia queda espacio: desbloquear el mutex hay_espacio.
-            // Sino se desbloquea después de que juegue y salga la ultima persona que entró.
-            drop(hay_espacio);
+            self.embarque.subir(lugares, avisar)
+        };
+        if !subieron {
+            self.dispersados.fetch_add(lugares, Ordering::SeqCst);
+            for persona in personas.iter_mut() {
+                persona.registrar_fila_dispersada();
+            }
         }
-        drop(espacio_libre);
-        self.admitir_para_jugar(persona);
+        subieron
     }
 
     fn cobrar_entrada(&self, persona: &mut Persona) {
@@ -160,18 +558,10 @@ impl Juego {
         self.parque.guardar_dinero(self.precio);
     }
 
-    fn admitir_para_jugar(&self, persona: &mut Persona) {
-        self.cobrar_entrada(persona);
-        self.log.write(&format!("Persona {} logró entrar al juego", persona.id));
-        self.sem_juego_en_curso.acquire();
-        self.permitir_salir();
-    }
-
-    fn permitir_salir(&self) {
-        let barrier = self.salida_barrier.read().expect("poisoned");
-        barrier.wait();
-        // lockear el mutex de la salida para salir de a uno
-        let _mutex = self.salida_mutex.lock().expect("poison");
+    fn reembolsar_entrada(&self, persona: &mut Persona) {
+        persona.reembolsar(self);
+        self.parque.devolver_dinero(self.precio);
+        self.reembolsos.fetch_add(1, Ordering::SeqCst);
     }
 
     /// Cantidad de desperfectos que ocurrieron (el parque lo usa)
@@ -179,6 +569,49 @@ impl Juego {
         self.cantidad_desperfectos.load(Ordering::SeqCst)
     }
 
+    /// Id del juego seguido de su nombre, si lo tiene.
+    pub fn descripcion(&self) -> String {
+        describir(self.id, self.nombre.as_deref())
+    }
+
+    pub fn estadisticas(&self) -> EstadisticasJuego {
+        let unidades: Vec<EstadisticasUnidad> = self.unidades.iter()
+            .map(|unidad| EstadisticasUnidad {
+                vueltas: unidad.vueltas.load(Ordering::SeqCst),
+                personas_transportadas: unidad.personas_transportadas.load(Ordering::SeqCst),
+                carga_ms: unidad.carga_ms.load(Ordering::SeqCst),
+                vuelta_ms: unidad.vuelta_ms.load(Ordering::SeqCst),
+                descarga_ms: unidad.descarga_ms.load(Ordering::SeqCst),
+            })
+            .collect();
+        EstadisticasJuego {
+            id: self.id,
+            nombre: self.nombre.clone(),
+            capacidad: self.capacidad,
+            duracion_ms: self.tiempo,
+            vueltas: unidades.iter().map(|u| u.vueltas).sum(),
+            personas_transportadas: unidades.iter().map(|u| u.personas_transportadas).sum(),
+            desperfectos: self.obtener_desperfectos(),
+            ocupacion_maxima: self.ocupacion_maxima.load(Ordering::SeqCst),
+            espera_total_ms: self.espera_total_ms.load(Ordering::SeqCst),
+            espera_maxima_ms: self.espera_maxima_ms.load(Ordering::SeqCst),
+            adelantamientos: self.adelantamientos.load(Ordering::SeqCst),
+            vueltas_cedidas: self.vueltas_cedidas.load(Ordering::SeqCst),
+            fila_individual: self.fila_individual,
+            restricciones: self.restricciones.clone(),
+            individuales: self.individuales.load(Ordering::SeqCst),
+            vueltas_completadas_por_individuales: self.vueltas_completadas_por_individuales.load(Ordering::SeqCst),
+            reembolsos: self.reembolsos.load(Ordering::SeqCst),
+            cubierto: self.cubierto,
+            cierres: self.cierres.load(Ordering::SeqCst),
+            dispersados: self.dispersados.load(Ordering::SeqCst),
+            carga_ms: unidades.iter().map(|u| u.carga_ms).sum(),
+            vuelta_ms: unidades.iter().map(|u| u.vuelta_ms).sum(),
+            descarga_ms: unidades.iter().map(|u| u.descarga_ms).sum(),
+            unidades,
+        }
+    }
+
     /// EL PARQUE LE INDICA AL JUEGO QUE DEBE CERRARSE CUANDO SE FUE TODA LA GENTE
     pub fn cerrar(&self) {
         self.cerrado.store(true, Ordering::SeqCst);
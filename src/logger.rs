@@ -5,19 +5,27 @@ use std::{
     time::{Duration, Instant}
 };
 
+enum Destino {
+    Stdout,
+    Archivo(Mutex<File>),
+    Descartar,
+    #[cfg(test)]
+    Memoria(Mutex<String>),
+}
+
 pub struct Logger {
-    file: Option<Mutex<File>>,
+    destino: Destino,
     timer: Instant
 }
 
 impl Logger {
     pub fn new_to_stdout() -> Self {
-        Self { file: None, timer: Instant::now() }
+        Self { destino: Destino::Stdout, timer: Instant::now() }
     }
 
     pub fn new_to_file(path: &str) -> Result<Self, String> {
         Ok(Self {
-            file: Some(Mutex::new(
+            destino: Destino::Archivo(Mutex::new(
                 File::create(path)
                     .map_err(|e| e.to_string())?
             )),
@@ -25,21 +33,46 @@ impl Logger {
         })
     }
 
+    /// Crea un logger que descarta todos los mensajes.
+    pub fn new_to_null() -> Self {
+        Self { destino: Destino::Descartar, timer: Instant::now() }
+    }
+
+    /// Crea un logger que guarda los mensajes en memoria. Se pueden
+    /// obtener con `contenido`.
+    #[cfg(test)]
+    pub fn new_to_memory() -> Self {
+        Self { destino: Destino::Memoria(Mutex::new(String::new())), timer: Instant::now() }
+    }
+
     /// Escribe msg al log sin agregar nada (ni salto de línea,
     /// ni etiquetas).
     pub fn write_raw(&self, msg: &str) {
-        if let Some(file_mutex) = &self.file {
-            let mut file = file_mutex.lock().expect("log poisoned");
-            file.write_all(msg.as_bytes())
-                .expect("No se puede escribir al archivo de log.");
-        } else {
-            print!("{}", msg);
+        match &self.destino {
+            Destino::Stdout => print!("{}", msg),
+            Destino::Archivo(file_mutex) => {
+                let mut file = file_mutex.lock().expect("log poisoned");
+                file.write_all(msg.as_bytes())
+                    .expect("No se puede escribir al archivo de log.");
+            },
+            Destino::Descartar => {},
+            #[cfg(test)]
+            Destino::Memoria(buffer) => buffer.lock().expect("log poisoned").push_str(msg),
+        }
+    }
+
+    /// Devuelve todo lo escrito en un logger en memoria.
+    #[cfg(test)]
+    pub fn contenido(&self) -> String {
+        match &self.destino {
+            Destino::Memoria(buffer) => buffer.lock().expect("log poisoned").clone(),
+            _ => String::new(),
         }
     }
 
     /// Vacía los buffers y cierra el archivo de log.
     pub fn close(&self) {
-        if let Some(mutex_lock) = &self.file {
+        if let Destino::Archivo(mutex_lock) = &self.destino {
             let mut file = mutex_lock.lock().expect("log mutex poisoned");
             file.flush().expect("Error al flushear el log");
         }
@@ -52,6 +85,23 @@ impl Logger {
     }
 }
 
+/// Etiqueta de log de un juego o persona: `<PREFIJO> <id>`, seguido del
+/// nombre entre paréntesis si lo tiene. Con el id la etiqueta es única
+/// aunque se repitan los nombres.
+pub fn etiqueta(prefijo: &str, id: usize, nombre: Option<&str>) -> String {
+    format!("{} {}", prefijo, describir(id, nombre))
+}
+
+/// Identificación de un juego o persona en los mensajes: su id seguido
+/// del nombre entre paréntesis, si lo tiene. El id se mantiene siempre
+/// para que la salida se pueda procesar automáticamente.
+pub fn describir(id: usize, nombre: Option<&str>) -> String {
+    match nombre {
+        Some(nombre) => format!("{} ({})", id, nombre),
+        None => id.to_string()
+    }
+}
+
 pub struct TaggedLogger {
     tag: String,
     logger: Arc<Logger>
@@ -65,6 +115,16 @@ impl TaggedLogger {
         }
     }
 
+    /// Logger que escribe al mismo destino con otra etiqueta.
+    pub fn con_tag(&self, tag: &str) -> Self {
+        Self::new(tag, self.logger.clone())
+    }
+
+    /// Etiqueta con la que se marcan los mensajes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
     /// Escribe un mensaje al log. 
     ///
     /// El mensaje estará marcado con la etiqueta correspondiente
@@ -0,0 +1,249 @@
+//! Modo lote: corre muchas simulaciones independientes del mismo
+//! escenario con distintas semillas y resume sus resultados.
+//!
+//! ```bash
+//! $ ./parque-oxidado lote --corridas=100 --semillas=1..100 --hilos=8 --personas=20:40
+//! ```
+//!
+//! Las corridas se hacen siempre en modo rápido y sin registro de
+//! eventos.
+
+use std::{
+    sync::{Arc, Mutex, atomic::{AtomicUsize, Ordering}},
+    thread,
+};
+
+use crate::{
+    args::Args,
+    estadistica::{Resumen, resumir},
+    logger::Logger,
+    simulacion::{METRICAS, Resultado, simular},
+};
+
+/// Cantidad de corridas si no se especifican ni `--corridas` ni
+/// `--semillas`.
+const CORRIDAS_POR_DEFECTO: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct OpcionesLote {
+    /// Cantidad de corridas.
+    pub corridas: usize,
+    /// Semilla de cada corrida. Si no se especifica se completa en
+    /// `resolver`.
+    pub semillas: Option<Vec<u32>>,
+    /// Cantidad de simulaciones que corren en paralelo.
+    pub hilos: usize,
+}
+
+/// Separa las opciones del lote (`--corridas`, `--semillas` e
+/// `--hilos`) del resto de los argumentos, que describen el escenario.
+pub fn parse_opciones(argv: &[String]) -> Result<(OpcionesLote, Vec<String>), String> {
+    let mut corridas = None;
+    let mut semillas = None;
+    let mut hilos = None;
+    let mut resto = vec![];
+    for arg in argv {
+        if let Some(valor) = arg.strip_prefix("--corridas=") {
+            corridas = Some(parse_natural("--corridas", valor)? as usize);
+        } else if let Some(valor) = arg.strip_prefix("--semillas=") {
+            semillas = Some(parse_semillas(valor).map_err(|e| format!("[--semillas] {}", e))?);
+        } else if let Some(valor) = arg.strip_prefix("--hilos=") {
+            hilos = Some(parse_natural("--hilos", valor)? as usize);
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+
+    let corridas = match (&semillas, corridas) {
+        (Some(semillas), Some(corridas)) if semillas.len() != corridas => return Err(format!(
+            "Los parámetros --corridas y --semillas indican distinta cantidad de corridas ({} != {})",
+            corridas, semillas.len()
+        )),
+        (Some(semillas), _) => semillas.len(),
+        (None, corridas) => corridas.unwrap_or(CORRIDAS_POR_DEFECTO),
+    };
+    let hilos = hilos.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
+    Ok((OpcionesLote { corridas, semillas, hilos }, resto))
+}
+
+impl OpcionesLote {
+    /// Si no se especificaron semillas usa `corridas` semillas
+    /// consecutivas a partir de la semilla del escenario.
+    pub fn resolver(&mut self, semilla_base: u32) {
+        if self.semillas.is_none() {
+            self.semillas = Some((0..self.corridas as u32)
+                .map(|i| semilla_base.wrapping_add(i))
+                .collect());
+        }
+    }
+
+    /// Semillas de las corridas. Requiere que se haya llamado a
+    /// `resolver`.
+    pub fn semillas(&self) -> &[u32] {
+        self.semillas.as_deref().unwrap_or_default()
+    }
+}
+
+/// Semillas en el formato `A..B` (rango inclusivo) o `A,B,C`.
+fn parse_semillas(valor: &str) -> Result<Vec<u32>, String> {
+    let numero = |s: &str| s.trim().parse::<u32>().map_err(|_| format!("'{}' no es un número", s));
+    if let Some((desde, hasta)) = valor.split_once("..") {
+        let (desde, hasta) = (numero(desde)?, numero(hasta)?);
+        if desde > hasta {
+            return Err(format!("Rango inválido ({} > {})", desde, hasta));
+        }
+        Ok((desde..=hasta).collect())
+    } else {
+        valor.split(',').map(numero).collect()
+    }
+}
+
+fn parse_natural(param: &str, valor: &str) -> Result<u32, String> {
+    match valor.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("[{}] '{}' no es un número natural", param, valor)),
+    }
+}
+
+/// Corre una simulación de `args` por cada semilla, de a `hilos`
+/// simulaciones en paralelo. Los resultados están en el mismo orden que
+/// las semillas. Quien llama decide si las corridas son en modo rápido.
+pub fn correr_corridas(args: &Args, semillas: &[u32], hilos: usize) -> Result<Vec<Resultado>, String> {
+    args.validar_grupos()?;
+    let siguiente = AtomicUsize::new(0);
+    let resultados: Mutex<Vec<Option<Result<Resultado, String>>>> =
+        Mutex::new(semillas.iter().map(|_| None).collect());
+
+    thread::scope(|s| {
+        for _ in 0..hilos.clamp(1, semillas.len().max(1)) {
+            s.spawn(|| loop {
+                let i = siguiente.fetch_add(1, Ordering::SeqCst);
+                if i >= semillas.len() {
+                    break;
+                }
+                let mut args = args.clone();
+                args.semilla = semillas[i];
+                let resultado = simular(&args, Arc::new(Logger::new_to_null()));
+                resultados.lock().expect("poisoned")[i] = Some(resultado);
+            });
+        }
+    });
+
+    resultados.into_inner().expect("poisoned")
+        .into_iter()
+        .zip(semillas)
+        .map(|(resultado, semilla)| resultado
+            .unwrap_or_else(|| Err("corrida no ejecutada".into()))
+            .map_err(|e| format!("semilla {}: {}", semilla, e)))
+        .collect()
+}
+
+/// Resume cada una de las métricas de `METRICAS` sobre todas las
+/// corridas.
+pub fn resumir_metricas(resultados: &[Resultado]) -> Vec<(&'static str, Resumen)> {
+    let metricas: Vec<[f64; METRICAS.len()]> = resultados.iter().map(Resultado::metricas).collect();
+    METRICAS.iter()
+        .enumerate()
+        .map(|(i, nombre)| (*nombre, resumir(&metricas.iter().map(|m| m[i]).collect::<Vec<f64>>())))
+        .collect()
+}
+
+/// Tabla con el resumen de las métricas del lote.
+pub fn informe(opciones: &OpcionesLote, resultados: &[Resultado]) -> String {
+    let mut informe = format!(
+        "Lote de {} corridas ({}) con {} hilos\n",
+        resultados.len(), describir_semillas(opciones.semillas()), opciones.hilos
+    );
+    informe += &format!("{:<20} {:>12} {:>12} {:>28}\n", "métrica", "media", "desvío", "IC 95%");
+    for (nombre, resumen) in resumir_metricas(resultados) {
+        informe += &format!(
+            "{:<20} {:>12.2} {:>12.2} {:>28}\n",
+            nombre, resumen.media, resumen.desvio,
+            format!("[{:.2}, {:.2}]", resumen.ic95.0, resumen.ic95.1)
+        );
+    }
+    informe
+}
+
+fn describir_semillas(semillas: &[u32]) -> String {
+    match (semillas.first(), semillas.last()) {
+        (Some(primera), Some(ultima)) if semillas.windows(2).all(|w| w[1] == w[0] + 1) => {
+            format!("semillas {}..{}", primera, ultima)
+        },
+        _ => format!("{} semillas", semillas.len())
+    }
+}
+
+/// Corre el lote descripto por `opciones` sobre el escenario `args`
+/// e imprime el resumen.
+pub fn correr(mut opciones: OpcionesLote, args: &Args) -> Result<(), String> {
+    opciones.resolver(args.semilla);
+    let mut args = args.clone();
+    args.rapido = true;
+    let resultados = correr_corridas(&args, opciones.semillas(), opciones.hilos)?;
+    print!("{}", informe(&opciones, &resultados));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn separa_las_opciones_del_lote_del_escenario() {
+        let (opciones, resto) = parse_opciones(&argv(&["--corridas=3", "--personas=5:20", "--semillas=4..6", "--hilos=2"])).unwrap();
+        assert_eq!(opciones.semillas, Some(vec![4, 5, 6]));
+        assert_eq!(opciones.hilos, 2);
+        assert_eq!(resto, argv(&["--personas=5:20"]));
+
+        let (opciones, _) = parse_opciones(&argv(&["--semillas=9,1"])).unwrap();
+        assert_eq!(opciones.semillas(), &[9, 1]);
+
+        let (mut opciones, _) = parse_opciones(&argv(&["--corridas=3"])).unwrap();
+        opciones.resolver(7);
+        assert_eq!(opciones.semillas(), &[7, 8, 9]);
+        assert!(parse_opciones(&argv(&["--corridas=2", "--semillas=1..3"])).is_err());
+        assert!(parse_opciones(&argv(&["--semillas=3..1"])).is_err());
+    }
+
+    #[test]
+    fn las_corridas_en_paralelo_coinciden_con_las_individuales() {
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![15, 30, 45];
+        args.costo_juegos = Some(vec![5, 10, 15]);
+        args.rapido = true;
+        args.resolver().unwrap();
+
+        let semillas = [3, 1, 4, 1, 5];
+        let resultados = correr_corridas(&args, &semillas, 3).unwrap();
+        assert_eq!(resultados.len(), semillas.len());
+        for (resultado, semilla) in resultados.iter().zip(semillas) {
+            args.semilla = semilla;
+            let individual = simular(&args, Arc::new(Logger::new_to_null())).unwrap();
+            let gastos = |r: &Resultado| r.personas.iter().map(|p| (p.gastado, p.juegos_jugados)).collect::<Vec<_>>();
+            assert_eq!(gastos(resultado), gastos(&individual));
+        }
+
+        let resumen = resumir_metricas(&resultados);
+        assert_eq!(resumen[0].0, "caja");
+        assert_eq!(resumen[0].1.n, semillas.len());
+    }
+
+    #[test]
+    fn un_grupo_que_no_entra_en_el_parque_es_un_error() {
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![20; 3];
+        args.grupos = Some(vec![3]);
+        args.rapido = true;
+        args.resolver().unwrap();
+        // como el barrido, cambia la capacidad después de resolver
+        args.capacidad_parque = 2;
+
+        assert!(correr_corridas(&args, &[1], 1).is_err());
+        assert!(simular(&args, Arc::new(Logger::new_to_null())).is_err());
+    }
+}
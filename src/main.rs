@@ -1,42 +1,103 @@
 extern crate rand;
-extern crate std_semaphore;
+extern crate serde;
+extern crate serde_json;
+extern crate toml;
 
 mod args;
+mod arquetipo;
+mod barrido;
+mod clima;
+mod config;
+mod embarque;
+mod entrada;
+mod espectaculo;
+mod estadistica;
+#[cfg(test)]
+mod golden;
+mod grupo;
 mod logger;
+mod lote;
+mod mapa;
+mod optimizador;
 mod parque;
 mod persona;
+mod planificador;
 mod juego;
+mod reloj;
+mod restricciones;
+mod salida;
+mod satisfaccion;
+mod simulacion;
+mod sync;
+mod teoria;
+mod temporada;
+mod watchdog;
 
-use std::{
-    sync::Arc,
-    time::Duration,
-    thread::sleep,
-};
+use std::sync::Arc;
 
-use args::{parse_args, mostrar_ayuda, ParseArgsResult};
-use logger::{Logger, TaggedLogger};
-use parque::Parque;
-use juego::Juego;
-use persona::iniciar_hilos_personas;
+use args::{Args, parse_args_desde, mostrar_ayuda, ParseArgsResult};
+use logger::Logger;
+use simulacion::simular;
 
 
 fn main()  {
     if let Err(e) = real_main() {
         println!("ERROR: {}", e);
+        std::process::exit(1);
     }
 }
 
 fn real_main() -> Result<(), String> {
-    let args = match parse_args() {
-        ParseArgsResult::Ok(args) => args,
-        ParseArgsResult::MostrarAyuda => {
-            mostrar_ayuda();
-            return Ok(())
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    match argv.first().map(String::as_str) {
+        Some("lote") => {
+            let (opciones, resto) = lote::parse_opciones(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => lote::correr(opciones, &args),
+                None => Ok(())
+            };
         },
-        ParseArgsResult::Error(e) => {
-            mostrar_ayuda();
-            return Err(e);
-        }
+        Some("barrido") => {
+            let (opciones, resto) = barrido::parse_opciones_barrido(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => barrido::correr(opciones, &args),
+                None => Ok(())
+            };
+        },
+        Some("optimizar") => {
+            let (opciones, resto) = optimizador::parse_opciones_optimizador(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => optimizador::correr(opciones, &args),
+                None => Ok(())
+            };
+        },
+        Some("teoria") => {
+            let (opciones, resto) = teoria::parse_opciones_teoria(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => teoria::correr(opciones, &args),
+                None => Ok(())
+            };
+        },
+        Some("planificar") => {
+            let (opciones, resto) = planificador::parse_opciones_planificador(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => planificador::correr(opciones, &args),
+                None => Ok(())
+            };
+        },
+        Some("temporada") => {
+            let (opciones, resto) = temporada::parse_opciones_temporada(&argv[1..])?;
+            return match obtener_args(&resto)? {
+                Some(args) => temporada::correr(opciones, &args),
+                None => Ok(())
+            };
+        },
+        _ => {}
+    }
+
+    let args = match obtener_args(&argv)? {
+        Some(args) => args,
+        None => return Ok(())
     };
 
     let logger = Arc::new(if args.debug {
@@ -45,57 +106,23 @@ fn real_main() -> Result<(), String> {
         Logger::new_to_stdout()
     });
 
-    let log = TaggedLogger::new("ADMIN", logger.clone());
-    log.write(&format!("Iniciando simulación con: {}", args.as_str()));
-    let parque = Arc::new(Parque::new(
-        TaggedLogger::new("PARQUE", logger.clone()),
-        args.capacidad_parque as usize,
-        args.semilla as u64
-    ));
-    let semilla = args.semilla;
-    let juegos = args.costo_juegos.unwrap()
-        .iter()
-        .zip(args.capacidad_juegos.unwrap())
-        .zip(args.duracion_juegos.unwrap())
-        .enumerate()
-        .map(|(id, ((costo, capacidad), duracion_ms))| Juego::new(
-            TaggedLogger::new(&format!("JUEGO {}", id), logger.clone()),
-            id, 
-            Arc::clone(&parque), 
-            *costo,
-            capacidad,
-            duracion_ms,
-            (semilla + 1 + id as u32) as u64
-        ))
-        .collect::<Vec<Juego>>();
-    
-    // iniciar thread de juegos
-    parque.registrar_juegos(juegos);
-
-    let personas_threads = iniciar_hilos_personas(
-        Arc::clone(&logger), 
-        Arc::clone(&parque), 
-        &args.presupuesto_personas
-    );
-    
-    while parque.obtener_cantidad_gente_que_salio_del_parque() < args.presupuesto_personas.len() {
-        sleep(Duration::from_millis(5000));
-        log.write(&format!("Caja: $ {}, desperfectos: {}",
-                 parque.obtener_caja(), 
-                 parque.obtener_desperfectos()));
-    }
-    
-    log.write("Salieron todos, cerrando el parque");
-    parque.cerrar();
-    log.write("Terminado");
-
-    log.write(&format!("Caja final: $ {}, desperfectos: {}",
-                 parque.obtener_caja(), 
-                 parque.obtener_desperfectos()));
+    let resultado = simular(&args, logger)?;
+    print!("\nResumen:\n{}", resultado.resumen());
+    Ok(())
+}
 
-    for persona in personas_threads {
-        persona.join().expect("no se pudo joinear hilo de persona");
+/// Interpreta los argumentos del escenario. Devuelve `None` si sólo se
+/// pidió la ayuda.
+fn obtener_args(argv: &[String]) -> Result<Option<Args>, String> {
+    match parse_args_desde(argv) {
+        ParseArgsResult::Ok(args) => Ok(Some(*args)),
+        ParseArgsResult::MostrarAyuda => {
+            mostrar_ayuda();
+            Ok(None)
+        },
+        ParseArgsResult::Error(e) => {
+            mostrar_ayuda();
+            Err(e)
+        }
     }
-    logger.close();
-    Ok(())
 }
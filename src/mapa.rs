@@ -0,0 +1,328 @@
+//! Mapa del parque: zonas y tiempos de caminata.
+//!
+//! La entrada, los juegos, los puestos de comida y los espectáculos son
+//! lugares unidos por caminos que se recorren en cierto tiempo, y las
+//! personas van de un lugar a otro por el camino más corto. Cada lugar
+//! salvo la entrada puede pertenecer a una zona, y las zonas pueden
+//! limitar cuántas personas hay en ellas al mismo tiempo: quien camina
+//! hacia una zona llena espera en el borde a que se libere lugar. Mientras caminan de una
+//! zona a otra las personas no ocupan ninguna, así que nadie retiene una
+//! zona mientras espera entrar a otra.
+
+use std::fmt;
+
+use crate::sync::{Condvar, Mutex};
+
+/// Un lugar del parque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lugar {
+    Entrada,
+    Juego(usize),
+    Puesto(usize),
+    Espectaculo(usize),
+}
+
+impl fmt::Display for Lugar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lugar::Entrada => write!(f, "la entrada"),
+            Lugar::Juego(id) => write!(f, "el juego {}", id),
+            Lugar::Puesto(id) => write!(f, "el puesto {}", id),
+            Lugar::Espectaculo(id) => write!(f, "el espectáculo {}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zona {
+    pub nombre: String,
+    /// Máxima cantidad de personas en la zona al mismo tiempo.
+    pub capacidad: Option<u32>,
+}
+
+/// Puesto de comida, donde compran quienes comen entre juego y juego.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puesto {
+    pub nombre: String,
+    pub zona: Option<usize>,
+}
+
+/// Camino de ida y vuelta entre dos lugares.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camino {
+    pub desde: Lugar,
+    pub hasta: Lugar,
+    /// Tiempo que lleva recorrerlo, en milisegundos simulados.
+    pub ms: u64,
+}
+
+#[derive(Debug)]
+pub struct Mapa {
+    pub zonas: Vec<Zona>,
+    pub puestos: Vec<Puesto>,
+    zonas_juegos: Vec<Option<usize>>,
+    zonas_espectaculos: Vec<Option<usize>>,
+    /// Tiempo del camino más corto entre cada par de lugares, indexados
+    /// por `indice`.
+    distancias: Vec<Vec<u64>>,
+    ocupacion: Ocupacion,
+}
+
+impl Mapa {
+    /// Arma el mapa de un parque con un juego por cada elemento de
+    /// `zonas_juegos` y un espectáculo por cada elemento de
+    /// `zonas_espectaculos`. Todos los lugares tienen que poder
+    /// alcanzarse desde la entrada.
+    pub fn new(zonas: Vec<Zona>, zonas_juegos: Vec<Option<usize>>, zonas_espectaculos: Vec<Option<usize>>,
+               puestos: Vec<Puesto>, caminos: &[Camino]) -> Result<Self, String> {
+        let lugares = 1 + zonas_juegos.len() + puestos.len() + zonas_espectaculos.len();
+        let zona_valida = |zona: &Option<usize>| zona.is_none_or(|zona| zona < zonas.len());
+        let mut todas = zonas_juegos.iter().chain(&zonas_espectaculos).chain(puestos.iter().map(|p| &p.zona));
+        if !todas.all(zona_valida) {
+            return Err("Hay lugares en zonas que no existen".into());
+        }
+        let mut mapa = Self {
+            ocupacion: Ocupacion::new(zonas.len()),
+            zonas,
+            puestos,
+            zonas_juegos,
+            zonas_espectaculos,
+            distancias: vec![vec![u64::MAX; lugares]; lugares],
+        };
+        for i in 0..lugares {
+            mapa.distancias[i][i] = 0;
+        }
+        for camino in caminos {
+            let (desde, hasta) = (mapa.indice(camino.desde)?, mapa.indice(camino.hasta)?);
+            let ms = camino.ms.min(mapa.distancias[desde][hasta]);
+            mapa.distancias[desde][hasta] = ms;
+            mapa.distancias[hasta][desde] = ms;
+        }
+        // Floyd-Warshall
+        for k in 0..lugares {
+            for i in 0..lugares {
+                for j in 0..lugares {
+                    let (ik, kj) = (mapa.distancias[i][k], mapa.distancias[k][j]);
+                    if ik != u64::MAX && kj != u64::MAX && ik + kj < mapa.distancias[i][j] {
+                        mapa.distancias[i][j] = ik + kj;
+                    }
+                }
+            }
+        }
+        if let Some(lugar) = (0..lugares).find(|i| mapa.distancias[0][*i] == u64::MAX) {
+            return Err(format!("No hay ningún camino desde la entrada hasta {}", mapa.lugar(lugar)));
+        }
+        Ok(mapa)
+    }
+
+    fn indice(&self, lugar: Lugar) -> Result<usize, String> {
+        match lugar {
+            Lugar::Entrada => Ok(0),
+            Lugar::Juego(id) if id < self.zonas_juegos.len() => Ok(1 + id),
+            Lugar::Puesto(id) if id < self.puestos.len() => Ok(1 + self.zonas_juegos.len() + id),
+            Lugar::Espectaculo(id) if id < self.zonas_espectaculos.len() => Ok(1 + self.zonas_juegos.len() + self.puestos.len() + id),
+            _ => Err(format!("No existe {}", lugar)),
+        }
+    }
+
+    fn lugar(&self, indice: usize) -> Lugar {
+        match indice {
+            0 => Lugar::Entrada,
+            i if i <= self.zonas_juegos.len() => Lugar::Juego(i - 1),
+            i if i <= self.zonas_juegos.len() + self.puestos.len() => Lugar::Puesto(i - 1 - self.zonas_juegos.len()),
+            i => Lugar::Espectaculo(i - 1 - self.zonas_juegos.len() - self.puestos.len()),
+        }
+    }
+
+    /// Tiempo del camino más corto entre dos lugares, en milisegundos
+    /// simulados.
+    pub fn distancia(&self, desde: Lugar, hasta: Lugar) -> u64 {
+        let (desde, hasta) = (self.indice(desde).expect("lugar del mapa"), self.indice(hasta).expect("lugar del mapa"));
+        self.distancias[desde][hasta]
+    }
+
+    pub fn zona(&self, lugar: Lugar) -> Option<usize> {
+        match lugar {
+            Lugar::Entrada => None,
+            Lugar::Juego(id) => self.zonas_juegos[id],
+            Lugar::Puesto(id) => self.puestos[id].zona,
+            Lugar::Espectaculo(id) => self.zonas_espectaculos[id],
+        }
+    }
+
+    /// El puesto de comida más cercano a `desde`, si hay alguno.
+    pub fn puesto_mas_cercano(&self, desde: Lugar) -> Option<usize> {
+        (0..self.puestos.len()).min_by_key(|puesto| self.distancia(desde, Lugar::Puesto(*puesto)))
+    }
+
+    /// Si entran `personas` en la zona de `lugar` para quienes vienen de
+    /// `desde`.
+    pub fn hay_lugar(&self, desde: Lugar, lugar: Lugar, personas: u32) -> bool {
+        match self.zona(lugar) {
+            Some(zona) if self.zona(desde) != Some(zona) => self.ocupacion.hay_lugar(zona, personas, &self.zonas),
+            _ => true,
+        }
+    }
+
+    /// Lleva a `personas` de `desde` a `hasta`: dejan la zona en la que
+    /// están, caminan llamando a `caminar` con el tiempo del camino y
+    /// esperan a que haya lugar en la zona de destino (llamando a
+    /// `esperar` antes de bloquearse). Si no cambian de zona sólo
+    /// caminan.
+    pub fn mover(&self, personas: u32, desde: Lugar, hasta: Lugar, caminar: impl FnOnce(u64), esperar: impl FnOnce(&Zona)) {
+        let (zona_desde, zona_hasta) = (self.zona(desde), self.zona(hasta));
+        if zona_desde != zona_hasta {
+            if let Some(zona) = zona_desde {
+                self.ocupacion.salir(zona, personas);
+            }
+        }
+        caminar(self.distancia(desde, hasta));
+        if zona_desde != zona_hasta {
+            if let Some(zona) = zona_hasta {
+                self.ocupacion.entrar(zona, personas, &self.zonas, || esperar(&self.zonas[zona]));
+            }
+        }
+    }
+
+    /// Máxima cantidad de personas que hubo en cada zona al mismo tiempo.
+    pub fn ocupacion_maxima(&self) -> Vec<u32> {
+        self.ocupacion.maxima()
+    }
+}
+
+/// Caminos de `ms` milisegundos entre todos los lugares de un parque
+/// con `juegos` juegos, `puestos` puestos de comida y `espectaculos`
+/// espectáculos.
+pub fn caminos_uniformes(juegos: usize, puestos: usize, espectaculos: usize, ms: u64) -> Vec<Camino> {
+    let lugares: Vec<Lugar> = std::iter::once(Lugar::Entrada)
+        .chain((0..juegos).map(Lugar::Juego))
+        .chain((0..puestos).map(Lugar::Puesto))
+        .chain((0..espectaculos).map(Lugar::Espectaculo))
+        .collect();
+    lugares.iter()
+        .enumerate()
+        .flat_map(|(i, desde)| lugares[i + 1..].iter().map(move |hasta| Camino { desde: *desde, hasta: *hasta, ms }))
+        .collect()
+}
+
+/// Personas en cada zona.
+#[derive(Debug)]
+struct Ocupacion {
+    /// Personas adentro y máximo, por zona.
+    estado: Mutex<Vec<(u32, u32)>>,
+    cv: Condvar,
+}
+
+impl Ocupacion {
+    fn new(zonas: usize) -> Self {
+        Self { estado: Mutex::new(vec![(0, 0); zonas]), cv: Condvar::new() }
+    }
+
+    fn hay_lugar(&self, zona: usize, personas: u32, zonas: &[Zona]) -> bool {
+        let adentro = self.estado.lock().expect("poisoned")[zona].0;
+        zonas[zona].capacidad.is_none_or(|capacidad| adentro + personas <= capacidad)
+    }
+
+    /// Espera a que entren `personas` en `zona` y las hace entrar. Un
+    /// grupo más grande que la zona entra cuando está vacía.
+    fn entrar(&self, zona: usize, personas: u32, zonas: &[Zona], esperar: impl FnOnce()) {
+        let mut estado = self.estado.lock().expect("poisoned");
+        let entran = |adentro: u32| zonas[zona].capacidad.is_none_or(|capacidad| adentro == 0 || adentro + personas <= capacidad);
+        if !entran(estado[zona].0) {
+            esperar();
+            while !entran(estado[zona].0) {
+                estado = self.cv.wait(estado).expect("poisoned");
+            }
+        }
+        let (adentro, maxima) = &mut estado[zona];
+        *adentro += personas;
+        *maxima = (*maxima).max(*adentro);
+    }
+
+    fn salir(&self, zona: usize, personas: u32) {
+        self.estado.lock().expect("poisoned")[zona].0 -= personas;
+        self.cv.notify_all();
+    }
+
+    fn maxima(&self) -> Vec<u32> {
+        self.estado.lock().expect("poisoned").iter().map(|(_, maxima)| *maxima).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::{Arc, mpsc}, thread};
+
+    fn zona(nombre: &str, capacidad: Option<u32>) -> Zona {
+        Zona { nombre: nombre.into(), capacidad }
+    }
+
+    #[test]
+    fn caminos_mas_cortos() {
+        let caminos = [
+            Camino { desde: Lugar::Entrada, hasta: Lugar::Juego(0), ms: 10 },
+            Camino { desde: Lugar::Juego(0), hasta: Lugar::Juego(1), ms: 5 },
+            Camino { desde: Lugar::Entrada, hasta: Lugar::Juego(1), ms: 30 },
+            Camino { desde: Lugar::Juego(1), hasta: Lugar::Puesto(0), ms: 2 },
+        ];
+        let puestos = vec![Puesto { nombre: "Pochoclos".into(), zona: Some(0) }];
+        let mapa = Mapa::new(vec![zona("Norte", None)], vec![None, Some(0)], vec![], puestos, &caminos).unwrap();
+        assert_eq!(mapa.distancia(Lugar::Entrada, Lugar::Juego(1)), 15);
+        assert_eq!(mapa.distancia(Lugar::Puesto(0), Lugar::Entrada), 17);
+        assert_eq!(mapa.distancia(Lugar::Juego(0), Lugar::Juego(0)), 0);
+        assert_eq!(mapa.puesto_mas_cercano(Lugar::Juego(0)), Some(0));
+        assert_eq!(mapa.zona(Lugar::Puesto(0)), Some(0));
+
+        let error = Mapa::new(vec![], vec![None, None], vec![], vec![], &caminos[..1]).unwrap_err();
+        assert!(error.contains("el juego 1"), "{}", error);
+        assert!(Mapa::new(vec![], vec![None], vec![], vec![], &caminos[1..2]).is_err());
+        assert!(Mapa::new(vec![], vec![Some(3)], vec![], vec![], &caminos[..1]).is_err());
+
+        // los caminos explícitos más cortos tienen prioridad
+        let mut uniformes = caminos_uniformes(3, 1, 1, 7);
+        assert_eq!(uniformes.len(), 15);
+        uniformes.push(caminos[0]);
+        let puesto = vec![Puesto { nombre: "Helados".into(), zona: None }];
+        let uniforme = Mapa::new(vec![], vec![None; 3], vec![Some(0)], puesto, &uniformes);
+        assert!(uniforme.is_err());
+        let puesto = vec![Puesto { nombre: "Helados".into(), zona: None }];
+        let uniforme = Mapa::new(vec![zona("Sur", None)], vec![None; 3], vec![Some(0)], puesto, &uniformes).unwrap();
+        assert_eq!(uniforme.distancia(Lugar::Espectaculo(0), Lugar::Puesto(0)), 7);
+        assert_eq!(uniforme.zona(Lugar::Espectaculo(0)), Some(0));
+        assert_eq!(uniforme.distancia(Lugar::Juego(0), Lugar::Juego(2)), 7);
+        assert_eq!(uniforme.distancia(Lugar::Entrada, Lugar::Juego(0)), 7);
+        assert_eq!(uniforme.distancia(Lugar::Puesto(0), Lugar::Juego(1)), 7);
+    }
+
+    #[test]
+    fn las_zonas_llenas_hacen_esperar() {
+        let caminos = [
+            Camino { desde: Lugar::Entrada, hasta: Lugar::Juego(0), ms: 1 },
+            Camino { desde: Lugar::Entrada, hasta: Lugar::Juego(1), ms: 1 },
+        ];
+        let mapa = Arc::new(Mapa::new(vec![zona("Chica", Some(2))], vec![Some(0), None], vec![], vec![], &caminos).unwrap());
+        mapa.mover(2, Lugar::Entrada, Lugar::Juego(0), |_| {}, |_| panic!("hay lugar"));
+        assert!(!mapa.hay_lugar(Lugar::Entrada, Lugar::Juego(0), 1));
+        // quien ya está en la zona puede moverse dentro de ella
+        assert!(mapa.hay_lugar(Lugar::Juego(0), Lugar::Juego(0), 1));
+
+        let otro = Arc::clone(&mapa);
+        let (empezo_a_esperar, espera) = mpsc::channel();
+        let esperando = thread::spawn(move || {
+            let mut espero = false;
+            otro.mover(1, Lugar::Entrada, Lugar::Juego(0), |_| {}, |zona| {
+                assert_eq!(zona.nombre, "Chica");
+                espero = true;
+                empezo_a_esperar.send(()).unwrap();
+            });
+            espero
+        });
+        // el aviso llega con la ocupación tomada, así que al salir de la
+        // zona ya está esperando
+        espera.recv().unwrap();
+        mapa.mover(1, Lugar::Juego(0), Lugar::Juego(1), |ms| assert_eq!(ms, 2), |_| {});
+        assert!(esperando.join().unwrap());
+        assert_eq!(mapa.ocupacion_maxima(), vec![2]);
+    }
+}
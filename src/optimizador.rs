@@ -0,0 +1,364 @@
+//! Optimizador de precios: busca el precio de cada juego que maximiza
+//! la caja (o la caja penalizada por la espera en las filas, o premiada
+//! por la satisfacción de los visitantes) sobre un lote de simulaciones
+//! con semillas fijas.
+//!
+//! ```bash
+//! $ ./parque-oxidado optimizar --metodo=recocido --precios=5..40:5 --evaluaciones=200 --corridas=10 --personas=30:20:80
+//! ```
+//!
+//! Todas las evaluaciones usan las mismas semillas, de forma que la
+//! diferencia entre dos configuraciones no dependa del azar de las
+//! corridas.
+
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng, prelude::StdRng};
+
+use crate::{
+    args::Args,
+    barrido::{Barrer, parse_valores, producto},
+    lote::{OpcionesLote, correr_corridas, parse_opciones},
+    simulacion::Resultado,
+};
+
+const EVALUACIONES_POR_DEFECTO: usize = 100;
+const PRECIOS_POR_DEFECTO: &str = "5..40:5";
+/// Factor por el que se multiplica la temperatura del recocido simulado
+/// en cada paso.
+const ENFRIAMIENTO: f64 = 0.95;
+
+/// Estrategia de búsqueda.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metodo {
+    /// Todas las combinaciones de precios. Ignora `--evaluaciones`.
+    Grilla,
+    /// Combinaciones de precios al azar.
+    Aleatorio,
+    /// Se mueve al mejor vecino mientras alguno mejore el objetivo.
+    Escalada,
+    /// Recocido simulado: acepta vecinos peores con una probabilidad
+    /// que disminuye con el tiempo.
+    Recocido,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpcionesOptimizador {
+    pub metodo: Metodo,
+    /// Precios posibles de cada juego, ordenados.
+    pub precios: Vec<u32>,
+    /// Cantidad máxima de configuraciones distintas a evaluar.
+    pub evaluaciones: usize,
+    /// Pesos que se le restan a la caja promedio por cada milisegundo
+    /// de espera promedio en las filas. Con 0 se maximiza sólo la caja.
+    pub peso_espera: f64,
+    /// Pesos que se le suman a la caja promedio por cada punto de
+    /// satisfacción promedio de los visitantes.
+    pub peso_satisfaccion: f64,
+    pub lote: OpcionesLote,
+}
+
+/// Separa las opciones del optimizador (`--metodo`, `--precios`,
+/// `--evaluaciones`, `--peso-espera`, `--peso-satisfaccion` y las del
+/// lote) del resto de los
+/// argumentos, que describen el escenario.
+pub fn parse_opciones_optimizador(argv: &[String]) -> Result<(OpcionesOptimizador, Vec<String>), String> {
+    let mut metodo = Metodo::Escalada;
+    let mut precios = parse_valores(PRECIOS_POR_DEFECTO)?;
+    let mut evaluaciones = EVALUACIONES_POR_DEFECTO;
+    let mut peso_espera = 0.0;
+    let mut peso_satisfaccion = 0.0;
+    let mut resto = vec![];
+    for arg in argv {
+        if let Some(valor) = arg.strip_prefix("--metodo=") {
+            metodo = match valor {
+                "grilla" => Metodo::Grilla,
+                "aleatorio" => Metodo::Aleatorio,
+                "escalada" => Metodo::Escalada,
+                "recocido" => Metodo::Recocido,
+                _ => return Err(format!(
+                    "[--metodo] '{}' no es un método válido (grilla, aleatorio, escalada o recocido)", valor
+                )),
+            };
+        } else if let Some(valor) = arg.strip_prefix("--precios=") {
+            precios = parse_valores(valor).map_err(|e| format!("[--precios] {}", e))?;
+        } else if let Some(valor) = arg.strip_prefix("--evaluaciones=") {
+            evaluaciones = match valor.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(format!("[--evaluaciones] '{}' no es un número natural", valor)),
+            };
+        } else if let Some(valor) = arg.strip_prefix("--peso-espera=") {
+            peso_espera = match valor.parse::<f64>() {
+                Ok(peso) if peso >= 0.0 => peso,
+                _ => return Err(format!("[--peso-espera] '{}' no es un número no negativo", valor)),
+            };
+        } else if let Some(valor) = arg.strip_prefix("--peso-satisfaccion=") {
+            peso_satisfaccion = match valor.parse::<f64>() {
+                Ok(peso) if peso >= 0.0 => peso,
+                _ => return Err(format!("[--peso-satisfaccion] '{}' no es un número no negativo", valor)),
+            };
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+    precios.sort_unstable();
+    precios.dedup();
+    let (lote, resto) = parse_opciones(&resto)?;
+    Ok((OpcionesOptimizador { metodo, precios, evaluaciones, peso_espera, peso_satisfaccion, lote }, resto))
+}
+
+/// Memoriza el valor del objetivo de cada configuración y cuenta las
+/// evaluaciones distintas.
+struct Evaluador<F> {
+    objetivo: F,
+    valores: HashMap<Vec<u32>, f64>,
+    limite: usize,
+    mejor: Option<(Vec<u32>, f64)>,
+}
+
+impl<F: FnMut(&[u32]) -> Result<f64, String>> Evaluador<F> {
+    fn new(objetivo: F, limite: usize) -> Self {
+        Self { objetivo, valores: HashMap::new(), limite, mejor: None }
+    }
+
+    fn valor(&mut self, punto: &[u32]) -> Result<f64, String> {
+        if let Some(valor) = self.valores.get(punto) {
+            return Ok(*valor);
+        }
+        let valor = (self.objetivo)(punto)?;
+        self.valores.insert(punto.to_vec(), valor);
+        if self.mejor.as_ref().is_none_or(|(_, mejor)| valor > *mejor) {
+            self.mejor = Some((punto.to_vec(), valor));
+        }
+        Ok(valor)
+    }
+
+    fn agotado(&self) -> bool {
+        self.valores.len() >= self.limite
+    }
+}
+
+/// Vecinos de `punto`: los que cambian el precio de un juego al precio
+/// inmediatamente superior o inferior de `dominio`.
+fn vecinos(punto: &[u32], dominio: &[u32]) -> Vec<Vec<u32>> {
+    let mut vecinos = vec![];
+    for i in 0..punto.len() {
+        let posicion = dominio.binary_search(&punto[i]).unwrap_or_else(|p| p.min(dominio.len() - 1));
+        for otra in [posicion.checked_sub(1), Some(posicion + 1)].iter().flatten() {
+            if let Some(precio) = dominio.get(*otra) {
+                let mut vecino = punto.to_vec();
+                vecino[i] = *precio;
+                vecinos.push(vecino);
+            }
+        }
+    }
+    vecinos
+}
+
+/// Busca la configuración de `dimensiones` precios de `dominio` que
+/// maximiza `objetivo`, partiendo de `inicial` (salvo en la grilla y la
+/// búsqueda aleatoria). Devuelve la mejor configuración evaluada.
+pub fn buscar(
+    metodo: Metodo,
+    dominio: &[u32],
+    inicial: Vec<u32>,
+    evaluaciones: usize,
+    rng: &mut StdRng,
+    objetivo: impl FnMut(&[u32]) -> Result<f64, String>,
+) -> Result<(Vec<u32>, f64), String> {
+    let dimensiones = inicial.len();
+    let mut evaluador = Evaluador::new(objetivo, evaluaciones);
+    // tope de pasos para no iterar indefinidamente sobre puntos ya evaluados
+    let pasos = evaluaciones * 10;
+
+    match metodo {
+        Metodo::Grilla => {
+            let ejes = vec![Barrer { parametro: String::new(), valores: dominio.to_vec() }; dimensiones];
+            for punto in producto(&ejes) {
+                evaluador.valor(&punto)?;
+            }
+        },
+        Metodo::Aleatorio => {
+            for _ in 0..pasos {
+                if evaluador.agotado() {
+                    break;
+                }
+                let punto: Vec<u32> = (0..dimensiones)
+                    .map(|_| dominio[rng.gen_range(0..dominio.len())])
+                    .collect();
+                evaluador.valor(&punto)?;
+            }
+        },
+        Metodo::Escalada => {
+            let mut actual = inicial;
+            let mut valor_actual = evaluador.valor(&actual)?;
+            while !evaluador.agotado() {
+                let mut mejor_vecino = None;
+                for vecino in vecinos(&actual, dominio) {
+                    let valor = evaluador.valor(&vecino)?;
+                    if valor > mejor_vecino.as_ref().map_or(valor_actual, |(_, v)| *v) {
+                        mejor_vecino = Some((vecino, valor));
+                    }
+                    if evaluador.agotado() {
+                        break;
+                    }
+                }
+                match mejor_vecino {
+                    Some((vecino, valor)) => {
+                        actual = vecino;
+                        valor_actual = valor;
+                    },
+                    None => break // óptimo local
+                }
+            }
+        },
+        Metodo::Recocido => {
+            let mut actual = inicial;
+            let mut valor_actual = evaluador.valor(&actual)?;
+            let mut temperatura = (valor_actual.abs() * 0.1).max(1.0);
+            for _ in 0..pasos {
+                if evaluador.agotado() {
+                    break;
+                }
+                let vecinos = vecinos(&actual, dominio);
+                if vecinos.is_empty() {
+                    break;
+                }
+                let vecino = vecinos[rng.gen_range(0..vecinos.len())].clone();
+                let valor = evaluador.valor(&vecino)?;
+                let delta = valor - valor_actual;
+                if delta >= 0.0 || rng.gen::<f64>() < (delta / temperatura).exp() {
+                    actual = vecino;
+                    valor_actual = valor;
+                }
+                temperatura *= ENFRIAMIENTO;
+            }
+        },
+    }
+    evaluador.mejor.ok_or_else(|| "No se evaluó ninguna configuración".into())
+}
+
+/// Valor del objetivo sobre un lote: caja promedio menos `peso_espera`
+/// por la espera promedio en las filas, más `peso_satisfaccion` por la
+/// satisfacción promedio de los visitantes.
+pub fn objetivo(resultados: &[Resultado], peso_espera: f64, peso_satisfaccion: f64) -> f64 {
+    let n = resultados.len().max(1) as f64;
+    let caja = resultados.iter().map(|r| r.caja as f64).sum::<f64>() / n;
+    let espera = resultados.iter().map(Resultado::espera_promedio_ms).sum::<f64>() / n;
+    let satisfaccion = resultados.iter().map(Resultado::satisfaccion_promedio).sum::<f64>() / n;
+    caja - peso_espera * espera + peso_satisfaccion * satisfaccion
+}
+
+/// Corre la búsqueda sobre el escenario `args` e imprime la mejor
+/// configuración como línea de comandos.
+pub fn correr(opciones: OpcionesOptimizador, args: &Args) -> Result<(), String> {
+    let mut lote = opciones.lote.clone();
+    lote.resolver(args.semilla);
+    let dominio = &opciones.precios;
+    let inicial: Vec<u32> = args.costo_juegos.as_deref().unwrap_or_default()
+        .iter()
+        .map(|precio| *dominio.iter().min_by_key(|p| p.abs_diff(*precio)).expect("dominio vacío"))
+        .collect();
+
+    let mut evaluacion = 0;
+    let mut mejor = f64::NEG_INFINITY;
+    let mut rng = StdRng::seed_from_u64(args.semilla as u64);
+    let (precios, valor) = buscar(opciones.metodo, dominio, inicial, opciones.evaluaciones, &mut rng, |precios| {
+        let mut args = args.clone();
+        args.costo_juegos = Some(precios.to_vec());
+        args.rapido = true;
+        let resultados = correr_corridas(&args, lote.semillas(), lote.hilos)?;
+        let valor = objetivo(&resultados, opciones.peso_espera, opciones.peso_satisfaccion);
+        evaluacion += 1;
+        if valor > mejor {
+            mejor = valor;
+            println!("Evaluación {}: precios {:?}, objetivo {:.2}", evaluacion, precios, valor);
+        }
+        Ok(valor)
+    })?;
+
+    let mut mejores = args.clone();
+    mejores.costo_juegos = Some(precios.clone());
+    println!("\nMejores precios: {:?}, objetivo {:.2} ({} evaluaciones)", precios, valor, evaluacion);
+    println!("{}", mejores.as_str());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    /// Objetivo cóncavo con máximo en (20, 10).
+    fn parabola(punto: &[u32]) -> Result<f64, String> {
+        let (x, y) = (punto[0] as f64, punto[1] as f64);
+        Ok(1000.0 - (x - 20.0).powi(2) - 2.0 * (y - 10.0).powi(2))
+    }
+
+    #[test]
+    fn interpreta_las_opciones() {
+        let (opciones, resto) = parse_opciones_optimizador(&argv(&[
+            "--metodo=grilla", "--precios=15,5,10,5", "--peso-espera=0.5", "--peso-satisfaccion=2",
+            "--corridas=3", "--personas=4:20"
+        ])).unwrap();
+        assert_eq!(opciones.metodo, Metodo::Grilla);
+        assert_eq!(opciones.precios, vec![5, 10, 15]);
+        assert_eq!(opciones.peso_espera, 0.5);
+        assert_eq!(opciones.peso_satisfaccion, 2.0);
+        assert_eq!(opciones.lote.corridas, 3);
+        assert_eq!(resto, argv(&["--personas=4:20"]));
+        assert!(parse_opciones_optimizador(&argv(&["--metodo=magia"])).is_err());
+        assert!(parse_opciones_optimizador(&argv(&["--peso-satisfaccion=-1"])).is_err());
+    }
+
+    #[test]
+    fn todos_los_metodos_encuentran_el_maximo() {
+        let dominio = parse_valores("5..40:5").unwrap();
+        for metodo in [Metodo::Grilla, Metodo::Aleatorio, Metodo::Escalada, Metodo::Recocido] {
+            let mut rng = StdRng::seed_from_u64(1);
+            let (punto, valor) = buscar(metodo, &dominio, vec![40, 40], 64, &mut rng, parabola).unwrap();
+            assert_eq!(punto, vec![20, 10], "{:?}", metodo);
+            assert_eq!(valor, 1000.0);
+        }
+    }
+
+    #[test]
+    fn respeta_la_cantidad_de_evaluaciones() {
+        let dominio = parse_valores("1..100").unwrap();
+        let mut evaluaciones = 0;
+        let mut rng = StdRng::seed_from_u64(1);
+        buscar(Metodo::Aleatorio, &dominio, vec![1, 1], 10, &mut rng, |punto| {
+            evaluaciones += 1;
+            parabola(punto)
+        }).unwrap();
+        assert_eq!(evaluaciones, 10);
+    }
+
+    #[test]
+    fn optimiza_el_precio_de_un_juego() {
+        // con presupuestos de $ 40 conviene cobrar $ 20: a $ 15 o a $ 30
+        // a cada persona le sobran $ 10
+        let (opciones, _) = parse_opciones_optimizador(&argv(&[
+            "--metodo=grilla", "--precios=15,20,30", "--semillas=1,2", "--hilos=2"
+        ])).unwrap();
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![40; 3];
+        args.costo_juegos = Some(vec![10]);
+        args.rapido = true;
+        args.resolver().unwrap();
+
+        let mut lote = opciones.lote.clone();
+        lote.resolver(args.semilla);
+        let mut rng = StdRng::seed_from_u64(1);
+        let (precios, valor) = buscar(Metodo::Grilla, &opciones.precios, vec![10], 10, &mut rng, |precios| {
+            let mut args = args.clone();
+            args.costo_juegos = Some(precios.to_vec());
+            Ok(objetivo(&correr_corridas(&args, lote.semillas(), lote.hilos)?, 0.0, 0.0))
+        }).unwrap();
+        assert_eq!(precios, vec![20]);
+        assert_eq!(valor, 120.0);
+    }
+}
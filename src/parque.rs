@@ -6,50 +6,201 @@ use std::{
     },
     thread::JoinHandle
 };
-use rand::{Rng, SeedableRng, prelude::StdRng};
-use std_semaphore::Semaphore;
+use rand::{Rng, prelude::StdRng};
 
-use crate::{juego::Juego, logger::{TaggedLogger}};
+use crate::{arquetipo::Arquetipo, clima::{Clima, ESPERA_REFUGIO_MS, Pronostico}, entrada::{Entrada, SinEntrada}, espectaculo::{ConfiguracionEspectaculo, Espectaculo, EstadisticasEspectaculo}, juego::Juego, logger::{TaggedLogger}, mapa::{Lugar, Mapa}, reloj::Reloj, restricciones::Atributos, watchdog::Watchdog};
+
+/// Por qué no hay ningún juego para elegir.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinJuego {
+    /// No alcanza el dinero, no entran o no pueden subir a ninguno.
+    Ninguno,
+    /// Los juegos a los que podrían ir están cerrados por tormenta.
+    Cerrados,
+}
 
 pub struct Parque {
     juegos: Mutex<Vec<Arc<Juego>>>,
     juegos_threads: Mutex<Vec<JoinHandle<()>>>,
     caja: Arc<AtomicU32>,
-    capacidad: Semaphore,
+    entrada: Entrada,
     cantidad_visitantes: AtomicUsize,
-    rng: Mutex<StdRng>,
+    desistieron: AtomicUsize,
+    personas_adentro: AtomicUsize,
+    ocupacion_maxima: AtomicUsize,
+    reloj: Reloj,
+    /// Momento en que cierra, en milisegundos simulados desde el inicio.
+    cierre_ms: Option<u64>,
+    /// Zonas y caminos. Sin mapa las personas van de un juego a otro
+    /// sin demora.
+    mapa: Option<Mapa>,
+    espectaculos: Vec<Espectaculo>,
+    /// Clima a lo largo del día. Sin pronóstico siempre está soleado.
+    pronostico: Option<Pronostico>,
+    watchdog: Watchdog,
     log: TaggedLogger
 }
 
 impl Parque {
-    pub fn new(log: TaggedLogger, capacidad: usize, semilla: u64) -> Self {
+    pub fn new(log: TaggedLogger, capacidad: usize, reloj: Reloj) -> Self {
         Self {
             caja: Arc::new(AtomicU32::new(0)), 
-            capacidad: Semaphore::new(capacidad as isize),
+            entrada: Entrada::new(capacidad),
             cantidad_visitantes: AtomicUsize::new(0),
+            desistieron: AtomicUsize::new(0),
+            personas_adentro: AtomicUsize::new(0),
+            ocupacion_maxima: AtomicUsize::new(0),
             juegos: Mutex::new(vec![]),
             juegos_threads: Mutex::new(vec![]),
-            rng: Mutex::new(StdRng::seed_from_u64(semilla)),
+            reloj,
+            cierre_ms: None,
+            mapa: None,
+            espectaculos: vec![],
+            pronostico: None,
+            watchdog: Watchdog::new(),
             log
         }
     }
 
+    /// Hace que el parque cierre a los `cierre_ms` milisegundos
+    /// simulados. Las personas que están adentro se van al terminar lo
+    /// que están haciendo.
+    pub fn con_cierre(mut self, cierre_ms: Option<u64>) -> Self {
+        self.cierre_ms = cierre_ms;
+        self
+    }
+
+    /// Hace que las personas caminen por `mapa` entre los juegos.
+    pub fn con_mapa(mut self, mapa: Option<Mapa>) -> Self {
+        self.mapa = mapa;
+        self
+    }
+
+    pub fn mapa(&self) -> Option<&Mapa> {
+        self.mapa.as_ref()
+    }
+
+    /// Agrega espectáculos con funciones en horarios fijos, con ids en
+    /// el orden de `espectaculos`.
+    pub fn con_espectaculos(mut self, espectaculos: Vec<ConfiguracionEspectaculo>) -> Self {
+        self.espectaculos = espectaculos.into_iter()
+            .enumerate()
+            .map(|(id, configuracion)| Espectaculo::new(id, configuracion))
+            .collect();
+        self
+    }
+
+    pub fn espectaculo(&self, id: usize) -> &Espectaculo {
+        &self.espectaculos[id]
+    }
+
+    pub fn estadisticas_espectaculos(&self) -> Vec<EstadisticasEspectaculo> {
+        self.espectaculos.iter().map(Espectaculo::estadisticas).collect()
+    }
+
+    /// Hace que el clima cambie según `cambios` (momento desde el que
+    /// rige cada clima, en milisegundos simulados). Sin cambios siempre
+    /// está soleado.
+    pub fn con_clima(mut self, cambios: Option<Vec<(u64, Clima)>>) -> Self {
+        self.pronostico = cambios.map(Pronostico::new);
+        self
+    }
+
+    /// Clima en este momento.
+    pub fn clima(&self) -> Clima {
+        self.pronostico.as_ref().map_or(Clima::Soleado, Pronostico::actual)
+    }
+
+    /// Cambios de clima del pronóstico, vacío si no hay.
+    pub fn cambios_de_clima(&self) -> Vec<(u64, Clima)> {
+        self.pronostico.as_ref().map_or(vec![], |pronostico| pronostico.cambios().to_vec())
+    }
+
+    /// Lanza el hilo que sigue el pronóstico, si hay, y devuelve la
+    /// cantidad de hilos lanzados. El hilo se joinea al cerrar el parque.
+    pub fn iniciar_clima(self: &Arc<Self>) -> usize {
+        let pronostico = match &self.pronostico {
+            Some(pronostico) => pronostico,
+            None => return 0,
+        };
+        // el clima del comienzo rige antes de que llegue nadie
+        let iniciales = pronostico.cambios().iter().take_while(|(desde, _)| *desde == 0).count();
+        for &(_, clima) in &pronostico.cambios()[..iniciales] {
+            self.cambiar_clima(clima);
+        }
+        let parque = self.clone();
+        self.juegos_threads.lock().expect("poisoned")
+            .push(std::thread::spawn(move || parque.seguir_clima(iniciales)));
+        1
+    }
+
+    /// Aplica cada cambio del pronóstico a partir de `desde_cambio` cuando
+    /// llega su momento.
+    fn seguir_clima(&self, desde_cambio: usize) {
+        let pronostico = self.pronostico.as_ref().expect("parque con pronóstico");
+        for &(desde, clima) in &pronostico.cambios()[desde_cambio..] {
+            let ahora = self.reloj.ahora();
+            if desde > ahora && !pronostico.esperar(self.reloj.duracion(desde - ahora)) {
+                return;
+            }
+            self.cambiar_clima(clima);
+        }
+    }
+
+    /// Con tormenta cierra los juegos al aire libre y con otro clima los
+    /// vuelve a abrir.
+    fn cambiar_clima(&self, clima: Clima) {
+        let pronostico = self.pronostico.as_ref().expect("parque con pronóstico");
+        self.log.con_tag("CLIMA").write(&format!("Cambia el clima: {}", clima));
+        pronostico.cambiar(clima);
+        for juego in self.obtener_juegos().iter().filter(|juego| !juego.cubierto()) {
+            if clima == Clima::Tormenta {
+                juego.suspender();
+            } else {
+                juego.reanudar();
+            }
+        }
+    }
+
+    /// Decide con `rng` si el clima hace que alguien se vaya del parque.
+    /// Sólo se sortea con lluvia o tormenta.
+    pub fn irse_por_el_clima(&self, rng: &mut StdRng) -> bool {
+        let probabilidad = self.clima().probabilidad_irse();
+        probabilidad > 0.0 && rng.gen_bool(probabilidad)
+    }
+
+    /// Espera bajo techo a que reabra algún juego, cuando todos los que
+    /// podrían elegir están cerrados por tormenta.
+    pub fn esperar_reapertura(&self, log: &TaggedLogger, reportar: impl Fn(String)) {
+        log.write(&format!("Los juegos están cerrados por la tormenta, espero {} ms bajo techo", ESPERA_REFUGIO_MS));
+        reportar("esperando que reabran los juegos".into());
+        self.reloj.dormir(ESPERA_REFUGIO_MS);
+    }
+
+    pub fn cerrado(&self) -> bool {
+        self.cierre_ms.is_some_and(|cierre| self.reloj.ahora() >= cierre)
+    }
+
     pub fn registrar_juegos(&self, juegos: Vec<Juego>) {
         let mut juegos_vec = self.juegos.lock().expect("poisoned");
         let mut juegos_threads_vec = self.juegos_threads.lock().expect("poisoned");
         for juego in juegos {
             let juego_ref = Arc::new(juego);
             juegos_vec.push(juego_ref.clone());
-            juegos_threads_vec.push(std::thread::spawn(move || {
-                juego_ref.iniciar_funcionamiento();
-            }));
+            // un hilo por cada unidad del juego
+            for unidad in 0..juego_ref.cantidad_unidades() {
+                let juego_ref = juego_ref.clone();
+                juegos_threads_vec.push(std::thread::spawn(move || {
+                    juego_ref.iniciar_funcionamiento(unidad);
+                }));
+            }
         }
     }
 
-    fn obtener_juegos_posibles(&self, presupuesto_maximo: u32) -> Vec<Arc<Juego>> {
+    fn obtener_juegos_posibles(&self, presupuesto_maximo: u32, atributos: &[Atributos]) -> Vec<Arc<Juego>> {
         let mut resultado = vec![];
         for juego in self.juegos.lock().expect("poisoned").iter() {
-            if presupuesto_maximo >= juego.precio {
+            if presupuesto_maximo >= juego.precio && atributos.iter().all(|a| juego.restricciones().permite(a)) {
                 resultado.push(juego.clone());
             }
         }
@@ -57,23 +208,191 @@ impl Parque {
         resultado
     }
 
-    pub fn elegir_juego_random(&self, presupuesto_maximo: u32) -> Result<Arc<Juego>, &'static str> {
-        let juegos_posibles = self.obtener_juegos_posibles(presupuesto_maximo);
+    /// Elige, usando `rng`, uno de los juegos en los que entra un grupo
+    /// de personas con `atributos`, al que todas pueden subir y que
+    /// cada una puede pagar con `presupuesto_maximo`. Sin `arquetipo`
+    /// se elige al azar; si no, según sus preferencias y, con mapa, la
+    /// distancia desde donde están. Con mapa se evitan los juegos de
+    /// zonas llenas, salvo que no quede otro, y al salir de un
+    /// espectáculo se elige entre los juegos de su zona si hay alguno.
+    /// Nunca se elige un juego cerrado por tormenta y, con lluvia o
+    /// tormenta, se prefieren los cubiertos.
+    pub fn elegir_juego_para_grupo(&self, presupuesto_maximo: u32, atributos: &[Atributos], arquetipo: Option<&Arquetipo>,
+                                   desde: Lugar, rng: &mut StdRng) -> Result<Arc<Juego>, SinJuego> {
+        let mut juegos_posibles: Vec<_> = self.obtener_juegos_posibles(presupuesto_maximo, atributos)
+            .into_iter()
+            .filter(|juego| juego.capacidad() as usize >= atributos.len())
+            .filter(|juego| arquetipo.is_none_or(|arquetipo| arquetipo.puede_subir(juego)))
+            .collect();
         if juegos_posibles.is_empty() {
-            Err("No alcanza el dinero")
-        } else {
-            let mut rng = self.rng.lock().expect("posioned rng");
-            Ok(juegos_posibles[rng.gen_range(0..juegos_posibles.len())].clone())
+            return Err(SinJuego::Ninguno);
+        }
+        juegos_posibles.retain(|juego| !juego.suspendido());
+        if juegos_posibles.is_empty() {
+            return Err(SinJuego::Cerrados);
+        }
+        if self.clima().desfavorable() && juegos_posibles.iter().any(|juego| juego.cubierto()) {
+            juegos_posibles.retain(|juego| juego.cubierto());
+        }
+        if let Some(mapa) = &self.mapa {
+            let con_lugar: Vec<_> = juegos_posibles.iter()
+                .filter(|juego| mapa.hay_lugar(desde, Lugar::Juego(juego.id), atributos.len() as u32))
+                .cloned()
+                .collect();
+            if !con_lugar.is_empty() {
+                juegos_posibles = con_lugar;
+            }
+            if let (Lugar::Espectaculo(_), Some(zona)) = (desde, mapa.zona(desde)) {
+                let cercanos: Vec<_> = juegos_posibles.iter()
+                    .filter(|juego| mapa.zona(Lugar::Juego(juego.id)) == Some(zona))
+                    .cloned()
+                    .collect();
+                if !cercanos.is_empty() {
+                    juegos_posibles = cercanos;
+                }
+            }
+        }
+        // el juego del que acaban de bajar queda como el más lejano, para
+        // que quienes prefieren los cercanos no repitan siempre el mismo
+        let distancia = |juego: &Juego| match &self.mapa {
+            Some(_) if desde == Lugar::Juego(juego.id) => u64::MAX,
+            Some(mapa) => mapa.distancia(desde, Lugar::Juego(juego.id)),
+            None => 0,
+        };
+        Ok(match arquetipo {
+            Some(arquetipo) => arquetipo.elegir(&juegos_posibles, distancia, rng),
+            None => juegos_posibles[rng.gen_range(0..juegos_posibles.len())].clone(),
+        })
+    }
+
+    /// Lleva a `personas` de `desde` a `hasta` por el camino más corto
+    /// del mapa y devuelve cuánto caminaron, en milisegundos simulados.
+    /// Si la zona de destino está llena esperan a que se libere lugar.
+    /// Lo que van haciendo se escribe en `log` y se informa con
+    /// `reportar`. Sin mapa no caminan.
+    pub fn caminar(&self, personas: u32, desde: Lugar, hasta: Lugar, log: &TaggedLogger, reportar: impl Fn(String)) -> u64 {
+        let mapa = match &self.mapa {
+            Some(mapa) => mapa,
+            None => return 0
+        };
+        let destino = self.describir(hasta);
+        let mut caminado = 0;
+        mapa.mover(personas, desde, hasta, |ms| {
+            if ms > 0 {
+                log.write(&format!("Camino {} ms hasta {}", ms, destino));
+                reportar(format!("caminando hasta {}", destino));
+                self.reloj.dormir(ms);
+            }
+            caminado = ms;
+        }, |zona| {
+            log.write(&format!("La zona {} está llena, espero para entrar", zona.nombre));
+            reportar(format!("esperando para entrar a la zona {}", zona.nombre));
+        });
+        caminado
+    }
+
+    /// La función más próxima a la que pueden llegar a tiempo desde
+    /// `desde`, de algún espectáculo que puedan pagar con `presupuesto`,
+    /// sin contar las `descartadas` (según el id del espectáculo y el
+    /// número de función).
+    pub fn elegir_funcion(&self, presupuesto: u32, desde: Lugar,
+                          descartada: impl Fn(usize, usize) -> bool) -> Option<(&Espectaculo, usize)> {
+        let ahora = self.reloj.ahora();
+        self.espectaculos.iter()
+            .filter(|espectaculo| espectaculo.precio <= presupuesto)
+            .flat_map(|espectaculo| {
+                let caminata = self.mapa.as_ref().map_or(0, |mapa| mapa.distancia(desde, Lugar::Espectaculo(espectaculo.id)));
+                espectaculo.proximas_funciones(ahora, caminata).into_iter().map(move |funcion| (espectaculo, funcion))
+            })
+            .filter(|(espectaculo, funcion)| !descartada(espectaculo.id, *funcion))
+            .min_by_key(|(espectaculo, funcion)| espectaculo.inicio_ms(*funcion))
+    }
+
+    /// Espera a que empiece `funcion` de `espectaculo` y la ve hasta que
+    /// termina. Lo que va pasando se escribe en `log` y se informa con
+    /// `reportar`.
+    pub fn ver_funcion(&self, espectaculo: &Espectaculo, funcion: usize, log: &TaggedLogger, reportar: impl Fn(String)) {
+        let inicio = espectaculo.inicio_ms(funcion);
+        let ahora = self.reloj.ahora();
+        if inicio > ahora {
+            log.write(&format!("Espero {} ms a que empiece el espectáculo {}", inicio - ahora, espectaculo.descripcion()));
+            reportar(format!("esperando que empiece el espectáculo {}", espectaculo.descripcion()));
+            self.reloj.dormir(inicio - ahora);
         }
+        log.write(&format!("Viendo el espectáculo {}", espectaculo.descripcion()));
+        reportar(format!("viendo el espectáculo {}", espectaculo.descripcion()));
+        self.reloj.dormir(espectaculo.duracion_ms);
+        log.write(&format!("Terminó el espectáculo {}", espectaculo.descripcion()));
     }
 
-    pub fn ingresar_persona(&self) {
-        self.capacidad.acquire();
+    /// El puesto de comida del mapa más cercano a `desde`, si hay.
+    pub fn puesto_mas_cercano(&self, desde: Lugar) -> Option<usize> {
+        self.mapa.as_ref().and_then(|mapa| mapa.puesto_mas_cercano(desde))
+    }
+
+    fn describir(&self, lugar: Lugar) -> String {
+        match lugar {
+            Lugar::Entrada => "la salida".into(),
+            Lugar::Juego(id) => match self.juegos.lock().expect("poisoned").iter().find(|juego| juego.id == id) {
+                Some(juego) => format!("el juego {}", juego.descripcion()),
+                None => lugar.to_string(),
+            },
+            Lugar::Puesto(id) => match self.mapa.as_ref().and_then(|mapa| mapa.puestos.get(id)) {
+                Some(puesto) => format!("el puesto {}", puesto.nombre),
+                None => lugar.to_string(),
+            },
+            Lugar::Espectaculo(id) => match self.espectaculos.get(id) {
+                Some(espectaculo) => format!("el espectáculo {}", espectaculo.descripcion()),
+                None => lugar.to_string(),
+            },
+        }
+    }
+
+    /// Hace entrar a un grupo de `personas` al parque por la fila de
+    /// entrada, en orden de llegada, y devuelve cuánto esperaron en
+    /// milisegundos simulados. Si la fila tiene más de `tolerancia`
+    /// personas, o el grupo no entra en el parque, desisten y se
+    /// devuelve el motivo; se las cuenta como si ya hubieran salido.
+    pub fn ingresar_grupo(&self, personas: usize, tolerancia: Option<u32>) -> Result<u64, SinEntrada> {
+        let llegada = self.reloj.ahora();
+        if let Err(motivo) = self.entrada.ingresar(personas, tolerancia) {
+            self.desistieron.fetch_add(personas, Ordering::SeqCst);
+            self.cantidad_visitantes.fetch_add(personas, Ordering::SeqCst);
+            return Err(motivo);
+        }
+        let espera = self.reloj.ahora() - llegada;
+        let adentro = self.personas_adentro.fetch_add(personas, Ordering::SeqCst) + personas;
+        self.ocupacion_maxima.fetch_max(adentro, Ordering::SeqCst);
+        Ok(espera)
     }
 
     pub fn salir_persona(&self) {
+        self.personas_adentro.fetch_sub(1, Ordering::SeqCst);
         self.cantidad_visitantes.fetch_add(1, Ordering::SeqCst);
-        self.capacidad.release();
+        self.entrada.salir();
+    }
+
+    /// Cantidad de personas esperando para entrar al parque.
+    pub fn largo_fila_entrada(&self) -> usize {
+        self.entrada.largo()
+    }
+
+    /// Máxima cantidad de personas que hubo esperando para entrar al
+    /// parque en simultáneo.
+    pub fn obtener_fila_entrada_maxima(&self) -> usize {
+        self.entrada.largo_maximo()
+    }
+
+    /// Cantidad de personas que desistieron de entrar por el largo de
+    /// la fila.
+    pub fn obtener_desistieron(&self) -> usize {
+        self.desistieron.load(Ordering::SeqCst)
+    }
+
+    /// Máxima cantidad de personas que hubo dentro del parque en
+    /// simultáneo.
+    pub fn obtener_ocupacion_maxima(&self) -> usize {
+        self.ocupacion_maxima.load(Ordering::SeqCst)
     }
 
     pub fn obtener_cantidad_gente_que_salio_del_parque(&self) -> usize {
@@ -83,23 +402,51 @@ impl Parque {
         self.cantidad_visitantes.load(Ordering::SeqCst)
     }
 
-    pub fn cerrar(&self) {
+    /// Cierra todos los juegos y espera a que terminen sus hilos.
+    /// Devuelve la cantidad de hilos que se joinearon.
+    pub fn cerrar(&self) -> usize {
+        if let Some(pronostico) = &self.pronostico {
+            pronostico.terminar();
+        }
         self.log.write("Cerrando juegos");
         for juego in self.juegos.lock().expect("poisoned").iter() {
             juego.cerrar();
         }
 
         self.log.write("Esperando a que los juegos terminen");
+        let mut unidos = 0;
         for juego_thread in self.juegos_threads.lock().expect("poisoned").drain(..) {
             juego_thread.join().expect("cannot join thread");
+            unidos += 1;
         }
         self.log.write("Parque cerrado");
+        unidos
+    }
+
+    /// Juegos registrados en el parque.
+    pub fn obtener_juegos(&self) -> Vec<Arc<Juego>> {
+        self.juegos.lock().expect("poisoned").clone()
+    }
+
+    /// Reloj de la simulación.
+    pub fn reloj(&self) -> &Reloj {
+        &self.reloj
+    }
+
+    /// Registro de estados usado para detectar deadlocks e inanición.
+    pub fn watchdog(&self) -> &Watchdog {
+        &self.watchdog
     }
 
     pub fn guardar_dinero(&self, monto: u32) {
         self.caja.fetch_add(monto, Ordering::SeqCst);
     }
 
+    /// Devuelve una entrada que ya se había guardado en la caja.
+    pub fn devolver_dinero(&self, monto: u32) {
+        self.caja.fetch_sub(monto, Ordering::SeqCst);
+    }
+
     pub fn obtener_caja(&self) -> u32 {
         self.caja.load(Ordering::SeqCst)
     }
@@ -117,7 +464,14 @@ impl Parque {
 
 #[cfg(test)]
 mod tests {
+    use rand::SeedableRng;
+
+    use crate::arquetipo::Estrategia;
+    use crate::juego::ConfiguracionJuego;
     use crate::logger::Logger;
+    use crate::mapa::{Camino, Zona};
+    use crate::persona::{ConfiguracionPersona, Persona};
+    use crate::restricciones::Restricciones;
 
     use super::*;
 
@@ -159,7 +513,7 @@ mod tests {
     #[test]
     fn obtener_juegos_posibles_cuando_no_hay_juegos() {
         let parque = Arc::new(crear_parque(2));
-        assert!(parque.obtener_juegos_posibles(2).is_empty());
+        assert!(parque.obtener_juegos_posibles(2, &[Atributos::default()]).is_empty());
     }
 
     #[test]
@@ -176,7 +530,7 @@ mod tests {
             ))
             .collect::<Vec<Juego>>();
         parque.registrar_juegos(juegos);
-        assert_eq!(parque.obtener_juegos_posibles(30).len(), 10);
+        assert_eq!(parque.obtener_juegos_posibles(30, &[Atributos::default()]).len(), 10);
     }
 
     #[test]
@@ -193,7 +547,7 @@ mod tests {
             ))
             .collect::<Vec<Juego>>();
         parque.registrar_juegos(juegos);
-        assert_eq!(parque.obtener_juegos_posibles(10).len(), 0);
+        assert_eq!(parque.obtener_juegos_posibles(10, &[Atributos::default()]).len(), 0);
     }
 
     #[test]
@@ -221,7 +575,7 @@ mod tests {
             ))
             .collect::<Vec<Juego>>();
         parque.registrar_juegos(juegos2);
-        assert_eq!(parque.obtener_juegos_posibles(25).len(), 5);
+        assert_eq!(parque.obtener_juegos_posibles(25, &[Atributos::default()]).len(), 5);
     }
 
     #[test]
@@ -238,7 +592,7 @@ mod tests {
             ))
             .collect::<Vec<Juego>>();
         parque.registrar_juegos(juegos);
-        let juego_random = parque.elegir_juego_random(30).unwrap();
+        let juego_random = parque.elegir_juego_para_grupo(30, &[Atributos::default()], None, Lugar::Entrada, &mut crear_rng()).unwrap();
         assert!(
             parque.juegos.lock().expect("poisoned").iter().any(
                 |juego| juego.id == juego_random.id
@@ -260,7 +614,96 @@ mod tests {
             ))
             .collect::<Vec<Juego>>();
         parque.registrar_juegos(juegos);
-        assert!(!parque.elegir_juego_random(10).is_ok());
+        assert!(parque.elegir_juego_para_grupo(10, &[Atributos::default()], None, Lugar::Entrada, &mut crear_rng()).is_err());
+    }
+
+    #[test]
+    fn obtener_juegos_posibles_filtra_por_restricciones() {
+        let parque = Arc::new(crear_parque(2));
+        let altura = Restricciones { altura_minima_cm: Some(120), ..Restricciones::default() };
+        let no_accesible = Restricciones { accesible: false, ..Restricciones::default() };
+        parque.registrar_juegos(vec![
+            crear_juego(0, Arc::clone(&parque), 10, 4, 25),
+            crear_juego_desde(1, Arc::clone(&parque), ConfiguracionJuego { restricciones: altura, ..configuracion(10, 4, 25) }),
+            crear_juego_desde(2, Arc::clone(&parque), ConfiguracionJuego { restricciones: no_accesible, ..configuracion(10, 4, 25) }),
+        ]);
+        let adulto = Atributos::default();
+        let chico = Atributos { edad: 6, altura_cm: 115, movilidad_reducida: false };
+        let en_silla = Atributos { movilidad_reducida: true, ..adulto };
+        let ids = |atributos: &[Atributos]| parque.obtener_juegos_posibles(30, atributos)
+            .iter()
+            .map(|juego| juego.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids(&[adulto]), vec![0, 1, 2]);
+        assert_eq!(ids(&[chico]), vec![0, 2]);
+        assert_eq!(ids(&[en_silla]), vec![0, 1]);
+        // un grupo sólo puede subir a donde pueden subir todos
+        assert_eq!(ids(&[adulto, chico, en_silla]), vec![0]);
+        assert!(parque.elegir_juego_para_grupo(30, &[chico; 5], None, Lugar::Entrada, &mut crear_rng()).is_err());
+    }
+
+    #[test]
+    fn los_arquetipos_eligen_segun_su_perfil() {
+        let parque = Arc::new(crear_parque(2));
+        parque.registrar_juegos((1..=5)
+            .map(|intensidad| crear_juego_desde(
+                intensidad as usize - 1,
+                Arc::clone(&parque),
+                ConfiguracionJuego { intensidad, ..configuracion(10 + intensidad, 4, 25) }
+            ))
+            .collect());
+        let arquetipos = Arquetipo::predefinidos();
+        let elegidos = |arquetipo: &Arquetipo| {
+            let mut rng = crear_rng();
+            (0..200)
+                .map(|_| parque.elegir_juego_para_grupo(100, &[Atributos::default()], Some(arquetipo), Lugar::Entrada, &mut rng).unwrap().intensidad())
+                .collect::<Vec<u32>>()
+        };
+
+        // el aventurero prefiere los más intensos
+        let aventurero = elegidos(&arquetipos[0]);
+        let extremos = aventurero.iter().filter(|i| **i == 5).count();
+        assert!(extremos > 80, "{}", extremos);
+        // la familia no sube a los de intensidad mayor a 3, y sin filas
+        // prefiere los más tranquilos
+        let familia = elegidos(&arquetipos[1]);
+        assert!(familia.iter().all(|i| *i <= 3));
+        assert!(familia.iter().filter(|i| **i == 1).count() > 80);
+        // el ahorrativo siempre el más barato
+        assert!(elegidos(&arquetipos[2]).iter().all(|i| *i == 1));
+        // el casual cualquiera
+        let casual = elegidos(&arquetipos[3]);
+        assert!((1..=5).all(|i| casual.contains(&i)));
+
+        let sin_presupuesto = parque.elegir_juego_para_grupo(10, &[Atributos::default()], Some(&arquetipos[1]), Lugar::Entrada, &mut crear_rng());
+        assert!(sin_presupuesto.is_err());
+    }
+
+    #[test]
+    fn con_mapa_se_elige_el_mas_cercano_y_se_evitan_las_zonas_llenas() {
+        let caminos = [(0, 10), (1, 5), (2, 20)]
+            .iter()
+            .map(|(id, ms)| Camino { desde: Lugar::Entrada, hasta: Lugar::Juego(*id), ms: *ms })
+            .collect::<Vec<_>>();
+        let zonas = vec![Zona { nombre: "Chica".into(), capacidad: Some(1) }];
+        let mapa = Mapa::new(zonas, vec![None, Some(0), None], vec![], vec![], &caminos).unwrap();
+        let parque = Arc::new(crear_parque(2).con_mapa(Some(mapa)));
+        parque.registrar_juegos((0..3).map(|id| crear_juego(id, Arc::clone(&parque), 10, 2, 25)).collect());
+        let cercano = Arquetipo { estrategia: Estrategia::MasCercano, ..Arquetipo::neutro("x") };
+        let elegir = |desde| parque.elegir_juego_para_grupo(30, &[Atributos::default()], Some(&cercano), desde, &mut crear_rng()).unwrap().id;
+        assert_eq!(elegir(Lugar::Entrada), 1);
+        assert_eq!(elegir(Lugar::Juego(0)), 1);
+        assert_eq!(elegir(Lugar::Juego(1)), 0);
+        assert_eq!(elegir(Lugar::Juego(2)), 1);
+
+        // con la zona llena se elige el siguiente más cercano, salvo
+        // quien ya está en ella
+        assert_eq!(parque.caminar(1, Lugar::Entrada, Lugar::Juego(1), &crear_logger(), |_| {}), 5);
+        assert_eq!(elegir(Lugar::Entrada), 0);
+        assert_eq!(elegir(Lugar::Juego(1)), 0);
+        assert_eq!(parque.caminar(1, Lugar::Juego(1), Lugar::Entrada, &crear_logger(), |_| {}), 5);
+        assert_eq!(elegir(Lugar::Entrada), 1);
+        assert_eq!(parque.mapa().unwrap().ocupacion_maxima(), vec![1]);
     }
 
     #[test]
@@ -274,34 +717,121 @@ mod tests {
     fn al_salir_persona_aumenta_la_cantidad_visitantes() {
         let parque = crear_parque(2);
         assert_eq!(parque.obtener_cantidad_gente_que_salio_del_parque(), 0);
-        parque.ingresar_persona();
+        parque.ingresar_grupo(1, None).unwrap();
         parque.salir_persona();
         assert_eq!(parque.obtener_cantidad_gente_que_salio_del_parque(), 1);
     }
 
-    fn crear_parque(capacidad: usize) -> Parque {
-        crear_parque_con_semilla(capacidad, 2)
+    #[test]
+    fn la_fila_de_entrada_hace_esperar() {
+        let parque = Arc::new(crear_parque(2));
+        parque.ingresar_grupo(1, None).unwrap();
+        parque.ingresar_grupo(1, None).unwrap();
+        let esperando: Vec<_> = (0..4).map(|_| {
+            let parque = parque.clone();
+            std::thread::spawn(move || parque.ingresar_grupo(1, None))
+        }).collect();
+        while parque.largo_fila_entrada() < 4 {
+            std::thread::yield_now();
+        }
+        assert_eq!(parque.obtener_fila_entrada_maxima(), 4);
+
+        // sale una persona por vez y entra la primera de la fila
+        parque.reloj().dormir(5);
+        for quedan in (0..4).rev() {
+            parque.salir_persona();
+            while parque.largo_fila_entrada() > quedan {
+                std::thread::yield_now();
+            }
+        }
+        for hilo in esperando {
+            assert!(hilo.join().unwrap().unwrap() >= 5);
+        }
+        assert_eq!(parque.largo_fila_entrada(), 0);
+        assert_eq!(parque.obtener_desistieron(), 0);
     }
 
-    fn crear_parque_con_semilla(capacidad: usize, semilla: u64) -> Parque {
+    #[test]
+    fn quien_desiste_cuenta_como_que_salio() {
+        let parque = Arc::new(crear_parque(1));
+        parque.ingresar_grupo(1, None).unwrap();
+        let esperando = {
+            let parque = parque.clone();
+            std::thread::spawn(move || parque.ingresar_grupo(1, None))
+        };
+        while parque.largo_fila_entrada() == 0 {
+            std::thread::yield_now();
+        }
+        assert_eq!(parque.ingresar_grupo(1, Some(0)), Err(SinEntrada::FilaLarga(1)));
+        assert_eq!(parque.obtener_desistieron(), 1);
+        assert_eq!(parque.obtener_cantidad_gente_que_salio_del_parque(), 1);
+
+        parque.salir_persona();
+        assert!(esperando.join().unwrap().is_ok());
+        assert_eq!(parque.obtener_fila_entrada_maxima(), 1);
+    }
+
+    #[test]
+    fn la_tormenta_dispersa_la_fila_de_un_juego_al_aire_libre() {
+        let parque = Arc::new(crear_parque(4));
+        // sin hilos de funcionamiento nadie sube y todos quedan en la fila
+        let juego = Arc::new(crear_juego(0, Arc::clone(&parque), 10, 2, 25));
+        let esperando = (0..3)
+            .map(|id| {
+                let juego = Arc::clone(&juego);
+                std::thread::spawn(move || {
+                    let configuracion = ConfiguracionPersona { presupuesto: 100, ..Default::default() };
+                    let mut persona = [Persona::new(crear_logger(), id, configuracion, id as u64)];
+                    let subio = juego.agregar_a_la_fila(&mut persona);
+                    (subio, persona[0].estadisticas())
+                })
+            })
+            .collect::<Vec<_>>();
+        while juego.largo_fila() < 3 {
+            std::thread::yield_now();
+        }
+        juego.suspender();
+
+        for hilo in esperando {
+            let (subio, estadisticas) = hilo.join().unwrap();
+            assert!(!subio);
+            assert_eq!(estadisticas.filas_dispersadas, 1);
+            assert_eq!(estadisticas.gastado, 0);
+        }
+        let estadisticas = juego.estadisticas();
+        assert_eq!(estadisticas.cierres, 1);
+        assert_eq!(estadisticas.dispersados, 3);
+        assert_eq!(parque.obtener_caja(), 0);
+
+        // mientras está suspendido, quien llega tampoco sube
+        let mut persona = [Persona::new(crear_logger(), 3, ConfiguracionPersona { presupuesto: 100, ..Default::default() }, 3)];
+        assert!(!juego.agregar_a_la_fila(&mut persona));
+        assert_eq!(juego.estadisticas().dispersados, 4);
+    }
+
+    fn crear_parque(capacidad: usize) -> Parque {
         Parque::new(
-            crear_logger(), capacidad, semilla
+            crear_logger(), capacidad, Reloj::real()
         )
     }
 
+    fn crear_rng() -> StdRng {
+        StdRng::seed_from_u64(2)
+    }
+
     fn crear_logger() -> TaggedLogger {
         TaggedLogger::new("ADMIN", Arc::new(Logger::new_to_stdout()))
     }
 
     fn crear_juego(id: usize, parque: Arc<Parque>, precio: u32, capacidad: u32, duracion_ms: u32) -> Juego {
-        Juego::new(
-            crear_logger(),
-            id,
-            Arc::clone(&parque),
-            precio,
-            capacidad,
-            duracion_ms,
-            id as u64
-        )
+        crear_juego_desde(id, parque, configuracion(precio, capacidad, duracion_ms))
+    }
+
+    fn crear_juego_desde(id: usize, parque: Arc<Parque>, configuracion: ConfiguracionJuego) -> Juego {
+        Juego::new(crear_logger(), id, Arc::clone(&parque), configuracion, id as u64)
+    }
+
+    fn configuracion(precio: u32, capacidad: u32, duracion_ms: u32) -> ConfiguracionJuego {
+        ConfiguracionJuego { precio, capacidad, duracion_ms, ..Default::default() }
     }
 }
\ No newline at end of file
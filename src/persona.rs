@@ -3,68 +3,552 @@ use std::{
     thread::JoinHandle
 };
 
-use crate::{logger::{Logger, TaggedLogger}, parque::Parque};
+use rand::{SeedableRng, prelude::StdRng};
+
+use crate::{
+    arquetipo::Arquetipo,
+    entrada::SinEntrada,
+    espectaculo::{Espectaculo, Rechazo},
+    grupo::Grupo,
+    logger::{Logger, TaggedLogger, describir, etiqueta},
+    mapa::Lugar,
+    parque::Parque,
+    restricciones::Atributos,
+    salida::{Cansancio, MotivoSalida},
+    satisfaccion::Satisfaccion,
+};
 use crate::juego::Juego;
 
-pub fn iniciar_hilos_personas(logger: Arc<Logger>, parque: Arc<Parque>, presupuestos: &[u32]) -> Vec<JoinHandle<()>> {
+/// Parámetros de una persona que visitará el parque.
+#[derive(Debug, Clone, Default)]
+pub struct ConfiguracionPersona {
+    pub nombre: Option<String>,
+    pub presupuesto: u32,
+    /// Momento en que llega al parque, en milisegundos simulados desde
+    /// el inicio.
+    pub llegada_ms: u64,
+    /// Máxima cantidad de personas en la fila de entrada con la que
+    /// está dispuesta a esperar. Sin tolerancia espera siempre.
+    pub tolerancia_fila: Option<u32>,
+    /// Grupo con el que visita el parque, si no va sola. Los integrantes
+    /// de un grupo son personas consecutivas.
+    pub grupo: Option<usize>,
+    /// Edad, altura y movilidad, que determinan a qué juegos puede
+    /// subir.
+    pub atributos: Atributos,
+    /// Perfil de comportamiento. Sin arquetipo elige los juegos al azar
+    /// y no come.
+    pub arquetipo: Option<Arquetipo>,
+    /// Tiempo máximo que se queda en el parque, en milisegundos
+    /// simulados desde que entra.
+    pub estadia_maxima_ms: Option<u64>,
+    /// Cuándo se cansa. Sin cansancio nunca descansa.
+    pub cansancio: Option<Cansancio>,
+    /// Se va si su satisfacción baja de este valor.
+    pub satisfaccion_minima: Option<f64>,
+}
+
+/// Lanza un hilo por persona de `personas`, cada una con su id, o uno
+/// por grupo para las que van en grupo (una persona sola es un grupo
+/// de uno). Cada persona elige los juegos con su propio generador
+/// aleatorio, derivado de `semilla` y de su id, para que su recorrido no
+/// dependa del orden en que corren los hilos; cada grupo, con uno
+/// derivado del id de su grupo. Si `presupuesto_compartido` los grupos
+/// juntan el presupuesto de sus integrantes.
+pub fn iniciar_hilos_personas(logger: Arc<Logger>,
+                              parque: Arc<Parque>,
+                              personas: Vec<(usize, ConfiguracionPersona)>,
+                              presupuesto_compartido: bool,
+                              semilla: u64) -> Vec<JoinHandle<Vec<EstadisticasPersona>>> {
     let mut handles = vec![];
-    for (id, presupuesto_persona) in presupuestos.iter().enumerate() {
-        let parque_child = parque.clone();
-        let presupuesto_persona = *presupuesto_persona;
-        let mut persona = Persona::new(
-            TaggedLogger::new(&format!("PERSONA {}", id), logger.clone()),
-            id, 
-            presupuesto_persona
+    let mut integrantes: Vec<Persona> = vec![];
+    for (id, configuracion) in personas {
+        let grupo = configuracion.grupo;
+        let persona = Persona::new(
+            TaggedLogger::new(&etiqueta("PERSONA", id, configuracion.nombre.as_deref()), logger.clone()),
+            id,
+            configuracion,
+            (semilla << 32) | id as u64
         );
-        handles.push(std::thread::spawn(move || {
-            persona.visitar_parque(parque_child);
-        }));
+        if integrantes.last().is_some_and(|ultima| ultima.grupo != grupo) {
+            handles.push(lanzar_grupo(&logger, &parque, std::mem::take(&mut integrantes), presupuesto_compartido, semilla));
+        }
+        match grupo {
+            Some(_) => integrantes.push(persona),
+            None => handles.push(lanzar(&parque, Grupo::solo(persona)))
+        }
+    }
+    if !integrantes.is_empty() {
+        handles.push(lanzar_grupo(&logger, &parque, integrantes, presupuesto_compartido, semilla));
     }
     handles
 }
 
+fn lanzar_grupo(logger: &Arc<Logger>,
+                parque: &Arc<Parque>,
+                integrantes: Vec<Persona>,
+                presupuesto_compartido: bool,
+                semilla: u64) -> JoinHandle<Vec<EstadisticasPersona>> {
+    let id = integrantes[0].grupo.expect("integrante de un grupo");
+    let grupo = Grupo::new(
+        TaggedLogger::new(&etiqueta("GRUPO", id, None), logger.clone()),
+        integrantes,
+        presupuesto_compartido,
+        !((semilla << 32) | id as u64)
+    );
+    lanzar(parque, grupo)
+}
+
+fn lanzar(parque: &Arc<Parque>, mut grupo: Grupo) -> JoinHandle<Vec<EstadisticasPersona>> {
+    let parque = parque.clone();
+    std::thread::spawn(move || {
+        grupo.visitar_parque(parque);
+        grupo.estadisticas()
+    })
+}
+
+/// Estadísticas de una persona al salir del parque.
+#[derive(Debug, Clone)]
+pub struct EstadisticasPersona {
+    pub id: usize,
+    pub nombre: Option<String>,
+    pub presupuesto_inicial: u32,
+    pub gastado: u32,
+    pub juegos_jugados: u32,
+    /// Tiempo total que esperó en las filas de los juegos, en
+    /// milisegundos simulados.
+    pub espera_total_ms: u64,
+    /// Máxima espera en la fila de un juego, en milisegundos simulados.
+    pub espera_maxima_ms: u64,
+    /// Veces que la adelantaron personas que llegaron después a la fila
+    /// de un juego.
+    pub adelantamientos: u32,
+    /// Tiempo que esperó en la fila de entrada al parque, en
+    /// milisegundos simulados.
+    pub espera_entrada_ms: u64,
+    /// Si se fue sin entrar porque la fila de entrada era muy larga.
+    pub desistio: bool,
+    pub grupo: Option<usize>,
+    /// Veces que su grupo dejó partir una unidad sin completarse por no
+    /// entrar en los lugares libres.
+    pub vueltas_cedidas: u32,
+    pub atributos: Atributos,
+    pub arquetipo: Option<String>,
+    /// Lo que gastó en comida, incluido en `gastado`.
+    pub gastado_comida: u32,
+    /// Tiempo que estuvo dentro del parque, en milisegundos simulados.
+    pub estadia_ms: u64,
+    /// Desperfectos de los juegos mientras esperaba en sus filas.
+    pub desperfectos_vividos: u32,
+    /// Entradas que le devolvieron por esos desperfectos.
+    pub reembolsos: u32,
+    pub satisfaccion: Satisfaccion,
+    /// Veces que paró a descansar.
+    pub descansos: u32,
+    /// Por qué se fue del parque, si entró.
+    pub motivo_salida: Option<MotivoSalida>,
+    /// Tiempo que caminó dentro del parque, en milisegundos simulados.
+    pub caminata_ms: u64,
+    /// Funciones de espectáculos que vio.
+    pub espectaculos: u32,
+    /// Veces que fue a una función y no pudo entrar.
+    pub sin_lugar: u32,
+    /// Veces que estaba en la fila de un juego que cerró por tormenta.
+    pub filas_dispersadas: u32,
+}
+
 pub struct Persona {
     pub id: usize,
+    pub nombre: Option<String>,
+    pub grupo: Option<usize>,
     presupuesto: u32,
+    presupuesto_inicial: u32,
+    gastado: u32,
+    gastado_comida: u32,
+    juegos_jugados: u32,
+    espera_total_ms: u64,
+    espera_maxima_ms: u64,
+    adelantamientos: u32,
+    espera_entrada_ms: u64,
+    desistio: bool,
+    vueltas_cedidas: u32,
+    llegada_ms: u64,
+    tolerancia_fila: Option<u32>,
+    atributos: Atributos,
+    arquetipo: Option<Arquetipo>,
+    /// Momento en que entró al parque, en milisegundos simulados.
+    entrada_ms: u64,
+    estadia_ms: u64,
+    desperfectos_vividos: u32,
+    reembolsos: u32,
+    satisfaccion: Satisfaccion,
+    estadia_maxima_ms: Option<u64>,
+    cansancio: Option<Cansancio>,
+    /// Suma de las intensidades de los juegos desde el último descanso.
+    cansancio_acumulado: u32,
+    descansos: u32,
+    satisfaccion_minima: Option<f64>,
+    motivo_salida: Option<MotivoSalida>,
+    /// Dónde está dentro del parque.
+    ubicacion: Lugar,
+    caminata_ms: u64,
+    espectaculos: u32,
+    sin_lugar: u32,
+    filas_dispersadas: u32,
+    /// Funciones a las que ya decidió si iba, por id del espectáculo y
+    /// número de función, y espectáculos que ya vio.
+    funciones_consideradas: Vec<(usize, usize)>,
+    espectaculos_vistos: Vec<usize>,
+    /// Espectáculo del que acaba de salir, hasta que elige un juego.
+    publico_de: Option<usize>,
+    rng: StdRng,
     log: TaggedLogger
 }
 
 impl Persona {
-    pub fn new(log: TaggedLogger, id: usize, presupuesto: u32) -> Self {
+    pub fn new(log: TaggedLogger, id: usize, configuracion: ConfiguracionPersona, semilla: u64) -> Self {
         Self {
             id,
-            presupuesto,
+            nombre: configuracion.nombre,
+            grupo: configuracion.grupo,
+            presupuesto: configuracion.presupuesto,
+            presupuesto_inicial: configuracion.presupuesto,
+            gastado: 0,
+            gastado_comida: 0,
+            juegos_jugados: 0,
+            espera_total_ms: 0,
+            espera_maxima_ms: 0,
+            adelantamientos: 0,
+            espera_entrada_ms: 0,
+            desistio: false,
+            vueltas_cedidas: 0,
+            llegada_ms: configuracion.llegada_ms,
+            tolerancia_fila: configuracion.tolerancia_fila,
+            atributos: configuracion.atributos,
+            arquetipo: configuracion.arquetipo,
+            entrada_ms: 0,
+            estadia_ms: 0,
+            desperfectos_vividos: 0,
+            reembolsos: 0,
+            satisfaccion: Satisfaccion::default(),
+            estadia_maxima_ms: configuracion.estadia_maxima_ms,
+            cansancio: configuracion.cansancio,
+            cansancio_acumulado: 0,
+            descansos: 0,
+            satisfaccion_minima: configuracion.satisfaccion_minima,
+            motivo_salida: None,
+            ubicacion: Lugar::Entrada,
+            caminata_ms: 0,
+            espectaculos: 0,
+            sin_lugar: 0,
+            filas_dispersadas: 0,
+            funciones_consideradas: vec![],
+            espectaculos_vistos: vec![],
+            publico_de: None,
+            rng: StdRng::seed_from_u64(semilla),
             log,
         }
     }
 
     pub fn pagar_juego(&mut self, juego: &Juego) -> u32 {
         let presupuesto_restante = self.presupuesto - juego.precio;
-        self.log.write(&format!("Pagando juego {}. Tenía $ {} y pagué $ {}, me quedan $ {}", juego.id, self.presupuesto, juego.precio, presupuesto_restante));
+        self.log.write(&format!("Pagando juego {}. Tenía $ {} y pagué $ {}, me quedan $ {}", juego.descripcion(), self.presupuesto, juego.precio, presupuesto_restante));
         self.presupuesto = presupuesto_restante;
+        self.gastado += juego.precio;
+        self.juegos_jugados += 1;
+        self.cansancio_acumulado += juego.intensidad();
+        self.satisfaccion.juego(self.arquetipo.as_ref().map_or(1.0, |a| a.peso(juego.intensidad())));
+        self.presupuesto
+    }
+
+    /// Recupera la entrada de `juego` por un desperfecto mientras
+    /// esperaba en su fila.
+    pub fn reembolsar(&mut self, juego: &Juego) {
+        self.presupuesto += juego.precio;
+        self.gastado -= juego.precio;
+        self.reembolsos += 1;
+        self.satisfaccion.reembolso();
+        self.log.write(&format!("Me devolvieron $ {} del juego {} por el desperfecto, tengo $ {}", juego.precio, juego.descripcion(), self.presupuesto));
+    }
+
+    /// Si su arquetipo come, decide si compra comida al salir de un
+    /// juego y cuánto gasta.
+    pub fn decidir_comida(&mut self) -> Option<u32> {
+        let (presupuesto, rng) = (self.presupuesto, &mut self.rng);
+        self.arquetipo.as_ref().and_then(|a| a.comida(presupuesto, rng))
+    }
+
+    /// Compra comida por `monto`.
+    pub fn comprar_comida(&mut self, parque: &Parque, monto: u32) {
+        self.presupuesto -= monto;
+        self.gastado += monto;
+        self.gastado_comida += monto;
+        parque.guardar_dinero(monto);
+        self.log.write(&format!("Compré comida por $ {}, me quedan $ {}", monto, self.presupuesto));
+    }
+
+    /// Si ya decidió si iba a la función `funcion` del espectáculo
+    /// `espectaculo`, o ya vio ese espectáculo.
+    pub fn funcion_considerada(&self, espectaculo: usize, funcion: usize) -> bool {
+        self.funciones_consideradas.contains(&(espectaculo, funcion)) || self.espectaculos_vistos.contains(&espectaculo)
+    }
+
+    pub fn considerar_funcion(&mut self, espectaculo: usize, funcion: usize) {
+        self.funciones_consideradas.push((espectaculo, funcion));
+    }
+
+    pub fn pagar_espectaculo(&mut self, parque: &Parque, espectaculo: &Espectaculo) {
+        self.presupuesto -= espectaculo.precio;
+        self.gastado += espectaculo.precio;
+        parque.guardar_dinero(espectaculo.precio);
+        self.log.write(&format!("Pagué $ {} por el espectáculo {}, me quedan $ {}", espectaculo.precio, espectaculo.descripcion(), self.presupuesto));
+    }
+
+    /// Registra que vio una función de `espectaculo`.
+    pub fn registrar_funcion(&mut self, espectaculo: &Espectaculo) {
+        self.espectaculos += 1;
+        self.espectaculos_vistos.push(espectaculo.id);
+        self.satisfaccion.espectaculo();
+        self.publico_de = Some(espectaculo.id);
+    }
+
+    /// Registra que no pudo entrar a una función de `espectaculo`.
+    pub fn registrar_rechazo(&mut self, espectaculo: &Espectaculo, rechazo: Rechazo) {
+        self.log.write(&format!("No pude entrar al espectáculo {}: {}", espectaculo.descripcion(), rechazo));
+        self.sin_lugar += 1;
+        self.satisfaccion.sin_lugar();
+    }
+
+    /// El espectáculo del que acaba de salir, si todavía no eligió otro
+    /// juego desde entonces.
+    pub fn salir_del_publico(&mut self) -> Option<usize> {
+        self.publico_de.take()
+    }
+
+    /// Suma `ms` milisegundos simulados de caminata hasta `hasta`.
+    pub fn registrar_caminata(&mut self, ms: u64, hasta: Lugar) {
+        self.caminata_ms += ms;
+        self.ubicacion = hasta;
+    }
+
+    /// Dónde está dentro del parque.
+    pub fn ubicacion(&self) -> Lugar {
+        self.ubicacion
+    }
+
+    /// Registra el momento en que entró al parque.
+    pub fn registrar_ingreso(&mut self, parque: &Parque) {
+        self.entrada_ms = parque.reloj().ahora();
+    }
+
+    /// Registra el momento en que salió del parque y por qué.
+    pub fn registrar_salida(&mut self, parque: &Parque, motivo: MotivoSalida) {
+        self.estadia_ms = parque.reloj().ahora() - self.entrada_ms;
+        self.motivo_salida = Some(motivo);
+    }
+
+    /// Si tiene que irse aunque todavía pueda pagar algún juego: porque
+    /// cerró el parque, porque ya se quedó todo el tiempo que pensaba
+    /// quedarse o porque no la está pasando bien.
+    pub fn motivo_para_irse(&self, parque: &Parque) -> Option<MotivoSalida> {
+        if parque.cerrado() {
+            Some(MotivoSalida::Cierre)
+        } else if self.estadia_maxima_ms.is_some_and(|maximo| parque.reloj().ahora() - self.entrada_ms >= maximo) {
+            Some(MotivoSalida::Tiempo)
+        } else if self.satisfaccion_minima.is_some_and(|minima| self.satisfaccion.total() < minima) {
+            Some(MotivoSalida::Insatisfaccion)
+        } else {
+            None
+        }
+    }
+
+    /// Cómo se cansa, si está cansada.
+    pub fn cansada(&self) -> Option<Cansancio> {
+        self.cansancio.filter(|cansancio| self.cansancio_acumulado >= cansancio.maximo)
+    }
+
+    /// Vuelve a empezar después de un descanso.
+    pub fn registrar_descanso(&mut self) {
+        self.cansancio_acumulado = 0;
+        self.descansos += 1;
+    }
+
+    /// Suma `ms` milisegundos simulados de espera en la fila de un
+    /// juego, durante la cual la adelantaron `adelantamientos` personas.
+    pub fn registrar_espera(&mut self, ms: u64, adelantamientos: u32) {
+        self.espera_total_ms += ms;
+        self.espera_maxima_ms = self.espera_maxima_ms.max(ms);
+        self.adelantamientos += adelantamientos;
+        self.satisfaccion.espera(ms);
+    }
+
+    /// Suma los desperfectos que tuvo un juego mientras esperaba en su
+    /// fila.
+    pub fn registrar_desperfectos(&mut self, cantidad: u32) {
+        self.desperfectos_vividos += cantidad;
+        self.satisfaccion.desperfectos(cantidad);
+    }
+
+    /// Cuenta una unidad que su grupo dejó partir sin completarse.
+    pub fn registrar_vuelta_cedida(&mut self) {
+        self.vueltas_cedidas += 1;
+    }
+
+    /// Cuenta una fila que tuvo que dejar porque el juego cerró por
+    /// tormenta.
+    pub fn registrar_fila_dispersada(&mut self) {
+        self.filas_dispersadas += 1;
+    }
+
+    /// Registra lo que esperó para entrar al parque, o que se fue sin
+    /// entrar porque la fila era demasiado larga o su grupo no entraba.
+    pub fn registrar_entrada(&mut self, ingreso: Result<u64, SinEntrada>) {
+        match ingreso {
+            Ok(espera) => self.espera_entrada_ms = espera,
+            Err(motivo) => {
+                match motivo {
+                    SinEntrada::FilaLarga(largo) => self.log.write(&format!("La fila para entrar tiene {} personas, me voy sin entrar", largo)),
+                    SinEntrada::NoEntra => self.log.write("Mi grupo no entra en el parque, me voy sin entrar"),
+                }
+                self.desistio = true;
+                self.satisfaccion.desistio();
+            }
+        }
+    }
+
+    pub fn presupuesto(&self) -> u32 {
         self.presupuesto
     }
 
-    pub fn visitar_parque(&mut self, parque: Arc<Parque>) {
-        self.log.write("Esperando para entrar al parque");
-        parque.ingresar_persona();
-        self.log.write(&format!("Entre al parque con $ {}", self.presupuesto));
-        while self.presupuesto > 0 {
-            let juego = match parque.elegir_juego_random(self.presupuesto) {
-                Ok(juego) => juego,
-                Err(_) => break
-            };
+    /// Junta el presupuesto que les queda a `integrantes` y lo reparte
+    /// en partes iguales (las primeras reciben el resto).
+    pub fn repartir_presupuesto(integrantes: &mut [Persona]) {
+        let total: u32 = integrantes.iter().map(|p| p.presupuesto).sum();
+        let cantidad = integrantes.len() as u32;
+        for (i, persona) in integrantes.iter_mut().enumerate() {
+            persona.presupuesto = total / cantidad + u32::from((i as u32) < total % cantidad);
+        }
+    }
+
+    pub fn llegada_ms(&self) -> u64 {
+        self.llegada_ms
+    }
+
+    pub fn tolerancia_fila(&self) -> Option<u32> {
+        self.tolerancia_fila
+    }
 
-            self.jugar(juego);
+    pub fn atributos(&self) -> &Atributos {
+        &self.atributos
+    }
+
+    pub fn arquetipo(&self) -> Option<&Arquetipo> {
+        self.arquetipo.as_ref()
+    }
+
+    /// Id de la persona seguido de su nombre, si lo tiene.
+    pub fn descripcion(&self) -> String {
+        describir(self.id, self.nombre.as_deref())
+    }
+
+    pub fn estadisticas(&self) -> EstadisticasPersona {
+        let mut satisfaccion = self.satisfaccion;
+        if !self.desistio {
+            satisfaccion.salir(self.presupuesto, self.presupuesto_inicial);
         }
-        self.log.write(&format!("No me alcanza para ningun juego (me quedaron $ {})", self.presupuesto));
-        parque.salir_persona();
-        self.log.write("Me fui del parque");
+        EstadisticasPersona {
+            id: self.id,
+            nombre: self.nombre.clone(),
+            presupuesto_inicial: self.presupuesto_inicial,
+            gastado: self.gastado,
+            juegos_jugados: self.juegos_jugados,
+            espera_total_ms: self.espera_total_ms,
+            espera_maxima_ms: self.espera_maxima_ms,
+            adelantamientos: self.adelantamientos,
+            espera_entrada_ms: self.espera_entrada_ms,
+            desistio: self.desistio,
+            grupo: self.grupo,
+            vueltas_cedidas: self.vueltas_cedidas,
+            atributos: self.atributos,
+            arquetipo: self.arquetipo.as_ref().map(|a| a.nombre.clone()),
+            gastado_comida: self.gastado_comida,
+            estadia_ms: self.estadia_ms,
+            desperfectos_vividos: self.desperfectos_vividos,
+            reembolsos: self.reembolsos,
+            satisfaccion,
+            descansos: self.descansos,
+            motivo_salida: self.motivo_salida,
+            caminata_ms: self.caminata_ms,
+            espectaculos: self.espectaculos,
+            sin_lugar: self.sin_lugar,
+            filas_dispersadas: self.filas_dispersadas,
+        }
+    }
+
+    /// Informa al watchdog del parque qué está haciendo la persona.
+    pub fn reportar(&self, parque: &Parque, estado: String) {
+        parque.watchdog().estado_persona(self.log.tag(), estado, false);
     }
 
-    fn jugar(&mut self, juego: Arc<Juego>) {
-        self.log.write(&format!("Entrando a la fila del juego {}.", juego.id));
-        juego.agregar_a_la_fila(self);
-        self.log.write(&format!("Jugué al juego {} y salí.", juego.id));
+    /// Informa al watchdog del parque que la persona ya no está.
+    pub fn reportar_salida(&self, parque: &Parque, estado: &str) {
+        parque.watchdog().estado_persona(self.log.tag(), estado.into(), true);
     }
-}
\ No newline at end of file
+
+    pub fn log(&self) -> &TaggedLogger {
+        &self.log
+    }
+
+    /// Generador con el que decide, si visita el parque sola.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reloj::Reloj;
+
+    fn crear_parque() -> Parque {
+        Parque::new(TaggedLogger::new("ADMIN", Arc::new(Logger::new_to_null())), 10, Reloj::real())
+    }
+
+    fn crear_persona(configuracion: ConfiguracionPersona) -> Persona {
+        Persona::new(TaggedLogger::new("PERSONA 0", Arc::new(Logger::new_to_null())), 0, configuracion, 0)
+    }
+
+    #[test]
+    fn se_va_cuando_la_satisfaccion_baja_del_minimo() {
+        let parque = crear_parque();
+        let mut persona = crear_persona(ConfiguracionPersona {
+            presupuesto: 100,
+            satisfaccion_minima: Some(0.0),
+            ..Default::default()
+        });
+        assert_eq!(persona.motivo_para_irse(&parque), None);
+
+        // 50 ms de espera le restan 1 punto
+        persona.registrar_espera(50, 0);
+        assert_eq!(persona.motivo_para_irse(&parque), Some(MotivoSalida::Insatisfaccion));
+    }
+
+    #[test]
+    fn sin_satisfaccion_minima_no_se_va_por_insatisfaccion() {
+        let parque = crear_parque();
+        let mut persona = crear_persona(ConfiguracionPersona { presupuesto: 100, ..Default::default() });
+        persona.registrar_espera(10_000, 3);
+        assert_eq!(persona.motivo_para_irse(&parque), None);
+    }
+
+    #[test]
+    fn el_cierre_del_parque_tiene_prioridad_sobre_la_insatisfaccion() {
+        let parque = crear_parque().con_cierre(Some(0));
+        let mut persona = crear_persona(ConfiguracionPersona {
+            presupuesto: 100,
+            satisfaccion_minima: Some(0.0),
+            ..Default::default()
+        });
+        persona.registrar_espera(50, 0);
+        assert_eq!(persona.motivo_para_irse(&parque), Some(MotivoSalida::Cierre));
+    }
+}
@@ -0,0 +1,366 @@
+//! Asesor de capacidad: busca, simulando, capacidades, duraciones y
+//! cantidad de unidades de los juegos y una capacidad del parque con las que la espera promedio
+//! en la fila de cada juego no supere un objetivo.
+//!
+//! ```bash
+//! $ ./parque-oxidado planificar --espera-maxima=50 --intervalo-llegadas=200 --personas=40:60 --corridas=5
+//! ```
+//!
+//! La búsqueda es golosa: mientras algún juego supere el objetivo se
+//! prueba mejorar el de mayor espera (el cuello de botella) con cada
+//! cambio posible y se aplica el que más reduce la espera máxima,
+//! deteniéndose si ninguno la reduce. Con los juegos resueltos se busca
+//! la mayor capacidad del parque que sigue cumpliendo el objetivo.
+
+use crate::{
+    args::Args,
+    juego::ConfiguracionJuego,
+    logger::describir,
+    lote::{OpcionesLote, correr_corridas, parse_opciones},
+    teoria::{Medidas, observar},
+};
+
+const ITERACIONES_POR_DEFECTO: usize = 20;
+/// Límites de los cambios que se proponen a los juegos.
+const CAPACIDAD_MAXIMA_JUEGO: u32 = 50;
+const DURACION_MINIMA_JUEGO: u32 = 5;
+const UNIDADES_MAXIMAS_JUEGO: u32 = 10;
+/// Utilización a partir de la cual se considera que un juego no da
+/// abasto.
+const UTILIZACION_ALTA: f64 = 0.7;
+/// Fracción de la capacidad a partir de la cual una vuelta se considera
+/// llena.
+const LLENO: f64 = 0.9;
+
+#[derive(Debug, Clone)]
+pub struct OpcionesPlanificador {
+    /// Espera promedio máxima en la fila de cada juego, en milisegundos
+    /// simulados.
+    pub espera_maxima: f64,
+    /// Cantidad máxima de cambios a los juegos.
+    pub iteraciones: usize,
+    /// Correr las simulaciones en tiempo real en lugar de en modo
+    /// rápido.
+    pub tiempo_real: bool,
+    pub lote: OpcionesLote,
+}
+
+/// Separa las opciones del planificador (`--espera-maxima`,
+/// `--iteraciones`, `--tiempo-real` y las del lote) del resto de los
+/// argumentos, que describen el escenario.
+pub fn parse_opciones_planificador(argv: &[String]) -> Result<(OpcionesPlanificador, Vec<String>), String> {
+    let mut espera_maxima = None;
+    let mut iteraciones = ITERACIONES_POR_DEFECTO;
+    let mut tiempo_real = false;
+    let mut resto = vec![];
+    for arg in argv {
+        if arg == "--tiempo-real" {
+            tiempo_real = true;
+        } else if let Some(valor) = arg.strip_prefix("--espera-maxima=") {
+            espera_maxima = match valor.parse::<f64>() {
+                Ok(ms) if ms > 0.0 => Some(ms),
+                _ => return Err(format!("[--espera-maxima] '{}' no es un número positivo", valor)),
+            };
+        } else if let Some(valor) = arg.strip_prefix("--iteraciones=") {
+            iteraciones = valor.parse::<usize>()
+                .map_err(|_| format!("[--iteraciones] '{}' no es un número", valor))?;
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+    let espera_maxima = espera_maxima.ok_or("[--espera-maxima] Se debe indicar la espera máxima por juego")?;
+    let (lote, resto) = parse_opciones(&resto)?;
+    Ok((OpcionesPlanificador { espera_maxima, iteraciones, tiempo_real, lote }, resto))
+}
+
+/// Cambio a un juego: el nuevo valor de uno de sus parámetros.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cambio {
+    Capacidad(u32),
+    Duracion(u32),
+    Unidades(u32),
+}
+
+/// Cambios que se pueden probar para reducir la espera de `juego`:
+/// aumentar la capacidad a la mitad más, acortar la vuelta un cuarto y
+/// agregar una unidad, dentro de los límites.
+pub fn propuestas(juego: &ConfiguracionJuego) -> Vec<Cambio> {
+    let mut propuestas = vec![];
+    if juego.capacidad < CAPACIDAD_MAXIMA_JUEGO {
+        let capacidad = juego.capacidad + (juego.capacidad / 2).max(1);
+        propuestas.push(Cambio::Capacidad(capacidad.min(CAPACIDAD_MAXIMA_JUEGO)));
+    }
+    if juego.duracion_ms > DURACION_MINIMA_JUEGO {
+        propuestas.push(Cambio::Duracion((juego.duracion_ms * 3 / 4).max(DURACION_MINIMA_JUEGO)));
+    }
+    if juego.unidades < UNIDADES_MAXIMAS_JUEGO {
+        propuestas.push(Cambio::Unidades(juego.unidades + 1));
+    }
+    propuestas
+}
+
+fn aplicar(args: &mut Args, id: usize, cambio: Cambio) {
+    let (lista, valor) = match cambio {
+        Cambio::Capacidad(valor) => (&mut args.capacidad_juegos, valor),
+        Cambio::Duracion(valor) => (&mut args.duracion_juegos, valor),
+        Cambio::Unidades(valor) => (&mut args.unidades_juegos, valor),
+    };
+    if let Some(lista) = lista {
+        lista[id] = valor;
+    }
+}
+
+fn describir_cambio(juego: &ConfiguracionJuego, cambio: Cambio) -> String {
+    match cambio {
+        Cambio::Capacidad(valor) => format!("capacidad {} -> {}", juego.capacidad, valor),
+        Cambio::Duracion(valor) => format!("duración {} ms -> {} ms", juego.duracion_ms, valor),
+        Cambio::Unidades(valor) => format!("unidades {} -> {}", juego.unidades, valor),
+    }
+}
+
+/// Medidas de cada juego en un lote de simulaciones.
+struct Evaluacion {
+    juegos: Vec<ConfiguracionJuego>,
+    medidas: Vec<Medidas>,
+}
+
+impl Evaluacion {
+    fn espera(&self, id: usize) -> f64 {
+        self.medidas[id].espera_ms.unwrap_or_default()
+    }
+
+    /// Juego con mayor espera promedio.
+    fn cuello_de_botella(&self) -> usize {
+        (0..self.medidas.len())
+            .max_by(|a, b| self.espera(*a).total_cmp(&self.espera(*b)))
+            .unwrap_or_default()
+    }
+
+    fn espera_maxima(&self) -> f64 {
+        if self.medidas.is_empty() { 0.0 } else { self.espera(self.cuello_de_botella()) }
+    }
+
+    /// Descripción del cuello de botella y de por qué se forma la fila.
+    fn explicar(&self) -> String {
+        let id = self.cuello_de_botella();
+        let (juego, medidas) = (&self.juegos[id], &self.medidas[id]);
+        let causa = if medidas.utilizacion >= UTILIZACION_ALTA {
+            "El juego pasa la mayor parte del tiempo dando vueltas y no da abasto: aumentar su capacidad, acortar la vuelta o agregar unidades"
+        } else if medidas.personas_por_vuelta >= juego.capacidad as f64 * LLENO {
+            "Las vueltas salen llenas y quedan personas en la fila: aumentar su capacidad"
+        } else {
+            "El juego tiene capacidad ociosa, la fila se forma porque las personas llegan en grupos: aumentar la capacidad absorbe mejor los grupos"
+        };
+        format!(
+            "Juego {}: espera promedio {:.1} ms, utilización {:.2} en {} unidades, {:.1} personas por vuelta de {}. {}.",
+            describir(id, juego.nombre.as_deref()), self.espera(id), medidas.utilizacion, juego.unidades,
+            medidas.personas_por_vuelta, juego.capacidad, causa
+        )
+    }
+}
+
+fn evaluar(args: &Args, lote: &OpcionesLote, tiempo_real: bool) -> Result<Evaluacion, String> {
+    let mut args = args.clone();
+    args.rapido = !tiempo_real;
+    let juegos = args.juegos()?;
+    let resultados = correr_corridas(&args, lote.semillas(), lote.hilos)?;
+    let medidas = juegos.iter()
+        .enumerate()
+        .map(|(id, juego)| observar(&resultados, id, juego).0)
+        .collect();
+    Ok(Evaluacion { juegos, medidas })
+}
+
+/// Mayor valor en `[desde, hasta]` para el que `cumple` es verdadero,
+/// suponiendo que si un valor cumple todos los menores también.
+pub fn mayor_que_cumple(desde: u32, hasta: u32, mut cumple: impl FnMut(u32) -> Result<bool, String>) -> Result<Option<u32>, String> {
+    if desde > hasta || !cumple(desde)? {
+        return Ok(None);
+    }
+    let (mut cumple_seguro, mut no_cumple) = (desde, hasta + 1);
+    while no_cumple - cumple_seguro > 1 {
+        let medio = cumple_seguro + (no_cumple - cumple_seguro) / 2;
+        if cumple(medio)? {
+            cumple_seguro = medio;
+        } else {
+            no_cumple = medio;
+        }
+    }
+    Ok(Some(cumple_seguro))
+}
+
+/// Resultado de la planificación.
+pub struct Plan {
+    /// Escenario recomendado.
+    pub args: Args,
+    /// Descripción de cada cambio aplicado.
+    pub pasos: Vec<String>,
+    pub cumple: bool,
+    pub explicacion: String,
+}
+
+/// Busca un escenario que cumpla el objetivo partiendo de `args`.
+pub fn planificar(opciones: &OpcionesPlanificador, args: &Args) -> Result<Plan, String> {
+    let mut lote = opciones.lote.clone();
+    lote.resolver(args.semilla);
+    let objetivo = opciones.espera_maxima;
+    let mut args = args.clone();
+    let mut pasos = vec![];
+
+    let mut evaluacion = evaluar(&args, &lote, opciones.tiempo_real)?;
+    pasos.push(format!("Inicial: espera máxima {:.1} ms. {}", evaluacion.espera_maxima(), evaluacion.explicar()));
+
+    for _ in 0..opciones.iteraciones {
+        if evaluacion.espera_maxima() <= objetivo {
+            break;
+        }
+        let id = evaluacion.cuello_de_botella();
+        let juego = evaluacion.juegos[id].clone();
+        let mut mejor: Option<(Cambio, Args, Evaluacion)> = None;
+        for cambio in propuestas(&juego) {
+            let mut candidato = args.clone();
+            aplicar(&mut candidato, id, cambio);
+            let resultado = evaluar(&candidato, &lote, opciones.tiempo_real)?;
+            if mejor.as_ref().is_none_or(|(_, _, e)| resultado.espera_maxima() < e.espera_maxima()) {
+                mejor = Some((cambio, candidato, resultado));
+            }
+        }
+        // sin cambios posibles (el cuello de botella ya está en los
+        // límites) o sin mejora, se deja de modificar los juegos
+        let (cambio, candidato, resultado) = match mejor {
+            Some(mejor) if mejor.2.espera_maxima() < evaluacion.espera_maxima() => mejor,
+            _ => {
+                pasos.push(format!(
+                    "Juego {}: ningún cambio reduce la espera máxima",
+                    describir(id, juego.nombre.as_deref())
+                ));
+                break;
+            }
+        };
+        pasos.push(format!(
+            "Juego {}: {}, espera máxima {:.1} ms -> {:.1} ms",
+            describir(id, juego.nombre.as_deref()), describir_cambio(&juego, cambio),
+            evaluacion.espera_maxima(), resultado.espera_maxima()
+        ));
+        args = candidato;
+        evaluacion = resultado;
+    }
+
+    // la mayor capacidad del parque con la que se cumple el objetivo,
+    // sin bajar de la que necesita el grupo más grande para entrar
+    let capacidad_inicial = args.capacidad_parque;
+    let personas = args.presupuesto_personas.len().max(1) as u32;
+    let capacidad = mayor_que_cumple(args.mayor_grupo(), personas, |capacidad| {
+        let mut candidato = args.clone();
+        candidato.capacidad_parque = capacidad;
+        Ok(evaluar(&candidato, &lote, opciones.tiempo_real)?.espera_maxima() <= objetivo)
+    })?;
+    if let Some(capacidad) = capacidad {
+        if capacidad != capacidad_inicial {
+            pasos.push(format!(
+                "Capacidad del parque: {} -> {} (la mayor que cumple el objetivo)",
+                capacidad_inicial, capacidad
+            ));
+        }
+        args.capacidad_parque = capacidad;
+        evaluacion = evaluar(&args, &lote, opciones.tiempo_real)?;
+    }
+
+    Ok(Plan {
+        cumple: evaluacion.espera_maxima() <= objetivo,
+        explicacion: evaluacion.explicar(),
+        args,
+        pasos,
+    })
+}
+
+/// Corre el planificador sobre el escenario `args` e imprime la
+/// recomendación.
+pub fn correr(opciones: OpcionesPlanificador, args: &Args) -> Result<(), String> {
+    let plan = planificar(&opciones, args)?;
+    println!("Objetivo: espera promedio máxima de {} ms por juego", opciones.espera_maxima);
+    for paso in &plan.pasos {
+        println!("  {}", paso);
+    }
+    if plan.cumple {
+        println!("\nRecomendación (cumple el objetivo):");
+    } else {
+        println!("\nNo se pudo cumplir el objetivo, mejor escenario encontrado:");
+    }
+    println!("{}", plan.args.as_str());
+    println!("\nCuello de botella: {}", plan.explicacion);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn juego(capacidad: u32, duracion_ms: u32) -> ConfiguracionJuego {
+        ConfiguracionJuego { capacidad, duracion_ms, probabilidad_desperfecto: 0.0, tiempo_maximo_arreglo_ms: 0, ..Default::default() }
+    }
+
+    #[test]
+    fn propuestas_dentro_de_los_limites() {
+        assert_eq!(propuestas(&juego(2, 100)), vec![Cambio::Capacidad(3), Cambio::Duracion(75), Cambio::Unidades(2)]);
+        assert_eq!(propuestas(&juego(1, 6)), vec![Cambio::Capacidad(2), Cambio::Duracion(5), Cambio::Unidades(2)]);
+        let mut al_limite = juego(CAPACIDAD_MAXIMA_JUEGO, DURACION_MINIMA_JUEGO);
+        al_limite.unidades = UNIDADES_MAXIMAS_JUEGO;
+        assert_eq!(propuestas(&al_limite), vec![]);
+    }
+
+    #[test]
+    fn busqueda_binaria_de_la_capacidad() {
+        let mut evaluaciones = 0;
+        let mayor = mayor_que_cumple(1, 100, |c| {
+            evaluaciones += 1;
+            Ok(c <= 37)
+        }).unwrap();
+        assert_eq!(mayor, Some(37));
+        assert!(evaluaciones <= 8);
+        assert_eq!(mayor_que_cumple(1, 100, |_| Ok(true)).unwrap(), Some(100));
+        assert_eq!(mayor_que_cumple(1, 100, |_| Ok(false)).unwrap(), None);
+    }
+
+    #[test]
+    fn opciones() {
+        let argv: Vec<String> = ["--espera-maxima=50", "--corridas=2", "--capacidad=4"]
+            .iter().map(|a| a.to_string()).collect();
+        let (opciones, resto) = parse_opciones_planificador(&argv).unwrap();
+        assert_eq!(opciones.espera_maxima, 50.0);
+        assert_eq!(resto, vec!["--capacidad=4".to_string()]);
+        assert!(parse_opciones_planificador(&argv[1..]).is_err());
+    }
+
+    #[test]
+    fn un_objetivo_holgado_no_cambia_los_juegos() {
+        let argv: Vec<String> = ["--espera-maxima=1000000", "--semillas=1,2", "--hilos=2"]
+            .iter().map(|a| a.to_string()).collect();
+        let (opciones, _) = parse_opciones_planificador(&argv).unwrap();
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![20; 6];
+        args.capacidad_parque = 2;
+        args.resolver().unwrap();
+
+        let plan = planificar(&opciones, &args).unwrap();
+        assert!(plan.cumple);
+        assert_eq!(plan.args.capacidad_juegos, args.capacidad_juegos);
+        // con cualquier capacidad se cumple, se recomienda dejar entrar a todos
+        assert_eq!(plan.args.capacidad_parque, 6);
+    }
+
+    #[test]
+    fn la_capacidad_del_parque_no_baja_del_grupo_mas_grande() {
+        let argv: Vec<String> = ["--espera-maxima=1000000", "--corridas=1"]
+            .iter().map(|a| a.to_string()).collect();
+        let (opciones, _) = parse_opciones_planificador(&argv).unwrap();
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![20; 3];
+        args.grupos = Some(vec![3]);
+        args.capacidad_parque = 3;
+        args.resolver().unwrap();
+
+        let plan = planificar(&opciones, &args).unwrap();
+        assert_eq!(plan.args.capacidad_parque, 3);
+    }
+}
@@ -0,0 +1,59 @@
+use std::{thread, time::{Duration, Instant}};
+
+/// Factor por el que se aceleran los tiempos en modo rápido.
+pub const ESCALA_RAPIDA: u32 = 1000;
+
+/// Reloj de la simulación.
+///
+/// Todos los tiempos de la simulación (duración de las vueltas, tiempos
+/// de espera, reparaciones) se expresan en milisegundos simulados. En
+/// modo normal coinciden con milisegundos reales; en modo rápido cada
+/// milisegundo simulado dura `1 / escala` milisegundos reales.
+pub struct Reloj {
+    escala: u32,
+    inicio: Instant,
+}
+
+impl Reloj {
+    pub fn new(escala: u32) -> Self {
+        Self { escala: escala.max(1), inicio: Instant::now() }
+    }
+
+    /// Reloj en tiempo real.
+    #[cfg(test)]
+    pub fn real() -> Self {
+        Self::new(1)
+    }
+
+    /// Tiempo real que dura `ms` milisegundos simulados.
+    pub fn duracion(&self, ms: u64) -> Duration {
+        Duration::from_micros(ms * 1000 / self.escala as u64)
+    }
+
+    /// Milisegundos simulados desde que se creó el reloj.
+    pub fn ahora(&self) -> u64 {
+        (self.inicio.elapsed().as_micros() * self.escala as u128 / 1000) as u64
+    }
+
+    /// Duerme el hilo actual durante `ms` milisegundos simulados.
+    pub fn dormir(&self, ms: u64) {
+        thread::sleep(self.duracion(ms));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn en_tiempo_real_las_duraciones_no_cambian() {
+        assert_eq!(Reloj::real().duracion(25), Duration::from_millis(25));
+    }
+
+    #[test]
+    fn en_modo_rapido_las_duraciones_se_escalan() {
+        let reloj = Reloj::new(ESCALA_RAPIDA);
+        assert_eq!(reloj.duracion(5000), Duration::from_millis(5));
+        assert_eq!(reloj.duracion(25), Duration::from_micros(25));
+    }
+}
@@ -0,0 +1,183 @@
+//! Atributos de los visitantes y restricciones de los juegos.
+//!
+//! Cada persona tiene una edad, una altura y puede tener movilidad
+//! reducida; cada juego puede exigir una altura mínima o máxima, una
+//! edad mínima, o no ser accesible. Las personas sólo eligen los juegos
+//! a los que pueden subir.
+
+use std::fmt;
+
+use rand::{Rng, prelude::StdRng};
+
+/// Atributos de una persona que determinan a qué juegos puede subir.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Atributos {
+    /// Edad, en años.
+    pub edad: u32,
+    /// Altura, en centímetros.
+    pub altura_cm: u32,
+    pub movilidad_reducida: bool,
+}
+
+impl Default for Atributos {
+    fn default() -> Self {
+        Self { edad: 30, altura_cm: 170, movilidad_reducida: false }
+    }
+}
+
+/// Distribución de la que se generan los atributos de las personas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistribucionAtributos {
+    /// Rango de edades, uniforme e incluyendo ambos extremos.
+    pub edad_minima: u32,
+    pub edad_maxima: u32,
+    /// Probabilidad de que una persona tenga movilidad reducida.
+    pub probabilidad_movilidad_reducida: f64,
+}
+
+impl Default for DistribucionAtributos {
+    fn default() -> Self {
+        Self { edad_minima: 3, edad_maxima: 70, probabilidad_movilidad_reducida: 0.05 }
+    }
+}
+
+/// Desvío estándar de la altura alrededor de la media para la edad, en
+/// centímetros.
+const DESVIO_ALTURA_CM: f64 = 7.0;
+
+impl DistribucionAtributos {
+    /// Genera los atributos de una persona. La altura sigue una
+    /// distribución normal alrededor de la altura media para su edad.
+    pub fn generar(&self, rng: &mut StdRng) -> Atributos {
+        let edad = rng.gen_range(self.edad_minima..=self.edad_maxima);
+        // Box-Muller
+        let (u1, u2): (f64, f64) = (rng.gen(), rng.gen());
+        let normal = (-2.0 * (1.0 - u1).ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+        let altura = Self::altura_media_cm(edad) + normal * DESVIO_ALTURA_CM;
+        Atributos {
+            edad,
+            altura_cm: altura.clamp(60.0, 210.0).round() as u32,
+            movilidad_reducida: rng.gen_bool(self.probabilidad_movilidad_reducida),
+        }
+    }
+
+    /// Altura media aproximada para una edad: crece linealmente hasta
+    /// los 17 años y después se mantiene.
+    fn altura_media_cm(edad: u32) -> f64 {
+        80.0 + 5.5 * edad.min(17) as f64
+    }
+}
+
+/// Motivo por el que una persona no puede subir a un juego.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motivo {
+    Altura,
+    Edad,
+    Accesibilidad,
+}
+
+/// Requisitos para subir a un juego. Por defecto no hay ninguno.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restricciones {
+    pub altura_minima_cm: Option<u32>,
+    pub altura_maxima_cm: Option<u32>,
+    pub edad_minima: Option<u32>,
+    /// Si pueden subir personas con movilidad reducida.
+    pub accesible: bool,
+}
+
+impl Default for Restricciones {
+    fn default() -> Self {
+        Self { altura_minima_cm: None, altura_maxima_cm: None, edad_minima: None, accesible: true }
+    }
+}
+
+impl Restricciones {
+    /// Si el juego tiene alguna restricción.
+    pub fn hay(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// El primer motivo por el que una persona con `atributos` no puede
+    /// subir, si hay alguno.
+    pub fn motivo(&self, atributos: &Atributos) -> Option<Motivo> {
+        if self.altura_minima_cm.is_some_and(|minima| atributos.altura_cm < minima)
+            || self.altura_maxima_cm.is_some_and(|maxima| atributos.altura_cm > maxima) {
+            Some(Motivo::Altura)
+        } else if self.edad_minima.is_some_and(|minima| atributos.edad < minima) {
+            Some(Motivo::Edad)
+        } else if !self.accesible && atributos.movilidad_reducida {
+            Some(Motivo::Accesibilidad)
+        } else {
+            None
+        }
+    }
+
+    pub fn permite(&self, atributos: &Atributos) -> bool {
+        self.motivo(atributos).is_none()
+    }
+}
+
+impl fmt::Display for Restricciones {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut partes = vec![];
+        match (self.altura_minima_cm, self.altura_maxima_cm) {
+            (Some(minima), Some(maxima)) => partes.push(format!("altura entre {} y {} cm", minima, maxima)),
+            (Some(minima), None) => partes.push(format!("altura mínima {} cm", minima)),
+            (None, Some(maxima)) => partes.push(format!("altura máxima {} cm", maxima)),
+            (None, None) => {}
+        }
+        if let Some(edad) = self.edad_minima {
+            partes.push(format!("edad mínima {} años", edad));
+        }
+        if !self.accesible {
+            partes.push("no accesible".into());
+        }
+        if partes.is_empty() {
+            write!(f, "ninguna")
+        } else {
+            write!(f, "{}", partes.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn los_atributos_respetan_la_distribucion() {
+        let distribucion = DistribucionAtributos { edad_minima: 5, edad_maxima: 10, probabilidad_movilidad_reducida: 0.0 };
+        let mut rng = StdRng::seed_from_u64(42);
+        let personas: Vec<_> = (0..200).map(|_| distribucion.generar(&mut rng)).collect();
+        assert!(personas.iter().all(|p| (5..=10).contains(&p.edad)));
+        assert!(personas.iter().all(|p| !p.movilidad_reducida));
+        // entre 5 y 10 años la altura media va de 107 a 135 cm
+        let media = personas.iter().map(|p| p.altura_cm as f64).sum::<f64>() / personas.len() as f64;
+        assert!((110.0..135.0).contains(&media), "{}", media);
+
+        let mut rng = StdRng::seed_from_u64(42);
+        assert_eq!(distribucion.generar(&mut rng), personas[0]);
+    }
+
+    #[test]
+    fn motivos_para_no_subir() {
+        let restricciones = Restricciones {
+            altura_minima_cm: Some(120),
+            altura_maxima_cm: Some(195),
+            edad_minima: Some(10),
+            accesible: false,
+        };
+        let adulto = Atributos::default();
+        assert_eq!(restricciones.motivo(&adulto), None);
+        assert_eq!(restricciones.motivo(&Atributos { altura_cm: 110, ..adulto }), Some(Motivo::Altura));
+        assert_eq!(restricciones.motivo(&Atributos { altura_cm: 200, ..adulto }), Some(Motivo::Altura));
+        assert_eq!(restricciones.motivo(&Atributos { edad: 8, ..adulto }), Some(Motivo::Edad));
+        assert_eq!(restricciones.motivo(&Atributos { movilidad_reducida: true, ..adulto }), Some(Motivo::Accesibilidad));
+
+        assert!(!Restricciones::default().hay());
+        assert!(Restricciones::default().permite(&Atributos { movilidad_reducida: true, edad: 1, altura_cm: 60 }));
+        assert_eq!(restricciones.to_string(), "altura entre 120 y 195 cm, edad mínima 10 años, no accesible");
+    }
+}
@@ -0,0 +1,57 @@
+//! Por qué se van los visitantes del parque.
+//!
+//! Además de quedarse sin dinero, una persona se va cuando se le termina
+//! el tiempo que pensaba quedarse, cuando está cansada y decide no
+//! descansar más, cuando no la está pasando bien, cuando el clima la
+//! espanta o cuando cierra el parque.
+
+use std::fmt;
+
+/// Motivo por el que una persona que entró al parque se fue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MotivoSalida {
+    /// No le alcanza para ningún juego al que pueda subir.
+    Dinero,
+    /// Se quedó todo el tiempo que pensaba quedarse.
+    Tiempo,
+    /// Se cansó y prefirió irse antes que descansar.
+    Cansancio,
+    /// Su satisfacción bajó del mínimo.
+    Insatisfaccion,
+    /// Cerró el parque.
+    Cierre,
+    /// Se fue por la lluvia o la tormenta.
+    Clima,
+}
+
+impl MotivoSalida {
+    pub const TODOS: [MotivoSalida; 6] = [
+        MotivoSalida::Dinero, MotivoSalida::Tiempo, MotivoSalida::Cansancio,
+        MotivoSalida::Insatisfaccion, MotivoSalida::Cierre, MotivoSalida::Clima,
+    ];
+}
+
+impl fmt::Display for MotivoSalida {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            MotivoSalida::Dinero => "dinero",
+            MotivoSalida::Tiempo => "tiempo",
+            MotivoSalida::Cansancio => "cansancio",
+            MotivoSalida::Insatisfaccion => "insatisfacción",
+            MotivoSalida::Cierre => "cierre",
+            MotivoSalida::Clima => "clima",
+        })
+    }
+}
+
+/// Cuándo se cansa una persona y qué hace entonces. Cada juego le suma
+/// su intensidad al cansancio; al llegar a `maximo` se va con
+/// probabilidad `probabilidad_irse` o, si no, descansa `descanso_ms` y
+/// vuelve a empezar.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cansancio {
+    pub maximo: u32,
+    /// Duración de cada descanso, en milisegundos simulados.
+    pub descanso_ms: u64,
+    pub probabilidad_irse: f64,
+}
@@ -0,0 +1,137 @@
+//! Satisfacción de los visitantes.
+//!
+//! Cada persona suma puntos por los juegos a los que sube y los pierde
+//! por el tiempo en las filas, por los desperfectos que le tocó esperar
+//! y por el dinero que se lleva sin gastar; los reembolsos compensan en
+//! parte los desperfectos. Los espectáculos suman como los juegos, y
+//! quedarse sin lugar en una función resta. Quien desiste de entrar tuvo
+//! un mal día. El puntaje permite comparar escenarios por la experiencia
+//! de los visitantes y no sólo por la caja.
+
+use std::fmt;
+
+/// Puntos por cada juego, multiplicados por la preferencia del
+/// arquetipo por su intensidad (1 sin arquetipo).
+pub const PUNTOS_POR_JUEGO: f64 = 10.0;
+/// Puntos que se pierden por cada milisegundo simulado en la fila de un
+/// juego: 500 ms de espera valen lo mismo que un juego.
+pub const PUNTOS_POR_MS_DE_ESPERA: f64 = 0.02;
+/// Puntos que se pierden por cada desperfecto del juego mientras
+/// esperaba en su fila.
+pub const PUNTOS_POR_DESPERFECTO: f64 = 5.0;
+/// Puntos que se recuperan por cada entrada reembolsada.
+pub const PUNTOS_POR_REEMBOLSO: f64 = 3.0;
+/// Puntos que se pierden si se va sin gastar nada de su presupuesto,
+/// proporcionales a la fracción que no gastó.
+pub const PUNTOS_SIN_GASTAR: f64 = 10.0;
+/// Puntos por cada función de un espectáculo que vio.
+pub const PUNTOS_POR_ESPECTACULO: f64 = 15.0;
+/// Puntos que se pierden al ir a una función y no poder entrar.
+pub const PUNTOS_SIN_LUGAR: f64 = 5.0;
+/// Puntos que se pierden al desistir de entrar al parque.
+pub const PUNTOS_POR_DESISTIR: f64 = 10.0;
+
+/// Puntaje de satisfacción de una persona, separado según de dónde
+/// viene cada parte. Las pérdidas son negativas.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Satisfaccion {
+    pub juegos: f64,
+    pub espera: f64,
+    pub desperfectos: f64,
+    pub reembolsos: f64,
+    pub sin_gastar: f64,
+    pub desistio: f64,
+    pub espectaculos: f64,
+}
+
+impl Satisfaccion {
+    /// Suma un juego cuya intensidad le gusta en `preferencia` (entre 0
+    /// y 1).
+    pub fn juego(&mut self, preferencia: f64) {
+        self.juegos += PUNTOS_POR_JUEGO * preferencia;
+    }
+
+    pub fn espera(&mut self, ms: u64) {
+        self.espera -= PUNTOS_POR_MS_DE_ESPERA * ms as f64;
+    }
+
+    pub fn desperfectos(&mut self, cantidad: u32) {
+        self.desperfectos -= PUNTOS_POR_DESPERFECTO * cantidad as f64;
+    }
+
+    pub fn reembolso(&mut self) {
+        self.reembolsos += PUNTOS_POR_REEMBOLSO;
+    }
+
+    pub fn espectaculo(&mut self) {
+        self.espectaculos += PUNTOS_POR_ESPECTACULO;
+    }
+
+    pub fn sin_lugar(&mut self) {
+        self.espectaculos -= PUNTOS_SIN_LUGAR;
+    }
+
+    pub fn desistio(&mut self) {
+        self.desistio = -PUNTOS_POR_DESISTIR;
+    }
+
+    /// Descuenta lo que le quedó sin gastar de su presupuesto al salir
+    /// del parque.
+    pub fn salir(&mut self, restante: u32, presupuesto_inicial: u32) {
+        let fraccion = restante as f64 / presupuesto_inicial.max(1) as f64;
+        self.sin_gastar = 0.0 - PUNTOS_SIN_GASTAR * fraccion.min(1.0);
+    }
+
+    pub fn total(&self) -> f64 {
+        self.juegos + self.espera + self.desperfectos + self.reembolsos + self.sin_gastar + self.desistio + self.espectaculos
+    }
+}
+
+impl fmt::Display for Satisfaccion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "{:.1} (juegos {:+.1}, espera {:+.1}, desperfectos {:+.1}, reembolsos {:+.1}, sin gastar {:+.1})",
+            self.total(), self.juegos, self.espera, self.desperfectos, self.reembolsos, self.sin_gastar
+        )?;
+        if self.espectaculos != 0.0 {
+            write!(f, ", espectáculos {:+.1}", self.espectaculos)?;
+        }
+        if self.desistio != 0.0 {
+            write!(f, ", desistió {:+.1}", self.desistio)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn el_puntaje_suma_todas_las_partes() {
+        let mut satisfaccion = Satisfaccion::default();
+        satisfaccion.juego(1.0);
+        satisfaccion.juego(0.5);
+        satisfaccion.espera(250);
+        satisfaccion.desperfectos(1);
+        satisfaccion.reembolso();
+        satisfaccion.salir(10, 40);
+        assert_eq!(satisfaccion.juegos, 15.0);
+        assert_eq!(satisfaccion.total(), 15.0 - 5.0 - 5.0 + 3.0 - 2.5);
+        assert_eq!(
+            satisfaccion.to_string(),
+            "5.5 (juegos +15.0, espera -5.0, desperfectos -5.0, reembolsos +3.0, sin gastar -2.5)"
+        );
+
+        let mut desistio = Satisfaccion::default();
+        desistio.desistio();
+        assert_eq!(desistio.total(), -10.0);
+        assert!(desistio.to_string().ends_with(", desistió -10.0"));
+
+        let mut espectador = Satisfaccion::default();
+        espectador.espectaculo();
+        espectador.sin_lugar();
+        assert_eq!(espectador.total(), 10.0);
+        assert!(espectador.to_string().ends_with(", espectáculos +10.0"));
+    }
+}
@@ -0,0 +1,1174 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+    thread::sleep,
+};
+
+use crate::{
+    args::Args,
+    clima::Clima,
+    espectaculo::EstadisticasEspectaculo,
+    estadistica::cuantil,
+    juego::{EstadisticasJuego, Juego},
+    logger::{Logger, TaggedLogger, describir, etiqueta},
+    mapa::Zona,
+    parque::Parque,
+    persona::{ConfiguracionPersona, EstadisticasPersona, iniciar_hilos_personas},
+    reloj::{ESCALA_RAPIDA, Reloj},
+    restricciones::Motivo,
+    salida::MotivoSalida,
+    watchdog::Diagnostico,
+};
+
+/// Cada cuánto se informa el estado de la caja, en milisegundos
+/// simulados.
+const INTERVALO_ESTADO: u64 = 5000;
+
+/// Resultado de una simulación completa.
+#[derive(Debug)]
+pub struct Resultado {
+    pub caja: u32,
+    pub desperfectos: u32,
+    pub personas: Vec<EstadisticasPersona>,
+    pub juegos: Vec<EstadisticasJuego>,
+    /// Máxima cantidad de personas dentro del parque en simultáneo.
+    pub ocupacion_maxima_parque: usize,
+    /// Máxima cantidad de personas en la fila de entrada en simultáneo.
+    pub fila_entrada_maxima: usize,
+    /// Personas que el parque contó al salir, incluidas las que se
+    /// fueron sin entrar.
+    pub salieron: usize,
+    /// Hilos de juegos y personas creados por la simulación.
+    pub hilos_lanzados: usize,
+    /// Hilos de juegos y personas que terminaron y fueron joineados.
+    pub hilos_unidos: usize,
+    /// Tiempo hasta que salieron todas las personas, en milisegundos
+    /// simulados.
+    pub duracion_ms: u64,
+    /// Zonas del parque, con la máxima cantidad de personas que hubo en
+    /// cada una en simultáneo.
+    pub zonas: Vec<(Zona, u32)>,
+    pub espectaculos: Vec<EstadisticasEspectaculo>,
+    /// Cambios de clima durante la simulación, con el momento desde el
+    /// que rige cada uno. Vacío si no hubo pronóstico.
+    pub clima: Vec<(u64, Clima)>,
+}
+
+/// Nombres de las métricas que devuelve `Resultado::metricas`.
+pub const METRICAS: [&str; 10] = [
+    "caja", "desperfectos", "espera_promedio_ms", "viajes_por_minuto", "espera_maxima_ms", "adelantamientos",
+    "espera_entrada_ms", "desistieron", "vueltas_cedidas", "satisfaccion",
+];
+
+impl Resultado {
+    /// Tiempo promedio que esperó una persona en la fila de un juego,
+    /// en milisegundos simulados.
+    pub fn espera_promedio_ms(&self) -> f64 {
+        let viajes: u32 = self.personas.iter().map(|p| p.juegos_jugados).sum();
+        let espera: u64 = self.personas.iter().map(|p| p.espera_total_ms).sum();
+        if viajes == 0 { 0.0 } else { espera as f64 / viajes as f64 }
+    }
+
+    /// Máxima espera de una persona en la fila de un juego, en
+    /// milisegundos simulados.
+    pub fn espera_maxima_ms(&self) -> u64 {
+        self.personas.iter().map(|p| p.espera_maxima_ms).max().unwrap_or(0)
+    }
+
+    /// Veces que una persona subió a un juego antes que otra que había
+    /// llegado antes a la fila.
+    pub fn adelantamientos(&self) -> u64 {
+        self.juegos.iter().map(|j| j.adelantamientos).sum()
+    }
+
+    /// Tiempo promedio que esperaron para entrar al parque las personas
+    /// que entraron, en milisegundos simulados.
+    pub fn espera_entrada_promedio_ms(&self) -> f64 {
+        let entraron: Vec<u64> = self.personas.iter()
+            .filter(|p| !p.desistio)
+            .map(|p| p.espera_entrada_ms)
+            .collect();
+        if entraron.is_empty() {
+            0.0
+        } else {
+            entraron.iter().sum::<u64>() as f64 / entraron.len() as f64
+        }
+    }
+
+    /// Máxima espera de una persona para entrar al parque, en
+    /// milisegundos simulados.
+    pub fn espera_entrada_maxima_ms(&self) -> u64 {
+        self.personas.iter().map(|p| p.espera_entrada_ms).max().unwrap_or(0)
+    }
+
+    /// Personas que se fueron sin entrar por el largo de la fila.
+    pub fn desistieron(&self) -> usize {
+        self.personas.iter().filter(|p| p.desistio).count()
+    }
+
+    /// Vueltas que partieron sin completarse porque el grupo primero en
+    /// la fila no entraba.
+    pub fn vueltas_cedidas(&self) -> u32 {
+        self.juegos.iter().map(|j| j.vueltas_cedidas).sum()
+    }
+
+    /// Tiempo promedio que caminaron dentro del parque las personas que
+    /// entraron, en milisegundos simulados.
+    pub fn caminata_promedio_ms(&self) -> f64 {
+        let entraron: Vec<u64> = self.personas.iter()
+            .filter(|p| !p.desistio)
+            .map(|p| p.caminata_ms)
+            .collect();
+        entraron.iter().sum::<u64>() as f64 / entraron.len().max(1) as f64
+    }
+
+    /// Integrantes de cada grupo de visitantes, por id de grupo.
+    pub fn grupos(&self) -> BTreeMap<usize, Vec<&EstadisticasPersona>> {
+        let mut grupos: BTreeMap<usize, Vec<&EstadisticasPersona>> = BTreeMap::new();
+        for persona in &self.personas {
+            if let Some(grupo) = persona.grupo {
+                grupos.entry(grupo).or_default().push(persona);
+            }
+        }
+        grupos
+    }
+
+    /// Satisfacción promedio de todas las personas, incluidas las que
+    /// desistieron de entrar.
+    pub fn satisfaccion_promedio(&self) -> f64 {
+        let total: f64 = self.personas.iter().map(|p| p.satisfaccion.total()).sum();
+        total / self.personas.len().max(1) as f64
+    }
+
+    /// Distribución de la satisfacción de las personas: mínima, primer
+    /// cuartil, mediana, tercer cuartil y máxima.
+    pub fn distribucion_satisfaccion(&self) -> Option<[f64; 5]> {
+        let mut valores: Vec<f64> = self.personas.iter().map(|p| p.satisfaccion.total()).collect();
+        if valores.is_empty() {
+            return None;
+        }
+        valores.sort_by(f64::total_cmp);
+        Some([0.0, 0.25, 0.5, 0.75, 1.0].map(|q| cuantil(&valores, q)))
+    }
+
+    /// Cantidad de personas que se fueron por cada motivo.
+    pub fn salidas(&self) -> BTreeMap<MotivoSalida, usize> {
+        let mut salidas = BTreeMap::new();
+        for motivo in self.personas.iter().filter_map(|p| p.motivo_salida) {
+            *salidas.entry(motivo).or_default() += 1;
+        }
+        salidas
+    }
+
+    /// Personas de cada arquetipo, por nombre. Las que no tienen
+    /// arquetipo no aparecen.
+    pub fn por_arquetipo(&self) -> BTreeMap<&str, Vec<&EstadisticasPersona>> {
+        let mut arquetipos: BTreeMap<&str, Vec<&EstadisticasPersona>> = BTreeMap::new();
+        for persona in &self.personas {
+            if let Some(arquetipo) = &persona.arquetipo {
+                arquetipos.entry(arquetipo).or_default().push(persona);
+            }
+        }
+        arquetipos
+    }
+
+    /// Personas que entraron al parque pero no podían subir a `juego`,
+    /// por altura, por edad y por accesibilidad.
+    pub fn no_pudieron_subir(&self, juego: &EstadisticasJuego) -> (u32, u32, u32) {
+        let (mut altura, mut edad, mut accesibilidad) = (0, 0, 0);
+        for persona in self.personas.iter().filter(|p| !p.desistio) {
+            match juego.restricciones.motivo(&persona.atributos) {
+                Some(Motivo::Altura) => altura += 1,
+                Some(Motivo::Edad) => edad += 1,
+                Some(Motivo::Accesibilidad) => accesibilidad += 1,
+                None => {}
+            }
+        }
+        (altura, edad, accesibilidad)
+    }
+
+    /// Personas transportadas por todos los juegos por minuto simulado.
+    pub fn viajes_por_minuto(&self) -> f64 {
+        let viajes: u32 = self.juegos.iter().map(|j| j.personas_transportadas).sum();
+        viajes as f64 * 60_000.0 / self.duracion_ms.max(1) as f64
+    }
+
+    /// Valores de las métricas de `METRICAS`, en el mismo orden.
+    pub fn metricas(&self) -> [f64; 10] {
+        [
+            self.caja as f64,
+            self.desperfectos as f64,
+            self.espera_promedio_ms(),
+            self.viajes_por_minuto(),
+            self.espera_maxima_ms() as f64,
+            self.adelantamientos() as f64,
+            self.espera_entrada_promedio_ms(),
+            self.desistieron() as f64,
+            self.vueltas_cedidas() as f64,
+            self.satisfaccion_promedio(),
+        ]
+    }
+
+    /// Resumen legible de la simulación: totales del parque y
+    /// estadísticas de cada juego y cada persona.
+    pub fn resumen(&self) -> String {
+        let mut resultado = format!(
+            "Caja: $ {}, desperfectos: {}, ocupación máxima del parque: {}, salieron: {}/{}, hilos terminados: {}/{}\n",
+            self.caja, self.desperfectos, self.ocupacion_maxima_parque,
+            self.salieron, self.personas.len(), self.hilos_unidos, self.hilos_lanzados
+        );
+        resultado += &format!(
+            "Duración: {} ms, espera promedio en las filas: {:.1} ms, viajes por minuto: {:.1}\n",
+            self.duracion_ms, self.espera_promedio_ms(), self.viajes_por_minuto()
+        );
+        resultado += &format!(
+            "Espera máxima en una fila: {} ms, adelantamientos en las filas: {}\n",
+            self.espera_maxima_ms(), self.adelantamientos()
+        );
+        resultado += &format!(
+            "Entrada: espera promedio {:.1} ms, espera máxima {} ms, fila máxima {}, desistieron {}\n",
+            self.espera_entrada_promedio_ms(), self.espera_entrada_maxima_ms(),
+            self.fila_entrada_maxima, self.desistieron()
+        );
+        let salidas = self.salidas();
+        let salidas: Vec<String> = MotivoSalida::TODOS.iter()
+            .map(|motivo| format!("{} {}", motivo, salidas.get(motivo).copied().unwrap_or(0)))
+            .collect();
+        resultado += &format!("Se fueron por: {}\n", salidas.join(", "));
+        if let Some([minima, q1, mediana, q3, maxima]) = self.distribucion_satisfaccion() {
+            resultado += &format!(
+                "Satisfacción: promedio {:.1}, mínima {:.1}, cuartiles {:.1} / {:.1} / {:.1}, máxima {:.1}\n",
+                self.satisfaccion_promedio(), minima, q1, mediana, q3, maxima
+            );
+        }
+        if !self.clima.is_empty() {
+            let cambios: Vec<String> = self.clima.iter().map(|(ms, clima)| format!("{} desde {} ms", clima, ms)).collect();
+            resultado += &format!("Clima: {}\n", cambios.join(", "));
+        }
+        if self.personas.iter().any(|p| p.caminata_ms > 0) {
+            resultado += &format!(
+                "Caminata: promedio {:.1} ms por persona, máxima {} ms\n",
+                self.caminata_promedio_ms(), self.personas.iter().map(|p| p.caminata_ms).max().unwrap_or(0)
+            );
+        }
+        for (zona, maxima) in &self.zonas {
+            resultado += &format!(
+                "  Zona {}: ocupación máxima {}/{}\n",
+                zona.nombre, maxima, zona.capacidad.map_or("sin límite".to_string(), |c| c.to_string())
+            );
+        }
+        for espectaculo in &self.espectaculos {
+            resultado += &format!(
+                "  Espectáculo {}: {} funciones, {} asistentes, recaudó $ {}\n",
+                describir(espectaculo.id, Some(&espectaculo.nombre)), espectaculo.funciones.len(),
+                espectaculo.asistentes(), espectaculo.recaudado()
+            );
+            for funcion in &espectaculo.funciones {
+                resultado += &format!(
+                    "    Función de las {} ms: {}/{} butacas, sin lugar {}, llegaron tarde {}\n",
+                    funcion.inicio_ms, funcion.asistentes, espectaculo.capacidad, funcion.sin_lugar, funcion.tarde
+                );
+            }
+            if !espectaculo.publico_en_juegos.is_empty() {
+                let juegos: Vec<String> = espectaculo.publico_en_juegos.iter()
+                    .map(|(id, personas)| {
+                        let nombre = self.juegos.iter().find(|j| j.id == *id).and_then(|j| j.nombre.as_deref());
+                        format!("{} {}", describir(*id, nombre), personas)
+                    })
+                    .collect();
+                resultado += &format!("    Al salir el público fue a los juegos: {}\n", juegos.join(", "));
+            }
+        }
+        for juego in &self.juegos {
+            resultado += &format!(
+                "  Juego {}: {} vueltas, {} personas, ocupación máxima {}/{}, espera promedio {:.1} ms, utilización {:.2}\n",
+                describir(juego.id, juego.nombre.as_deref()), juego.vueltas, juego.personas_transportadas,
+                juego.ocupacion_maxima, juego.capacidad, juego.espera_promedio_ms(),
+                juego.utilizacion(self.duracion_ms)
+            );
+            resultado += &format!(
+                "    Espera máxima: {} ms, adelantamientos: {}, vueltas cedidas por grupos: {}\n",
+                juego.espera_maxima_ms, juego.adelantamientos, juego.vueltas_cedidas
+            );
+            if juego.reembolsos > 0 {
+                resultado += &format!("    Entradas reembolsadas por desperfectos: {}\n", juego.reembolsos);
+            }
+            if juego.cubierto && !self.clima.is_empty() {
+                resultado += "    Cubierto, no cierra por tormenta\n";
+            }
+            if juego.cierres > 0 {
+                resultado += &format!(
+                    "    Cerró {} veces por tormenta y {} personas dejaron la fila\n",
+                    juego.cierres, juego.dispersados
+                );
+            }
+            if juego.restricciones.hay() {
+                let (altura, edad, accesibilidad) = self.no_pudieron_subir(juego);
+                resultado += &format!(
+                    "    Restricciones: {}; no pudieron subir {} personas ({} por altura, {} por edad, {} por accesibilidad)\n",
+                    juego.restricciones, altura + edad + accesibilidad, altura, edad, accesibilidad
+                );
+            }
+            if juego.fila_individual {
+                resultado += &format!(
+                    "    Fila individual: {} personas, completó {} vueltas, ocupación promedio {:.2} ({:.2} sin ella)\n",
+                    juego.individuales, juego.vueltas_completadas_por_individuales,
+                    juego.ocupacion_promedio(true), juego.ocupacion_promedio(false)
+                );
+            }
+            let (carga, vuelta, descarga) = juego.ciclo_promedio_ms();
+            resultado += &format!(
+                "    Ciclo promedio: {:.1} ms (carga {:.1} ms, vuelta {:.1} ms, descarga {:.1} ms)\n",
+                carga + vuelta + descarga, carga, vuelta, descarga
+            );
+            if juego.unidades.len() > 1 {
+                for (numero, unidad) in juego.unidades.iter().enumerate() {
+                    resultado += &format!(
+                        "    Unidad {}: {} vueltas, {} personas, utilización {:.2}\n",
+                        numero, unidad.vueltas, unidad.personas_transportadas,
+                        unidad.utilizacion(juego.duracion_ms, self.duracion_ms)
+                    );
+                }
+            }
+        }
+        for (grupo, integrantes) in self.grupos() {
+            let nombres: Vec<String> = integrantes.iter()
+                .map(|p| describir(p.id, p.nombre.as_deref()))
+                .collect();
+            resultado += &format!(
+                "  Grupo {}: {} personas ({}), dejó partir {} vueltas incompletas\n",
+                grupo, integrantes.len(), nombres.join(", "), integrantes[0].vueltas_cedidas
+            );
+        }
+        for (arquetipo, personas) in self.por_arquetipo() {
+            let cantidad = personas.len() as f64;
+            let juegos: u32 = personas.iter().map(|p| p.juegos_jugados).sum();
+            let gastado: u32 = personas.iter().map(|p| p.gastado).sum();
+            let comida: u32 = personas.iter().map(|p| p.gastado_comida).sum();
+            let espera: u64 = personas.iter().map(|p| p.espera_total_ms).sum();
+            let estadia: u64 = personas.iter().map(|p| p.estadia_ms).sum();
+            let satisfaccion: f64 = personas.iter().map(|p| p.satisfaccion.total()).sum();
+            resultado += &format!(
+                "  Arquetipo {}: {} personas, {:.1} juegos, gastó $ {:.1} (comida $ {:.1}), espera {:.1} ms por juego, estadía {:.1} ms, satisfacción {:.1} en promedio, desistieron {}\n",
+                arquetipo, personas.len(), juegos as f64 / cantidad, gastado as f64 / cantidad,
+                comida as f64 / cantidad, if juegos == 0 { 0.0 } else { espera as f64 / juegos as f64 },
+                estadia as f64 / cantidad, satisfaccion / cantidad, personas.iter().filter(|p| p.desistio).count()
+            );
+        }
+        let hay_restricciones = self.juegos.iter().any(|j| j.restricciones.hay());
+        for persona in &self.personas {
+            if persona.desistio {
+                resultado += &format!(
+                    "  Persona {}: desistió de entrar por el largo de la fila, satisfacción {:.1}\n",
+                    describir(persona.id, persona.nombre.as_deref()), persona.satisfaccion.total()
+                );
+                continue;
+            }
+            resultado += &format!(
+                "  Persona {}: gastó $ {} de $ {} en {} juegos, espera máxima {} ms, la adelantaron {} veces\n",
+                describir(persona.id, persona.nombre.as_deref()), persona.gastado,
+                persona.presupuesto_inicial, persona.juegos_jugados,
+                persona.espera_maxima_ms, persona.adelantamientos
+            );
+            resultado += &format!("    Satisfacción: {}", persona.satisfaccion);
+            if persona.desperfectos_vividos > 0 {
+                resultado += &format!(
+                    ", esperó {} desperfectos y le reembolsaron {} entradas",
+                    persona.desperfectos_vividos, persona.reembolsos
+                );
+            }
+            resultado += "\n";
+            if let Some(motivo) = persona.motivo_salida {
+                resultado += &format!(
+                    "    Se fue por {} después de {} ms, descansó {} veces",
+                    motivo, persona.estadia_ms, persona.descansos
+                );
+                if persona.caminata_ms > 0 {
+                    resultado += &format!(", caminó {} ms", persona.caminata_ms);
+                }
+                resultado += "\n";
+            }
+            if let Some(arquetipo) = &persona.arquetipo {
+                resultado += &format!("    {}, gastó $ {} en comida\n", arquetipo, persona.gastado_comida);
+            }
+            if persona.filas_dispersadas > 0 {
+                resultado += &format!(
+                    "    Dejó {} filas de juegos que cerraron por tormenta\n",
+                    persona.filas_dispersadas
+                );
+            }
+            if persona.espectaculos > 0 || persona.sin_lugar > 0 {
+                resultado += &format!(
+                    "    Vio {} espectáculos y se quedó sin entrar {} veces\n",
+                    persona.espectaculos, persona.sin_lugar
+                );
+            }
+            if hay_restricciones {
+                let atributos = &persona.atributos;
+                resultado += &format!(
+                    "    {} años, {} cm{}\n",
+                    atributos.edad, atributos.altura_cm,
+                    if atributos.movilidad_reducida { ", movilidad reducida" } else { "" }
+                );
+            }
+        }
+        resultado
+    }
+}
+
+/// Corre una simulación completa con la configuración `args`,
+/// escribiendo el registro de eventos en `logger`.
+///
+/// Si el watchdog corta la simulación por falta de progreso, los hilos
+/// de juegos y personas siguen bloqueados y no se joinean: quedan vivos,
+/// con el parque, hasta que termina el proceso. Quien corre muchas
+/// simulaciones en el mismo proceso (`lote`, `barrido`) pierde esos
+/// hilos por cada corrida trabada.
+pub fn simular(args: &Args, logger: Arc<Logger>) -> Result<Resultado, String> {
+    simular_con_personas(args, args.personas().into_iter().enumerate().collect(), logger)
+}
+
+/// Como `simular`, pero con las personas dadas, cada una con su id, en
+/// lugar de las que describe `args`.
+pub fn simular_con_personas(args: &Args,
+                            personas: Vec<(usize, ConfiguracionPersona)>,
+                            logger: Arc<Logger>) -> Result<Resultado, String> {
+    // la capacidad pudo cambiar después de `resolver` (planificador,
+    // barrido), y un grupo que no entra trabaría la fila de entrada
+    let mut integrantes: BTreeMap<usize, u32> = BTreeMap::new();
+    for grupo in personas.iter().filter_map(|(_, persona)| persona.grupo) {
+        *integrantes.entry(grupo).or_default() += 1;
+    }
+    if let Some(grande) = integrantes.values().find(|cantidad| **cantidad > args.capacidad_parque) {
+        return Err(format!(
+            "Un grupo de {} personas no entra en un parque de capacidad {}", grande, args.capacidad_parque
+        ));
+    }
+    let cantidad_personas = personas.len();
+    let reloj = Reloj::new(if args.rapido { ESCALA_RAPIDA } else { 1 });
+    let log = TaggedLogger::new("ADMIN", logger.clone());
+    log.write(&format!("Iniciando simulación con: {}", args.as_str()));
+    let parque = Arc::new(Parque::new(
+        TaggedLogger::new("PARQUE", logger.clone()),
+        args.capacidad_parque as usize,
+        reloj
+    ).con_cierre(args.cierre.map(u64::from))
+        .con_mapa(args.mapa()?)
+        .con_espectaculos(args.espectaculos.clone())
+        .con_clima(args.pronostico()));
+    let semilla = args.semilla;
+    let juegos = args.juegos()?
+        .into_iter()
+        .enumerate()
+        .map(|(id, configuracion)| Juego::new(
+            TaggedLogger::new(&etiqueta("JUEGO", id, configuracion.nombre.as_deref()), logger.clone()),
+            id,
+            Arc::clone(&parque),
+            configuracion,
+            (semilla + 1 + id as u32) as u64
+        ))
+        .collect::<Vec<Juego>>();
+    let hilos_juegos: usize = juegos.iter().map(Juego::cantidad_unidades).sum();
+
+    // iniciar thread de juegos y del clima
+    parque.registrar_juegos(juegos);
+    let hilos_clima = parque.iniciar_clima();
+
+    let personas_threads = iniciar_hilos_personas(
+        Arc::clone(&logger),
+        Arc::clone(&parque),
+        personas,
+        args.presupuesto_compartido,
+        semilla as u64
+    );
+    let hilos_lanzados = hilos_juegos + hilos_clima + personas_threads.len();
+
+    esperar_salida_de_personas(args, cantidad_personas, &parque, &log, &logger)?;
+    let duracion_ms = parque.reloj().ahora();
+
+    log.write("Salieron todos, cerrando el parque");
+    let mut hilos_unidos = parque.cerrar();
+    log.write("Terminado");
+
+    log.write(&format!("Caja final: $ {}, desperfectos: {}",
+                 parque.obtener_caja(),
+                 parque.obtener_desperfectos()));
+
+    let mut personas = vec![];
+    for persona in personas_threads {
+        personas.extend(persona.join().map_err(|_| "no se pudo joinear hilo de persona")?);
+        hilos_unidos += 1;
+    }
+    logger.close();
+
+    Ok(Resultado {
+        caja: parque.obtener_caja(),
+        desperfectos: parque.obtener_desperfectos(),
+        personas,
+        juegos: parque.obtener_juegos().iter().map(|juego| juego.estadisticas()).collect(),
+        ocupacion_maxima_parque: parque.obtener_ocupacion_maxima(),
+        fila_entrada_maxima: parque.obtener_fila_entrada_maxima(),
+        salieron: parque.obtener_cantidad_gente_que_salio_del_parque(),
+        hilos_lanzados,
+        hilos_unidos,
+        duracion_ms,
+        zonas: parque.mapa().map_or(vec![], |mapa| mapa.zonas.iter().cloned().zip(mapa.ocupacion_maxima()).collect()),
+        espectaculos: parque.estadisticas_espectaculos(),
+        clima: parque.cambios_de_clima().into_iter().filter(|(ms, _)| *ms <= duracion_ms).collect(),
+    })
+}
+
+/// Espera a que salgan del parque las `personas`, informando
+/// periódicamente el estado de la caja. Si el watchdog está habilitado
+/// y la simulación deja de avanzar devuelve un error sin esperar a los
+/// hilos, que no se pueden interrumpir mientras están bloqueados.
+fn esperar_salida_de_personas(args: &Args,
+                              personas: usize,
+                              parque: &Parque,
+                              log: &TaggedLogger,
+                              logger: &Logger) -> Result<(), String> {
+    let reloj = parque.reloj();
+    let intervalo_estado = reloj.duracion(INTERVALO_ESTADO);
+    let limite_watchdog = args.watchdog.map(|ms| reloj.duracion(ms as u64));
+    let paso = match limite_watchdog {
+        Some(limite) => intervalo_estado.min(limite / 4).max(Duration::from_micros(100)),
+        None => intervalo_estado
+    };
+    let mut ultimo_estado = Instant::now();
+    while parque.obtener_cantidad_gente_que_salio_del_parque() < personas {
+        sleep(paso);
+        if let Some(limite) = limite_watchdog {
+            match parque.watchdog().verificar(limite) {
+                Diagnostico::Ok => {},
+                Diagnostico::Inanicion(personas) => {
+                    for persona in personas {
+                        log.write(&format!("Posible inanición: {}", persona));
+                    }
+                },
+                Diagnostico::SinProgreso(volcado) => {
+                    let ms = args.watchdog.unwrap_or_default();
+                    log.write(&format!(
+                        "La simulación no avanzó en {} ms. Estado de juegos y personas:\n{}",
+                        ms, volcado
+                    ));
+                    logger.close();
+                    return Err(format!("watchdog: sin progreso durante {} ms", ms));
+                }
+            }
+        }
+        if ultimo_estado.elapsed() >= intervalo_estado {
+            ultimo_estado = Instant::now();
+            log.write(&format!("Caja: $ {}, desperfectos: {}, fila de entrada: {}, desistieron: {}",
+                     parque.obtener_caja(),
+                     parque.obtener_desperfectos(),
+                     parque.largo_fila_entrada(),
+                     parque.obtener_desistieron()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clima::ConfiguracionClima, espectaculo::ConfiguracionEspectaculo};
+
+    /// Configuración base de los escenarios: modo rápido, semilla fija y
+    /// cinco juegos con los valores por defecto.
+    fn escenario(presupuestos: Vec<u32>, capacidad_parque: u32) -> Args {
+        escenario_con(presupuestos, capacidad_parque, |_| {})
+    }
+
+    /// Como `escenario`, con los cambios de `configurar` aplicados antes
+    /// de resolver los argumentos.
+    fn escenario_con(presupuestos: Vec<u32>, capacidad_parque: u32, configurar: impl FnOnce(&mut Args)) -> Args {
+        let mut args = Args::default();
+        args.presupuesto_personas = presupuestos;
+        args.capacidad_parque = capacidad_parque;
+        args.semilla = 42;
+        args.rapido = true;
+        configurar(&mut args);
+        args.resolver().expect("escenario inválido");
+        args
+    }
+
+    fn correr(args: &Args) -> Resultado {
+        correr_con_registro(args).0
+    }
+
+    /// Como `correr`, devolviendo también el registro de la simulación.
+    fn correr_con_registro(args: &Args) -> (Resultado, String) {
+        let logger = Arc::new(Logger::new_to_memory());
+        let resultado = simular(args, logger.clone())
+            .expect("la simulación falló");
+        verificar_invariantes(args, &resultado);
+        (resultado, logger.contenido())
+    }
+
+    /// Verifica lo que vale en cualquier simulación, agrupado por la
+    /// parte del parque a la que corresponde.
+    fn verificar_invariantes(args: &Args, resultado: &Resultado) {
+        verificar_entrada_y_salida(args, resultado);
+        verificar_dinero(args, resultado);
+        verificar_juegos(args, resultado);
+        verificar_grupos(resultado);
+        verificar_motivos_de_salida(args, resultado);
+        verificar_mapa(args, resultado);
+        verificar_espectaculos(resultado);
+        verificar_clima(resultado);
+
+        // todos los hilos terminaron
+        assert_eq!(resultado.hilos_unidos, resultado.hilos_lanzados);
+    }
+
+    fn verificar_entrada_y_salida(args: &Args, resultado: &Resultado) {
+        // el parque contó la salida de todas las personas, también de las
+        // que se fueron sin entrar, y de cada una una sola vez
+        assert_eq!(resultado.salieron, args.presupuesto_personas.len());
+        assert_eq!(resultado.personas.len(), args.presupuesto_personas.len());
+
+        // nunca se superó la capacidad del parque
+        assert!(resultado.ocupacion_maxima_parque <= args.capacidad_parque as usize);
+
+        // quienes desistieron no jugaron ni gastaron nada
+        for persona in resultado.personas.iter().filter(|p| p.desistio) {
+            assert_eq!((persona.juegos_jugados, persona.gastado), (0, 0));
+        }
+    }
+
+    fn verificar_dinero(args: &Args, resultado: &Resultado) {
+        // nadie pagó más que su presupuesto (o que el de su grupo, si
+        // lo comparten)
+        for persona in resultado.personas.iter().filter(|p| p.grupo.is_none() || !args.presupuesto_compartido) {
+            assert!(
+                persona.gastado <= persona.presupuesto_inicial,
+                "persona {} gastó $ {} con presupuesto $ {}",
+                persona.id, persona.gastado, persona.presupuesto_inicial
+            );
+        }
+
+        // la caja es lo que gastaron todas las personas
+        let total_gastado: u32 = resultado.personas.iter().map(|p| p.gastado).sum();
+        assert_eq!(resultado.caja, total_gastado);
+
+        // sólo se reembolsan entradas si los juegos lo hacen, y a quienes
+        // esperaron durante un desperfecto
+        for persona in &resultado.personas {
+            assert!(persona.reembolsos <= persona.juegos_jugados);
+            assert!(persona.reembolsos == 0 || (args.reembolsar_desperfectos && persona.desperfectos_vividos > 0));
+        }
+        let reembolsos: u32 = resultado.juegos.iter().map(|j| j.reembolsos).sum();
+        assert_eq!(reembolsos, resultado.personas.iter().map(|p| p.reembolsos).sum::<u32>());
+
+        // sólo compran comida las personas con arquetipo
+        for persona in &resultado.personas {
+            assert!(persona.gastado_comida <= persona.gastado);
+            assert!(persona.arquetipo.is_some() || persona.gastado_comida == 0);
+        }
+    }
+
+    fn verificar_juegos(args: &Args, resultado: &Resultado) {
+        // nunca se superó la capacidad de ningún juego
+        for juego in &resultado.juegos {
+            assert!(
+                juego.ocupacion_maxima <= juego.capacidad,
+                "juego {} tuvo {} personas con capacidad {}",
+                juego.id, juego.ocupacion_maxima, juego.capacidad
+            );
+        }
+
+        // en las filas justas nadie adelanta a nadie, y los
+        // adelantamientos de los juegos son los que sufrieron las personas
+        if !args.filas_injustas {
+            assert_eq!(resultado.adelantamientos(), 0);
+        }
+        let adelantadas: u64 = resultado.personas.iter().map(|p| p.adelantamientos as u64).sum();
+        assert_eq!(resultado.adelantamientos(), adelantadas);
+
+        // sólo suben por la fila individual los juegos que la tienen, y
+        // nunca más que las personas que transportaron
+        for juego in &resultado.juegos {
+            assert!(juego.fila_individual || juego.individuales == 0);
+            assert!(juego.individuales <= juego.personas_transportadas);
+            assert!(juego.vueltas_completadas_por_individuales <= juego.vueltas);
+        }
+
+        // los desperfectos del parque son los de sus juegos
+        assert_eq!(resultado.desperfectos, resultado.juegos.iter().map(|j| j.desperfectos).sum::<u32>());
+    }
+
+    fn verificar_grupos(resultado: &Resultado) {
+        // los grupos comparten su recorrido, y las vueltas que dejaron
+        // partir son las que registraron los juegos
+        for integrantes in resultado.grupos().values() {
+            let gastado: u32 = integrantes.iter().map(|p| p.gastado).sum();
+            let presupuesto: u32 = integrantes.iter().map(|p| p.presupuesto_inicial).sum();
+            assert!(gastado <= presupuesto);
+            assert!(integrantes.iter().all(|p| p.juegos_jugados == integrantes[0].juegos_jugados));
+            assert!(integrantes.iter().all(|p| p.vueltas_cedidas == integrantes[0].vueltas_cedidas));
+            assert!(integrantes.iter().all(|p| p.desistio == integrantes[0].desistio));
+        }
+        let cedidas: u32 = resultado.grupos().values().map(|integrantes| integrantes[0].vueltas_cedidas).sum();
+        assert_eq!(cedidas, resultado.vueltas_cedidas());
+    }
+
+    fn verificar_motivos_de_salida(args: &Args, resultado: &Resultado) {
+        // quienes entraron se fueron por algún motivo, y sólo por los
+        // que están habilitados
+        for persona in &resultado.personas {
+            assert_eq!(persona.motivo_salida.is_none(), persona.desistio);
+            match persona.motivo_salida {
+                Some(MotivoSalida::Cierre) => assert!(args.cierre.is_some()),
+                Some(MotivoSalida::Cansancio) => assert!(args.cansancio_maximo.is_some()),
+                Some(MotivoSalida::Insatisfaccion) => assert!(args.satisfaccion_minima.is_some()),
+                Some(MotivoSalida::Clima) => assert!(resultado.clima.iter().any(|(_, clima)| clima.desfavorable())),
+                _ => {}
+            }
+            assert!(args.cansancio_maximo.is_some() || persona.descansos == 0);
+        }
+    }
+
+    fn verificar_mapa(args: &Args, resultado: &Resultado) {
+        // nunca se superó la capacidad de ninguna zona, salvo un grupo
+        // más grande que la zona, que entra sólo si está vacía; y nadie
+        // caminó más de lo que estuvo en el parque
+        let grupo_maximo = resultado.grupos().values().map(Vec::len).max().unwrap_or(1) as u32;
+        for (zona, maxima) in &resultado.zonas {
+            if let Some(capacidad) = zona.capacidad {
+                assert!(*maxima <= capacidad.max(grupo_maximo), "zona {} tuvo {} personas con capacidad {}", zona.nombre, maxima, capacidad);
+            }
+        }
+        for persona in &resultado.personas {
+            assert!(persona.caminata_ms <= persona.estadia_ms);
+            assert!(args.mapa().unwrap().is_some() || persona.caminata_ms == 0);
+        }
+    }
+
+    fn verificar_espectaculos(resultado: &Resultado) {
+        // ninguna función tuvo más público que butacas, y cada asistente y
+        // cada rechazo es el de una persona
+        for espectaculo in &resultado.espectaculos {
+            for funcion in &espectaculo.funciones {
+                assert!(funcion.asistentes <= espectaculo.capacidad, "{:?}", espectaculo);
+            }
+            assert!(espectaculo.publico_en_juegos.values().sum::<u32>() <= espectaculo.asistentes());
+        }
+        let asistentes: u32 = resultado.espectaculos.iter().map(|e| e.asistentes()).sum();
+        assert_eq!(asistentes, resultado.personas.iter().map(|p| p.espectaculos).sum::<u32>());
+        let rechazos: u32 = resultado.espectaculos.iter()
+            .flat_map(|e| &e.funciones)
+            .map(|f| f.sin_lugar + f.tarde)
+            .sum();
+        assert_eq!(rechazos, resultado.personas.iter().map(|p| p.sin_lugar).sum::<u32>());
+        let recaudado: u32 = resultado.espectaculos.iter().map(|e| e.recaudado()).sum();
+        assert!(recaudado <= resultado.caja);
+    }
+
+    fn verificar_clima(resultado: &Resultado) {
+        // sólo cierran por tormenta los juegos al aire libre, y quienes
+        // dejaron la fila son las personas que la estaban haciendo
+        for juego in &resultado.juegos {
+            assert!(!juego.cubierto || juego.cierres == 0);
+            assert!(juego.cierres > 0 || juego.dispersados == 0);
+            assert!(resultado.clima.iter().any(|(_, clima)| *clima == Clima::Tormenta) || juego.cierres == 0);
+        }
+        let dispersados: u32 = resultado.juegos.iter().map(|j| j.dispersados).sum();
+        assert_eq!(dispersados, resultado.personas.iter().map(|p| p.filas_dispersadas).sum::<u32>());
+    }
+
+    #[test]
+    fn escenario_por_defecto() {
+        let resultado = correr(&escenario(vec![40; 5], 10));
+        assert_eq!(resultado.caja, 200);
+    }
+
+    #[test]
+    fn mas_personas_que_capacidad_del_parque() {
+        let resultado = correr(&escenario(vec![20; 12], 3));
+        assert_eq!(resultado.ocupacion_maxima_parque, 3);
+    }
+
+    #[test]
+    fn nadie_puede_pagar_ningun_juego() {
+        let resultado = correr(&escenario(vec![5; 4], 10));
+        assert_eq!(resultado.caja, 0);
+        assert!(resultado.personas.iter().all(|p| p.juegos_jugados == 0));
+    }
+
+    #[test]
+    fn juegos_de_a_una_persona() {
+        let mut args = escenario(vec![30, 15, 25, 40], 10);
+        args.costo_juegos = Some(vec![5, 10]);
+        args.capacidad_juegos = Some(vec![1, 1]);
+        args.duracion_juegos = Some(vec![10, 40]);
+        let resultado = correr(&args);
+        assert!(resultado.juegos.iter().all(|j| j.ocupacion_maxima <= 1));
+    }
+
+    #[test]
+    fn presupuestos_que_no_son_multiplo_del_precio() {
+        let mut args = escenario(vec![7, 33, 18, 1, 50], 2);
+        args.costo_juegos = Some(vec![3, 8, 11]);
+        args.capacidad_juegos = Some(vec![2, 3, 4]);
+        args.duracion_juegos = Some(vec![25, 25, 25]);
+        let resultado = correr(&args);
+        let transportadas: u32 = resultado.juegos.iter().map(|j| j.personas_transportadas).sum();
+        let jugados: u32 = resultado.personas.iter().map(|p| p.juegos_jugados).sum();
+        assert_eq!(transportadas, jugados);
+    }
+
+    #[test]
+    fn los_nombres_se_usan_en_el_registro_y_el_resumen() {
+        let args = escenario_con(vec![20, 20], 10, |args| {
+            args.nombres_personas = Some(vec!["Ana".into(), String::new()]);
+            let mut nombres_juegos = vec![String::new(); 5];
+            nombres_juegos[0] = "Carrusel".into();
+            args.nombres_juegos = Some(nombres_juegos);
+        });
+        let (resultado, log) = correr_con_registro(&args);
+
+        let etiquetada = |tag: &str, mensaje: &str| log.contains(&format!("| {:>12}| {}", tag, mensaje));
+        assert!(etiquetada("PERSONA 0 (Ana)", "Entre al parque"), "{}", log);
+        assert!(etiquetada("PERSONA 1", "Entre al parque"), "{}", log);
+        assert!(etiquetada("JUEGO 0 (Carrusel)", ""), "{}", log);
+        assert!(etiquetada("JUEGO 1", ""), "{}", log);
+
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("Juego 0 (Carrusel):"), "{}", resumen);
+        assert!(resumen.contains("Juego 1:"), "{}", resumen);
+        assert!(resumen.contains("Persona 0 (Ana):"), "{}", resumen);
+    }
+
+    #[test]
+    fn unidades_que_comparten_la_fila() {
+        let args = escenario_con(vec![40; 12], 12, |args| {
+            args.capacidad_juegos = Some(vec![2, 2]);
+            args.unidades_juegos = Some(vec![3, 1]);
+        });
+        let (resultado, log) = correr_con_registro(&args);
+
+        let juego = &resultado.juegos[0];
+        assert_eq!(juego.unidades.len(), 3);
+        assert_eq!(resultado.juegos[1].unidades.len(), 1);
+        let transportadas: u32 = juego.unidades.iter().map(|u| u.personas_transportadas).sum();
+        assert_eq!(transportadas, juego.personas_transportadas);
+        assert_eq!(resultado.hilos_lanzados, 4 + 12);
+        assert!(log.contains("JUEGO 0.2"));
+        assert!(resultado.resumen().contains("    Unidad 2:"));
+    }
+
+    #[test]
+    fn desisten_si_la_fila_de_entrada_es_larga() {
+        let mut args = escenario(vec![40; 20], 1);
+        args.tolerancia_personas = Some(vec![Some(0); 20]);
+        let resultado = correr(&args);
+        // llegan todas juntas a un parque de una persona: entra una,
+        // espera otra y el resto se va
+        assert!(resultado.desistieron() > 0);
+        assert!(resultado.desistieron() < 20);
+        assert!(resultado.fila_entrada_maxima >= 1);
+        assert!(resultado.resumen().contains("desistió de entrar"));
+    }
+
+    #[test]
+    fn la_fila_de_entrada_hace_esperar() {
+        // cuántas hacen fila a la vez depende de cuándo arranca cada
+        // hilo; el protocolo de la fila se prueba en `parque` y `entrada`
+        let resultado = correr(&escenario(vec![40; 6], 2));
+        assert_eq!(resultado.desistieron(), 0);
+        assert!(resultado.fila_entrada_maxima <= 4, "{}", resultado.fila_entrada_maxima);
+        let entrada = format!("fila máxima {}, desistieron 0", resultado.fila_entrada_maxima);
+        assert!(resultado.resumen().contains(&entrada), "{}", resultado.resumen());
+    }
+
+    #[test]
+    fn grupos_que_juegan_juntos() {
+        let args = escenario_con(vec![40; 12], 12, |args| {
+            args.capacidad_juegos = Some(vec![4, 3]);
+            args.grupos = Some(vec![3, 3, 1, 2]);
+        });
+        let (resultado, log) = correr_con_registro(&args);
+
+        assert_eq!(resultado.grupos().len(), 3);
+        assert!(resultado.personas.iter().filter(|p| p.grupo.is_some()).all(|p| p.juegos_jugados == 4));
+        // dos juegos, tres grupos y cuatro personas solas
+        assert_eq!(resultado.hilos_lanzados, 2 + 3 + 4);
+        assert!(log.contains("GRUPO 1"));
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("  Grupo 0: 3 personas (0, 1, 2)"), "{}", resumen);
+    }
+
+    #[test]
+    fn la_fila_individual_completa_las_vueltas() {
+        let args = escenario_con(vec![40; 12], 12, |args| {
+            args.capacidad_juegos = Some(vec![3, 3]);
+            args.grupos = Some(vec![2, 1, 2, 1, 2, 1, 2]);
+            args.filas_individuales = true;
+        });
+        let resultado = correr(&args);
+
+        for juego in &resultado.juegos {
+            assert!(juego.ocupacion_promedio(true) >= juego.ocupacion_promedio(false));
+        }
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("    Fila individual: "), "{}", resumen);
+    }
+
+    #[test]
+    fn los_arquetipos_se_comportan_distinto() {
+        let args = escenario_con(vec![100; 30], 30, |args| {
+            args.intensidad_juegos = Some(vec![1, 2, 3, 4, 5]);
+            args.mezcla_visitantes = Some(vec![("aventurero".into(), 1), ("familia".into(), 1), ("ahorrativo".into(), 1)]);
+        });
+        let resultado = correr(&args);
+
+        let arquetipos = resultado.por_arquetipo();
+        assert_eq!(arquetipos.values().map(Vec::len).sum::<usize>(), 30);
+        // los ahorrativos no compran comida, las familias sí
+        assert!(arquetipos["ahorrativo"].iter().all(|p| p.gastado_comida == 0));
+        assert!(arquetipos["familia"].iter().any(|p| p.gastado_comida > 0));
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("  Arquetipo aventurero: "), "{}", resumen);
+    }
+
+    #[test]
+    fn se_van_por_distintos_motivos() {
+        let mut args = escenario(vec![1000; 10], 10);
+        args.cansancio_maximo = Some(6);
+        args.probabilidad_irse_cansado = 0.5;
+        let resultado = correr(&args);
+        let salidas = resultado.salidas();
+        // con tanto dinero sólo se van por cansancio
+        assert_eq!(salidas.get(&MotivoSalida::Cansancio), Some(&10));
+        assert!(resultado.personas.iter().any(|p| p.descansos > 0));
+
+        let mut args = escenario(vec![1000; 10], 10);
+        args.estadia_maxima = Some(300);
+        let resultado = correr(&args);
+        assert_eq!(resultado.salidas().get(&MotivoSalida::Tiempo), Some(&10));
+        assert!(resultado.personas.iter().all(|p| p.estadia_ms >= 300));
+
+        let mut args = escenario(vec![1000; 10], 10);
+        args.cierre = Some(500);
+        let resultado = correr(&args);
+        assert_eq!(resultado.salidas().get(&MotivoSalida::Cierre), Some(&10));
+        assert!(resultado.resumen().contains("Se fueron por: dinero 0, tiempo 0, cansancio 0, insatisfacción 0, cierre 10"));
+    }
+
+    #[test]
+    fn los_desperfectos_bajan_la_satisfaccion() {
+        let mut args = escenario_con(vec![100; 8], 8, |args| {
+            args.costo_juegos = Some(vec![10]);
+            args.capacidad_juegos = Some(vec![2]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.5]);
+            args.tiempo_arreglo_juegos = Some(vec![20]);
+        });
+        let sin_reembolso = correr(&args);
+        assert!(sin_reembolso.personas.iter().any(|p| p.desperfectos_vividos > 0));
+        assert!(sin_reembolso.personas.iter().all(|p| p.reembolsos == 0));
+
+        args.reembolsar_desperfectos = true;
+        let con_reembolso = correr(&args);
+        assert!(con_reembolso.juegos[0].reembolsos > 0);
+        for persona in &con_reembolso.personas {
+            assert!(persona.satisfaccion.reembolsos > 0.0 || persona.desperfectos_vividos == 0);
+        }
+        let [minima, _, mediana, _, maxima] = con_reembolso.distribucion_satisfaccion().unwrap();
+        assert!(minima <= mediana && mediana <= maxima);
+        assert!(con_reembolso.resumen().contains("Entradas reembolsadas por desperfectos: "));
+    }
+
+    #[test]
+    fn las_restricciones_dejan_afuera_a_quienes_no_las_cumplen() {
+        let args = escenario_con(vec![100; 30], 30, |args| {
+            args.capacidad_juegos = Some(vec![4, 4, 4]);
+            args.edad_minima_juegos = Some(vec![None, Some(200), None]);
+            args.altura_minima_juegos = Some(vec![None, None, Some(130)]);
+            args.accesible_juegos = Some(vec![false, true, true]);
+            args.atributos_personas.probabilidad_movilidad_reducida = 0.5;
+        });
+        let resultado = correr(&args);
+
+        // nadie cumple la edad mínima del juego 1
+        assert_eq!(resultado.juegos[1].personas_transportadas, 0);
+        assert_eq!(resultado.no_pudieron_subir(&resultado.juegos[1]), (0, 30, 0));
+        let (altura, edad, _) = resultado.no_pudieron_subir(&resultado.juegos[2]);
+        assert!(altura > 0 && edad == 0);
+        let (_, _, accesibilidad) = resultado.no_pudieron_subir(&resultado.juegos[0]);
+        assert_eq!(accesibilidad as usize, resultado.personas.iter().filter(|p| p.atributos.movilidad_reducida).count());
+
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("    Restricciones: edad mínima 200 años; no pudieron subir 30 personas"), "{}", resumen);
+        assert!(resumen.contains("    Restricciones: no accesible;"), "{}", resumen);
+    }
+
+    #[test]
+    fn las_personas_caminan_entre_las_zonas() {
+        let args = escenario_con(vec![30; 10], 10, |args| {
+            args.capacidad_juegos = Some(vec![2, 2]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0, 0.0]);
+            args.grupos = Some(vec![2]);
+            args.zonas = vec![
+                Zona { nombre: "Norte".into(), capacidad: Some(3) },
+                Zona { nombre: "Sur".into(), capacidad: None },
+            ];
+            args.zona_juegos = Some(vec![Some(0), Some(1)]);
+            args.caminata = Some(50);
+        });
+        let (resultado, log) = correr_con_registro(&args);
+
+        // caminan al menos hasta el primer juego y de vuelta a la salida
+        for persona in resultado.personas.iter().filter(|p| p.juegos_jugados > 0) {
+            assert!(persona.caminata_ms >= 100, "{:?}", persona);
+        }
+        assert!(resultado.caminata_promedio_ms() >= 100.0);
+        let (norte, maxima) = &resultado.zonas[0];
+        assert_eq!(norte.nombre, "Norte");
+        assert!((1..=3).contains(maxima), "{}", maxima);
+        assert!(log.contains("Camino 50 ms hasta la salida"));
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("  Zona Norte: ocupación máxima "), "{}", resumen);
+        assert!(resumen.contains("/sin límite"), "{}", resumen);
+        assert!(resumen.contains(", caminó "), "{}", resumen);
+    }
+
+    #[test]
+    fn el_publico_de_los_espectaculos_va_a_los_juegos_cercanos() {
+        let args = escenario_con(vec![30; 20], 20, |args| {
+            args.capacidad_juegos = Some(vec![4, 4, 4]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0; 3]);
+            args.zonas = vec![Zona { nombre: "Norte".into(), capacidad: None }];
+            args.zona_juegos = Some(vec![None, Some(0), None]);
+            args.caminata = Some(50);
+            args.espectaculos = vec![ConfiguracionEspectaculo {
+                nombre: "Magia".into(),
+                precio: 5,
+                capacidad: 5,
+                duracion_ms: 1000,
+                horarios_ms: vec![5000, 20000],
+                anticipacion_ms: 30000,
+                interes: 1.0,
+                zona: Some(0),
+            }];
+        });
+        let resultado = correr(&args);
+
+        // cuántos llegan a cada función depende de cómo corren los hilos,
+        // pero nunca se pasa de las butacas, cada asistente pagó su
+        // entrada y nadie ve el espectáculo dos veces
+        let magia = &resultado.espectaculos[0];
+        assert!(magia.funciones.iter().all(|f| f.asistentes <= 5), "{:?}", magia);
+        let asistentes: u32 = magia.funciones.iter().map(|f| f.asistentes).sum();
+        assert_eq!(asistentes, resultado.personas.iter().map(|p| p.espectaculos).sum::<u32>());
+        assert_eq!(magia.recaudado(), 5 * asistentes);
+        let sin_lugar: u32 = magia.funciones.iter().map(|f| f.sin_lugar + f.tarde).sum();
+        assert_eq!(sin_lugar, resultado.personas.iter().map(|p| p.sin_lugar).sum::<u32>());
+        assert!(resultado.personas.iter().all(|p| p.espectaculos <= 1));
+        // al salir eligen juegos de la zona del espectáculo
+        assert!(magia.publico_en_juegos.keys().all(|id| *id == 1), "{:?}", magia);
+        let resumen = resultado.resumen();
+        let linea = format!("  Espectáculo 0 (Magia): 2 funciones, {} asistentes, recaudó $ {}", asistentes, 5 * asistentes);
+        assert!(resumen.contains(&linea), "{}", resumen);
+        let funcion = format!("    Función de las 5000 ms: {}/5 butacas", magia.funciones[0].asistentes);
+        assert!(resumen.contains(&funcion), "{}", resumen);
+    }
+
+    #[test]
+    fn las_tormentas_cierran_los_juegos_al_aire_libre() {
+        let args = escenario_con(vec![100; 10], 10, |args| {
+            args.capacidad_juegos = Some(vec![2, 2]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0; 2]);
+            args.cubierto_juegos = Some(vec![false, true]);
+            // la tormenta desde el inicio cierra el juego al aire libre
+            // antes de que llegue nadie, sin depender de cuánto tardan los
+            // hilos
+            args.clima = Some(ConfiguracionClima::Fijo(vec![(0, Clima::Tormenta)]));
+        });
+        let (resultado, log) = correr_con_registro(&args);
+
+        assert_eq!(resultado.clima, vec![(0, Clima::Tormenta)]);
+        assert_eq!(resultado.juegos[0].cierres, 1);
+        assert_eq!(resultado.juegos[0].personas_transportadas, 0);
+        assert_eq!(resultado.juegos[1].cierres, 0);
+        assert!(resultado.juegos[1].personas_transportadas > 0);
+        assert!(log.contains("Cambia el clima: tormenta"));
+        assert!(log.contains("Cerrado por tormenta, se dispersa la fila"));
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("Clima: tormenta desde 0 ms"), "{}", resumen);
+        assert!(resumen.contains("    Cerró 1 veces por tormenta y "), "{}", resumen);
+        assert!(resumen.contains("    Cubierto, no cierra por tormenta"), "{}", resumen);
+    }
+
+    #[test]
+    fn con_tormenta_y_sin_juegos_cubiertos_todos_se_van() {
+        let mut args = escenario(vec![50; 10], 10);
+        args.clima = Some(ConfiguracionClima::Fijo(vec![(0, Clima::Tormenta)]));
+        let resultado = correr(&args);
+
+        assert_eq!(resultado.caja, 0);
+        assert!(resultado.juegos.iter().all(|juego| juego.cierres == 1));
+        assert!(resultado.personas.iter().all(|p| p.motivo_salida == Some(MotivoSalida::Clima)));
+    }
+
+    #[test]
+    fn los_grupos_que_no_entran_no_juegan() {
+        let args = escenario_con(vec![40; 3], 3, |args| {
+            args.costo_juegos = Some(vec![10]);
+            args.capacidad_juegos = Some(vec![2]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0]);
+            args.grupos = Some(vec![3]);
+        });
+        let resultado = correr(&args);
+        assert!(resultado.personas.iter().all(|p| p.juegos_jugados == 0));
+    }
+
+    #[test]
+    fn presupuesto_compartido_o_separado() {
+        let mut args = escenario_con(vec![10, 30], 2, |args| {
+            args.costo_juegos = Some(vec![10]);
+            args.capacidad_juegos = Some(vec![2]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0]);
+            args.grupos = Some(vec![2]);
+        });
+        let gastado: Vec<u32> = correr(&args).personas.iter().map(|p| p.gastado).collect();
+        assert_eq!(gastado, vec![10, 10]);
+
+        args.presupuesto_compartido = true;
+        let gastado: Vec<u32> = correr(&args).personas.iter().map(|p| p.gastado).collect();
+        assert_eq!(gastado, vec![20, 20]);
+    }
+
+    #[test]
+    fn filas_injustas() {
+        let mut args = escenario(vec![40; 12], 12);
+        args.filas_injustas = true;
+        let resultado = correr(&args);
+        let resumen = resultado.resumen();
+        assert!(resumen.contains("adelantamientos en las filas:"), "{}", resumen);
+        assert!(resultado.espera_maxima_ms() as f64 >= resultado.espera_promedio_ms());
+    }
+
+    #[test]
+    fn la_carga_y_la_descarga_alargan_el_ciclo() {
+        let args = escenario_con(vec![40; 6], 10, |args| {
+            args.capacidad_juegos = Some(vec![3]);
+            args.duracion_juegos = Some(vec![50]);
+            args.carga_juegos = Some(vec![20]);
+            args.descarga_juegos = Some(vec![10]);
+            args.probabilidad_desperfecto_juegos = Some(vec![0.0]);
+        });
+        let resultado = correr(&args);
+
+        let juego = &resultado.juegos[0];
+        assert!(juego.vueltas > 0);
+        // cada vuelta lleva a lo sumo 3 personas: carga de hasta 60 ms y
+        // descarga de hasta 30 ms, además de la vuelta de 50 ms
+        let (carga, vuelta, descarga) = juego.ciclo_promedio_ms();
+        let personas = juego.personas_transportadas as f64 / juego.vueltas as f64;
+        assert!(carga >= 20.0 * personas, "{}", carga);
+        assert!(vuelta >= 50.0, "{}", vuelta);
+        assert!(descarga >= 10.0 * personas, "{}", descarga);
+        assert!(resultado.resumen().contains("Ciclo promedio"));
+    }
+}
@@ -0,0 +1,74 @@
+//! Primitivas de sincronización usadas por el protocolo de los juegos.
+//!
+//! Con `--cfg loom` se usan las versiones de `loom`, que permiten
+//! explorar en los tests las intercalaciones de los hilos (acotadas a
+//! unos pocos cambios de hilo forzados). En cualquier otro caso se usan las de la biblioteca estándar.
+//! Como `loom` no provee semáforos ni barreras, ambos se implementan
+//! acá sobre `Mutex` y `Condvar`.
+
+#[cfg(loom)]
+pub use loom::sync::{Condvar, Mutex, RwLock};
+#[cfg(not(loom))]
+pub use std::sync::{Condvar, Mutex, RwLock};
+
+/// Semáforo contador.
+pub struct Semaforo {
+    permisos: Mutex<isize>,
+    cv: Condvar,
+}
+
+impl Semaforo {
+    pub fn new(permisos: isize) -> Self {
+        Self {
+            permisos: Mutex::new(permisos),
+            cv: Condvar::new(),
+        }
+    }
+
+    /// Toma un permiso, bloqueando hasta que haya uno disponible.
+    pub fn acquire(&self) {
+        let mut permisos = self.permisos.lock().expect("poisoned");
+        while *permisos <= 0 {
+            permisos = self.cv.wait(permisos).expect("poisoned");
+        }
+        *permisos -= 1;
+    }
+
+    /// Devuelve un permiso, despertando a un hilo que lo esté esperando.
+    pub fn release(&self) {
+        *self.permisos.lock().expect("poisoned") += 1;
+        self.cv.notify_one();
+    }
+}
+
+/// Barrera para `n` hilos, reutilizable.
+pub struct Barrera {
+    n: usize,
+    estado: Mutex<(usize, u64)>, // (hilos esperando, generación)
+    cv: Condvar,
+}
+
+impl Barrera {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            estado: Mutex::new((0, 0)),
+            cv: Condvar::new(),
+        }
+    }
+
+    /// Bloquea hasta que `n` hilos hayan llamado a `wait`.
+    pub fn wait(&self) {
+        let mut estado = self.estado.lock().expect("poisoned");
+        let generacion = estado.1;
+        estado.0 += 1;
+        if estado.0 >= self.n {
+            *estado = (0, generacion + 1);
+            self.cv.notify_all();
+            return;
+        }
+        while estado.1 == generacion {
+            estado = self.cv.wait(estado).expect("poisoned");
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! Temporada: simula varios días seguidos del mismo parque.
+//!
+//! ```bash
+//! $ ./parque-oxidado temporada --dias=5 --regreso=0.6 --mantenimiento=300 --personas=30:20:80 --cierre=20000
+//! ```
+//!
+//! Cada día el parque abre, recibe a sus visitantes y cierra. El primer
+//! día vienen todas las personas del escenario; los siguientes, cada una
+//! vuelve con una probabilidad y un presupuesto que crecen con la
+//! satisfacción de su última visita. Los juegos se desgastan con las
+//! vueltas, y cuanto más desgastados más probable es un desperfecto. Por
+//! la noche se hace el mantenimiento de los que llegaron a un umbral de
+//! vueltas, que al otro día abren como nuevos.
+//!
+//! Los días se corren en modo rápido y sin registro de eventos.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use rand::{Rng, SeedableRng, prelude::StdRng};
+
+use crate::{
+    args::Args,
+    logger::{Logger, describir},
+    persona::ConfiguracionPersona,
+    simulacion::{Resultado, simular_con_personas},
+};
+
+const DIAS_POR_DEFECTO: u32 = 3;
+const REGRESO_POR_DEFECTO: f64 = 0.5;
+/// Satisfacción a partir de la cual una visita hace mucho más (o, si es
+/// negativa, mucho menos) probable volver.
+const ESCALA_SATISFACCION: f64 = 50.0;
+/// Límites del factor por el que se multiplica el presupuesto de quien
+/// vuelve.
+const FACTOR_PRESUPUESTO_MINIMO: f64 = 0.5;
+const FACTOR_PRESUPUESTO_MAXIMO: f64 = 1.5;
+/// Vueltas desde el último mantenimiento con las que se duplica la
+/// probabilidad de desperfecto de un juego.
+const VUELTAS_DESGASTE: u32 = 100;
+/// Tope de la probabilidad de desperfecto por desgaste.
+const PROBABILIDAD_DESPERFECTO_MAXIMA: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct OpcionesTemporada {
+    pub dias: u32,
+    /// Probabilidad de volver de quien tuvo satisfacción 0 en su última
+    /// visita.
+    pub regreso: f64,
+    /// Vueltas desde el último mantenimiento a partir de las cuales se
+    /// hace mantenimiento a un juego al cerrar. Sin umbral nunca se hace.
+    pub mantenimiento: Option<u32>,
+}
+
+/// Separa las opciones de la temporada (`--dias`, `--regreso` y
+/// `--mantenimiento`) del resto de los argumentos, que describen el
+/// escenario.
+pub fn parse_opciones_temporada(argv: &[String]) -> Result<(OpcionesTemporada, Vec<String>), String> {
+    let mut opciones = OpcionesTemporada {
+        dias: DIAS_POR_DEFECTO,
+        regreso: REGRESO_POR_DEFECTO,
+        mantenimiento: None,
+    };
+    let mut resto = vec![];
+    for arg in argv {
+        if let Some(valor) = arg.strip_prefix("--dias=") {
+            opciones.dias = parse_natural("--dias", valor)?;
+        } else if let Some(valor) = arg.strip_prefix("--regreso=") {
+            opciones.regreso = match valor.parse::<f64>() {
+                Ok(x) if (0.0..=1.0).contains(&x) => x,
+                _ => return Err(format!("[--regreso] '{}' no es una probabilidad entre 0 y 1", valor)),
+            };
+        } else if let Some(valor) = arg.strip_prefix("--mantenimiento=") {
+            opciones.mantenimiento = Some(parse_natural("--mantenimiento", valor)?);
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+    Ok((opciones, resto))
+}
+
+fn parse_natural(param: &str, valor: &str) -> Result<u32, String> {
+    match valor.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("[{}] '{}' no es un número natural", param, valor)),
+    }
+}
+
+/// Factor por el que la satisfacción de una visita multiplica la
+/// probabilidad de volver y el presupuesto: 1 con satisfacción 0, y
+/// entre 0 y 2 a medida que la satisfacción baja o sube.
+pub fn factor_satisfaccion(satisfaccion: f64) -> f64 {
+    2.0 / (1.0 + (-satisfaccion / ESCALA_SATISFACCION).exp())
+}
+
+/// Probabilidad de desperfecto de un juego con probabilidad `base`
+/// cuando está nuevo y `desgaste` vueltas desde su último mantenimiento.
+pub fn probabilidad_desperfecto(base: f64, desgaste: u32) -> f64 {
+    let desgastada = base * (1.0 + desgaste as f64 / VUELTAS_DESGASTE as f64);
+    desgastada.min(PROBABILIDAD_DESPERFECTO_MAXIMA.max(base))
+}
+
+/// Suma al desgaste de cada juego las `vueltas` que dio en el día (por
+/// id del juego) y hace el mantenimiento de los que llegaron al
+/// `umbral`, que vuelven a cero. Devuelve los ids de los mantenidos.
+fn desgastar(desgaste: &mut [u32], vueltas: impl IntoIterator<Item = (usize, u32)>, umbral: Option<u32>) -> Vec<usize> {
+    let mut mantenidos = vec![];
+    for (id, vueltas) in vueltas {
+        desgaste[id] += vueltas;
+        if umbral.is_some_and(|umbral| desgaste[id] >= umbral) {
+            desgaste[id] = 0;
+            mantenidos.push(id);
+        }
+    }
+    mantenidos
+}
+
+/// Semilla de la simulación del día `dia`. El día 0 usa la del
+/// escenario, y los siguientes saltan lejos para que las semillas de los
+/// juegos de un día no coincidan con las de otro.
+fn semilla_del_dia(semilla: u32, dia: u32) -> u32 {
+    semilla.wrapping_add(dia.wrapping_mul(0x9E37_79B9))
+}
+
+/// Un día de la temporada.
+#[derive(Debug)]
+pub struct Dia {
+    pub resultado: Resultado,
+    /// Vueltas de cada juego desde su último mantenimiento al abrir.
+    pub desgaste: Vec<u32>,
+    /// Probabilidad de desperfecto de cada juego durante el día.
+    pub probabilidades: Vec<f64>,
+    /// Juegos a los que se les hizo mantenimiento al cerrar, por id.
+    pub mantenidos: Vec<usize>,
+}
+
+/// Visitas de una persona a lo largo de la temporada.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Visitas {
+    pub dias: Vec<u32>,
+    pub gastado: u32,
+    pub satisfaccion_total: f64,
+}
+
+impl Visitas {
+    pub fn satisfaccion_promedio(&self) -> f64 {
+        self.satisfaccion_total / self.dias.len().max(1) as f64
+    }
+}
+
+/// Resultado de una temporada completa.
+#[derive(Debug)]
+pub struct Temporada {
+    pub dias: Vec<Dia>,
+    /// Personas del escenario, que vienen el primer día.
+    pub visitantes: usize,
+}
+
+impl Temporada {
+    pub fn caja(&self) -> u32 {
+        self.dias.iter().map(|dia| dia.resultado.caja).sum()
+    }
+
+    pub fn desperfectos(&self) -> u32 {
+        self.dias.iter().map(|dia| dia.resultado.desperfectos).sum()
+    }
+
+    /// Visitas de todos los días, contando las de quienes desistieron de
+    /// entrar.
+    pub fn cantidad_visitas(&self) -> usize {
+        self.dias.iter().map(|dia| dia.resultado.personas.len()).sum()
+    }
+
+    /// Satisfacción promedio de todas las visitas.
+    pub fn satisfaccion_promedio(&self) -> f64 {
+        let total: f64 = self.dias.iter()
+            .flat_map(|dia| &dia.resultado.personas)
+            .map(|p| p.satisfaccion.total())
+            .sum();
+        total / self.cantidad_visitas().max(1) as f64
+    }
+
+    /// Visitas de cada persona, por id.
+    pub fn visitas(&self) -> BTreeMap<usize, Visitas> {
+        let mut visitas: BTreeMap<usize, Visitas> = BTreeMap::new();
+        for (numero, dia) in self.dias.iter().enumerate() {
+            for persona in &dia.resultado.personas {
+                let visitas = visitas.entry(persona.id).or_default();
+                visitas.dias.push(numero as u32 + 1);
+                visitas.gastado += persona.gastado;
+                visitas.satisfaccion_total += persona.satisfaccion.total();
+            }
+        }
+        visitas
+    }
+
+    /// Resumen de cada día y de toda la temporada.
+    pub fn informe(&self) -> String {
+        let mut informe = format!("Temporada de {} días con {} visitantes\n", self.dias.len(), self.visitantes);
+        for (numero, dia) in self.dias.iter().enumerate() {
+            let resultado = &dia.resultado;
+            informe += &format!(
+                "Día {}: {} visitantes, caja $ {}, desperfectos {}, espera promedio {:.1} ms, satisfacción promedio {:.1}, duración {} ms\n",
+                numero + 1, resultado.personas.len(), resultado.caja, resultado.desperfectos,
+                resultado.espera_promedio_ms(), resultado.satisfaccion_promedio(), resultado.duracion_ms
+            );
+            for juego in &resultado.juegos {
+                informe += &format!(
+                    "  Juego {}: {} vueltas, {} desperfectos, desgaste al abrir {} vueltas, probabilidad de desperfecto {:.3}{}\n",
+                    describir(juego.id, juego.nombre.as_deref()), juego.vueltas, juego.desperfectos,
+                    dia.desgaste[juego.id], dia.probabilidades[juego.id],
+                    if dia.mantenidos.contains(&juego.id) { ", mantenimiento al cerrar" } else { "" }
+                );
+            }
+        }
+        let visitas = self.visitas();
+        informe += &format!(
+            "Total: {} visitas de {} visitantes, caja $ {}, desperfectos {}, satisfacción promedio {:.1}\n",
+            self.cantidad_visitas(), visitas.len(), self.caja(), self.desperfectos(), self.satisfaccion_promedio()
+        );
+        let nombres: BTreeMap<usize, Option<&str>> = self.dias.iter()
+            .flat_map(|dia| &dia.resultado.personas)
+            .map(|p| (p.id, p.nombre.as_deref()))
+            .collect();
+        for (id, visitas) in &visitas {
+            let dias: Vec<String> = visitas.dias.iter().map(u32::to_string).collect();
+            informe += &format!(
+                "  Persona {}: {} visitas (días {}), gastó $ {}, satisfacción promedio {:.1}\n",
+                describir(*id, nombres[id]), visitas.dias.len(), dias.join(", "),
+                visitas.gastado, visitas.satisfaccion_promedio()
+            );
+        }
+        informe
+    }
+}
+
+/// Elige con `rng` quiénes de `cohorte` vuelven, según la satisfacción de
+/// su última visita, y con qué presupuesto. Los grupos deciden juntos con
+/// la satisfacción promedio de sus integrantes; quien no vino nunca no
+/// vuelve.
+fn visitantes_que_vuelven(cohorte: &[ConfiguracionPersona],
+                          ultima_satisfaccion: &[Option<f64>],
+                          regreso: f64,
+                          rng: &mut StdRng) -> Vec<(usize, ConfiguracionPersona)> {
+    let mut decisiones: BTreeMap<usize, bool> = BTreeMap::new();
+    let mut visitantes = vec![];
+    for (id, configuracion) in cohorte.iter().enumerate() {
+        let satisfaccion = match ultima_satisfaccion[id] {
+            Some(satisfaccion) => satisfaccion,
+            None => continue,
+        };
+        let vuelve = match configuracion.grupo {
+            Some(grupo) => *decisiones.entry(grupo).or_insert_with(|| {
+                let integrantes: Vec<f64> = cohorte.iter()
+                    .zip(ultima_satisfaccion)
+                    .filter(|(otra, _)| otra.grupo == Some(grupo))
+                    .filter_map(|(_, satisfaccion)| *satisfaccion)
+                    .collect();
+                let promedio = integrantes.iter().sum::<f64>() / integrantes.len() as f64;
+                rng.gen_bool((regreso * factor_satisfaccion(promedio)).min(1.0))
+            }),
+            None => rng.gen_bool((regreso * factor_satisfaccion(satisfaccion)).min(1.0)),
+        };
+        if vuelve {
+            let factor = factor_satisfaccion(satisfaccion).clamp(FACTOR_PRESUPUESTO_MINIMO, FACTOR_PRESUPUESTO_MAXIMO);
+            let mut configuracion = configuracion.clone();
+            configuracion.presupuesto = (configuracion.presupuesto as f64 * factor).round() as u32;
+            visitantes.push((id, configuracion));
+        }
+    }
+    visitantes
+}
+
+/// Simula los días de la temporada sobre el escenario `args`, que tiene
+/// que estar resuelto.
+pub fn simular_temporada(opciones: &OpcionesTemporada, args: &Args) -> Result<Temporada, String> {
+    let cohorte = args.personas();
+    let bases: Vec<f64> = args.juegos()?.iter().map(|juego| juego.probabilidad_desperfecto).collect();
+    let mut rng = StdRng::seed_from_u64((args.semilla as u64).rotate_left(24));
+    let mut desgaste = vec![0; bases.len()];
+    let mut ultima_satisfaccion: Vec<Option<f64>> = vec![None; cohorte.len()];
+    let mut dias = vec![];
+    for numero in 0..opciones.dias {
+        let personas = if numero == 0 {
+            cohorte.iter().cloned().enumerate().collect()
+        } else {
+            visitantes_que_vuelven(&cohorte, &ultima_satisfaccion, opciones.regreso, &mut rng)
+        };
+        let probabilidades: Vec<f64> = bases.iter()
+            .zip(&desgaste)
+            .map(|(base, desgaste)| probabilidad_desperfecto(*base, *desgaste))
+            .collect();
+        let mut args_dia = args.clone();
+        args_dia.rapido = true;
+        args_dia.semilla = semilla_del_dia(args.semilla, numero);
+        args_dia.probabilidad_desperfecto_juegos = Some(probabilidades.clone());
+        let resultado = simular_con_personas(&args_dia, personas, Arc::new(Logger::new_to_null()))
+            .map_err(|e| format!("día {}: {}", numero + 1, e))?;
+
+        for persona in &resultado.personas {
+            ultima_satisfaccion[persona.id] = Some(persona.satisfaccion.total());
+        }
+        let desgaste_al_abrir = desgaste.clone();
+        let vueltas = resultado.juegos.iter().map(|juego| (juego.id, juego.vueltas));
+        let mantenidos = desgastar(&mut desgaste, vueltas, opciones.mantenimiento);
+        dias.push(Dia { resultado, desgaste: desgaste_al_abrir, probabilidades, mantenidos });
+    }
+    Ok(Temporada { dias, visitantes: cohorte.len() })
+}
+
+/// Corre la temporada descripta por `opciones` sobre el escenario `args`
+/// e imprime el informe.
+pub fn correr(opciones: OpcionesTemporada, args: &Args) -> Result<(), String> {
+    let temporada = simular_temporada(&opciones, args)?;
+    print!("{}", temporada.informe());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulacion::simular;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    fn escenario() -> Args {
+        let mut args = Args::default();
+        args.presupuesto_personas = vec![40; 12];
+        args.grupos = Some(vec![3]);
+        args.capacidad_juegos = Some(vec![4, 4, 4]);
+        args.probabilidad_desperfecto_juegos = Some(vec![0.05; 3]);
+        args.semilla = 7;
+        args.rapido = true;
+        args.resolver().unwrap();
+        args
+    }
+
+    #[test]
+    fn separa_las_opciones_de_la_temporada_del_escenario() {
+        let (opciones, resto) = parse_opciones_temporada(&argv(&["--dias=5", "--personas=5:20", "--regreso=0.8", "--mantenimiento=40"])).unwrap();
+        assert_eq!(opciones.dias, 5);
+        assert_eq!(opciones.regreso, 0.8);
+        assert_eq!(opciones.mantenimiento, Some(40));
+        assert_eq!(resto, argv(&["--personas=5:20"]));
+
+        let (opciones, _) = parse_opciones_temporada(&[]).unwrap();
+        assert_eq!(opciones.dias, DIAS_POR_DEFECTO);
+        assert_eq!(opciones.mantenimiento, None);
+        assert!(parse_opciones_temporada(&argv(&["--dias=0"])).is_err());
+        assert!(parse_opciones_temporada(&argv(&["--regreso=1.5"])).is_err());
+    }
+
+    #[test]
+    fn el_desgaste_y_la_satisfaccion_cambian_las_probabilidades() {
+        assert_eq!(probabilidad_desperfecto(0.1, 0), 0.1);
+        assert!((probabilidad_desperfecto(0.1, VUELTAS_DESGASTE) - 0.2).abs() < 1e-9);
+        assert_eq!(probabilidad_desperfecto(0.1, 100 * VUELTAS_DESGASTE), PROBABILIDAD_DESPERFECTO_MAXIMA);
+        assert_eq!(probabilidad_desperfecto(0.0, 1000), 0.0);
+
+        assert_eq!(factor_satisfaccion(0.0), 1.0);
+        assert!(factor_satisfaccion(-100.0) < factor_satisfaccion(-10.0));
+        assert!(factor_satisfaccion(10.0) < factor_satisfaccion(100.0));
+        assert!(factor_satisfaccion(1000.0) <= 2.0 && factor_satisfaccion(-1000.0) >= 0.0);
+    }
+
+    #[test]
+    fn el_mantenimiento_vuelve_a_cero_el_desgaste() {
+        let mut desgaste = vec![0, 5, 8];
+        assert_eq!(desgastar(&mut desgaste, vec![(0, 4), (1, 4), (2, 1)], Some(9)), vec![1, 2]);
+        assert_eq!(desgaste, vec![4, 0, 0]);
+        assert_eq!(desgastar(&mut desgaste, vec![(0, 4), (1, 4), (2, 1)], Some(9)), Vec::<usize>::new());
+        assert_eq!(desgaste, vec![8, 4, 1]);
+        // sin umbral nunca se hace mantenimiento
+        assert!(desgastar(&mut desgaste, vec![(0, 1000)], None).is_empty());
+        assert_eq!(desgaste, vec![1008, 4, 1]);
+    }
+
+    #[test]
+    fn el_primer_dia_es_la_simulacion_del_escenario() {
+        let args = escenario();
+        let opciones = OpcionesTemporada { dias: 1, regreso: 0.5, mantenimiento: None };
+        let temporada = simular_temporada(&opciones, &args).unwrap();
+        let individual = simular(&args, Arc::new(Logger::new_to_null())).unwrap();
+        let gastos = |r: &Resultado| r.personas.iter().map(|p| (p.id, p.gastado, p.juegos_jugados)).collect::<Vec<_>>();
+        assert_eq!(gastos(&temporada.dias[0].resultado), gastos(&individual));
+    }
+
+    #[test]
+    fn vuelven_visitantes_y_los_juegos_se_desgastan() {
+        let args = escenario();
+        let opciones = OpcionesTemporada { dias: 4, regreso: 1.0, mantenimiento: Some(10) };
+        let temporada = simular_temporada(&opciones, &args).unwrap();
+        assert_eq!(temporada.dias.len(), 4);
+        assert_eq!(temporada.dias[0].resultado.personas.len(), 12);
+        assert!(temporada.dias[0].desgaste.iter().all(|d| *d == 0));
+
+        let cohorte = args.personas();
+        let mut ultima: BTreeMap<usize, f64> = BTreeMap::new();
+        for (anterior, dia) in temporada.dias.iter().zip(&temporada.dias[1..]) {
+            // el desgaste se acumula salvo que se haga mantenimiento
+            for juego in &anterior.resultado.juegos {
+                let esperado = if anterior.mantenidos.contains(&juego.id) {
+                    0
+                } else {
+                    anterior.desgaste[juego.id] + juego.vueltas
+                };
+                assert_eq!(dia.desgaste[juego.id], esperado);
+                assert!(anterior.mantenidos.contains(&juego.id) == (anterior.desgaste[juego.id] + juego.vueltas >= 10));
+            }
+            // vuelven los grupos completos, con presupuesto según la
+            // satisfacción de su última visita
+            ultima.extend(anterior.resultado.personas.iter().map(|p| (p.id, p.satisfaccion.total())));
+            for persona in &dia.resultado.personas {
+                let factor = factor_satisfaccion(ultima[&persona.id]).clamp(0.5, 1.5);
+                assert_eq!(persona.presupuesto_inicial, (cohorte[persona.id].presupuesto as f64 * factor).round() as u32);
+                assert_eq!(persona.atributos, cohorte[persona.id].atributos);
+            }
+            let grupo: Vec<usize> = dia.resultado.personas.iter().filter(|p| p.grupo == Some(0)).map(|p| p.id).collect();
+            assert!(grupo.is_empty() || grupo == vec![0, 1, 2]);
+        }
+
+        let visitas = temporada.visitas();
+        assert_eq!(visitas.values().map(|v| v.dias.len()).sum::<usize>(), temporada.cantidad_visitas());
+        assert_eq!(visitas.values().map(|v| v.gastado).sum::<u32>(), temporada.caja());
+        let informe = temporada.informe();
+        assert!(informe.starts_with("Temporada de 4 días con 12 visitantes\n"), "{}", informe);
+        assert!(informe.contains("Día 4: "), "{}", informe);
+        let mantenimientos: usize = temporada.dias.iter().map(|dia| dia.mantenidos.len()).sum();
+        assert_eq!(informe.matches(", mantenimiento al cerrar").count(), mantenimientos, "{}", informe);
+        assert!(informe.contains("  Persona 0: "), "{}", informe);
+    }
+}
@@ -0,0 +1,281 @@
+//! Modelo analítico de las filas de los juegos, para comparar contra la
+//! simulación.
+//!
+//! Cada juego es una cola con servicio en lotes: arranca la vuelta
+//! cuando se llena (`c` personas) o cuando se agota el tiempo de espera
+//! `T` con alguien arriba, y la vuelta dura `d`. Con una tasa de llegada
+//! `λ` a la fila:
+//!
+//! - personas por vuelta: `b = min(c, 1 + λ·T)` (la primera persona
+//!   espera a lo sumo `T` a que lleguen las demás),
+//! - duración del ciclo: `s = d + b·(u + v) + p/(1-p)·A/2`, con `u` y
+//!   `v` los tiempos de carga y descarga por persona, `p` la
+//!   probabilidad de desperfecto y `A` el tiempo máximo de arreglo,
+//! - utilización de cada una de las `N` unidades del juego (fracción del
+//!   tiempo en vuelta): `ρ = (λ/b)·s/N`,
+//! - espera en la fila hasta conseguir lugar, tratando cada vuelta como
+//!   un cliente con servicio determinístico. Con una unidad es la
+//!   fórmula de Pollaczek-Khinchine, `W = ρ·s / (2·(1-ρ))`, y con varias
+//!   se usa la aproximación de Sakasegawa,
+//!   `W = ρ^(√(2(N+1))-1)·s / (2·N·(1-ρ))`. Diverge cuando `ρ ≥ 1`.
+//!
+//! Como las personas eligen los juegos al azar (el sistema es cerrado),
+//! `λ` no es un dato de entrada: se estima de la simulación como las
+//! personas transportadas por milisegundo simulado. Esa estimación nunca
+//! supera lo que el juego llegó a transportar, así que en el informe `ρ`
+//! queda prácticamente siempre por debajo de 1 y la fila no se marca
+//! como inestable aunque en la simulación haya crecido sin control: en
+//! ese caso lo que se ve es una espera observada mucho mayor que la
+//! predicha.
+//!
+//! El modelo supone llegadas independientes, mientras que en el parque
+//! las personas que bajan juntas de un juego llegan casi juntas al
+//! siguiente, así que es esperable que la simulación muestre algo más
+//! de espera. En modo rápido además cada microsegundo de demora del
+//! sistema operativo cuenta como un milisegundo simulado, por lo que
+//! para comparar esperas conviene correr con `--tiempo-real`.
+
+use crate::{
+    args::Args,
+    juego::{ConfiguracionJuego, TIEMPO_ESPERA_PERSONAS},
+    logger::describir,
+    lote::{OpcionesLote, correr_corridas, parse_opciones},
+    simulacion::Resultado,
+};
+
+/// Diferencia relativa a partir de la cual se informa una divergencia
+/// si no se especifica `--tolerancia`.
+const TOLERANCIA_POR_DEFECTO: f64 = 0.5;
+/// Diferencias absolutas menores a estas no se consideran divergencias
+/// (para utilización y ocupación, y para la espera en milisegundos).
+const DIFERENCIA_MINIMA_FRACCION: f64 = 0.05;
+const DIFERENCIA_MINIMA_ESPERA_MS: f64 = 5.0;
+
+#[derive(Debug, Clone)]
+pub struct OpcionesTeoria {
+    /// Diferencia relativa entre teoría y simulación a partir de la cual
+    /// se marca una divergencia.
+    pub tolerancia: f64,
+    /// Correr las simulaciones en tiempo real en lugar de en modo
+    /// rápido.
+    pub tiempo_real: bool,
+    pub lote: OpcionesLote,
+}
+
+/// Separa las opciones de `teoria` (`--tolerancia`, `--tiempo-real` y
+/// las del lote) del resto de los argumentos, que describen el
+/// escenario.
+pub fn parse_opciones_teoria(argv: &[String]) -> Result<(OpcionesTeoria, Vec<String>), String> {
+    let mut tolerancia = TOLERANCIA_POR_DEFECTO;
+    let mut tiempo_real = false;
+    let mut resto = vec![];
+    for arg in argv {
+        if arg == "--tiempo-real" {
+            tiempo_real = true;
+        } else if let Some(valor) = arg.strip_prefix("--tolerancia=") {
+            tolerancia = match valor.parse::<f64>() {
+                Ok(t) if t > 0.0 => t,
+                _ => return Err(format!("[--tolerancia] '{}' no es un número positivo", valor)),
+            };
+        } else {
+            resto.push(arg.clone());
+        }
+    }
+    let (lote, resto) = parse_opciones(&resto)?;
+    Ok((OpcionesTeoria { tolerancia, tiempo_real, lote }, resto))
+}
+
+/// Medidas de un juego, predichas por el modelo u observadas en la
+/// simulación. Los tiempos están en milisegundos simulados.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Medidas {
+    /// Fracción del tiempo que el juego pasa dando vueltas.
+    pub utilizacion: f64,
+    pub personas_por_vuelta: f64,
+    /// Espera promedio en la fila hasta conseguir lugar. `None` si la
+    /// fila es inestable.
+    pub espera_ms: Option<f64>,
+}
+
+/// Predicción del modelo para `juego` con `llegadas` personas por
+/// milisegundo. La utilización es la de cada unidad.
+pub fn predecir(juego: &ConfiguracionJuego, llegadas: f64) -> Medidas {
+    let capacidad = juego.capacidad as f64;
+    let personas_por_vuelta = capacidad.min(1.0 + llegadas * TIEMPO_ESPERA_PERSONAS as f64);
+    let p = juego.probabilidad_desperfecto.min(0.99);
+    let ciclo = juego.duracion_ms as f64
+        + personas_por_vuelta * (juego.carga_ms + juego.descarga_ms) as f64
+        + p / (1.0 - p) * juego.tiempo_maximo_arreglo_ms as f64 / 2.0;
+    let unidades = juego.unidades.max(1) as f64;
+    let vueltas_por_ms = llegadas / personas_por_vuelta / unidades;
+    let rho = vueltas_por_ms * ciclo;
+    let espera = |rho: f64| rho.powf((2.0 * (unidades + 1.0)).sqrt() - 1.0) * ciclo / (2.0 * unidades * (1.0 - rho));
+    Medidas {
+        utilizacion: vueltas_por_ms * juego.duracion_ms as f64,
+        personas_por_vuelta,
+        espera_ms: if rho < 1.0 { Some(espera(rho)) } else { None },
+    }
+}
+
+/// Medidas del juego `id` observadas en todas las corridas, y la tasa
+/// de llegada estimada (personas por milisegundo).
+pub fn observar(resultados: &[Resultado], id: usize, juego: &ConfiguracionJuego) -> (Medidas, f64) {
+    let duracion: u64 = resultados.iter().map(|r| r.duracion_ms.max(1)).sum();
+    let (mut vueltas, mut personas, mut espera) = (0u64, 0u64, 0u64);
+    for estadisticas in resultados.iter().filter_map(|r| r.juegos.get(id)) {
+        vueltas += estadisticas.vueltas as u64;
+        personas += estadisticas.personas_transportadas as u64;
+        espera += estadisticas.espera_total_ms;
+    }
+    let medidas = Medidas {
+        utilizacion: (vueltas * juego.duracion_ms as u64) as f64 / (duracion * juego.unidades.max(1) as u64) as f64,
+        personas_por_vuelta: if vueltas == 0 { 0.0 } else { personas as f64 / vueltas as f64 },
+        espera_ms: Some(if personas == 0 { 0.0 } else { espera as f64 / personas as f64 }),
+    };
+    (medidas, personas as f64 / duracion as f64)
+}
+
+/// Indica si `observado` difiere de `predicho` en más de `tolerancia`
+/// (relativa al mayor de los dos) y en más de `minimo` (absoluta).
+fn diverge(predicho: f64, observado: f64, tolerancia: f64, minimo: f64) -> bool {
+    let diferencia = (observado - predicho).abs();
+    diferencia > minimo && diferencia > tolerancia * predicho.abs().max(observado.abs())
+}
+
+/// Tabla comparativa de teoría y simulación para cada juego, con las
+/// divergencias marcadas.
+pub fn informe(juegos: &[ConfiguracionJuego], resultados: &[Resultado], tolerancia: f64) -> String {
+    let mut informe = format!("Teoría vs. simulación ({} corridas)\n", resultados.len());
+    let mut divergencias = 0;
+    for (id, juego) in juegos.iter().enumerate() {
+        let (observado, llegadas) = observar(resultados, id, juego);
+        let predicho = predecir(juego, llegadas);
+        informe += &format!(
+            "\nJuego {}: capacidad {}, vuelta {} ms, {} unidades, llegadas {:.2} personas/s\n",
+            describir(id, juego.nombre.as_deref()), juego.capacidad, juego.duracion_ms, juego.unidades, llegadas * 1000.0
+        );
+        informe += &format!("  {:<20} {:>12} {:>12}\n", "", "teoría", "simulación");
+
+        let mut fila = |nombre: &str, predicho: Option<f64>, observado: f64, minimo: f64| {
+            let marca = match predicho {
+                Some(predicho) if diverge(predicho, observado, tolerancia, minimo) => " <- DIVERGE",
+                Some(_) => "",
+                None => " <- fila inestable según el modelo",
+            };
+            if !marca.is_empty() {
+                divergencias += 1;
+            }
+            let predicho = predicho.map_or("∞".to_string(), |p| format!("{:.2}", p));
+            format!("  {:<20} {:>12} {:>12.2}{}\n", nombre, predicho, observado, marca)
+        };
+        informe += &fila("utilización", Some(predicho.utilizacion), observado.utilizacion, DIFERENCIA_MINIMA_FRACCION);
+        informe += &fila(
+            "personas por vuelta", Some(predicho.personas_por_vuelta), observado.personas_por_vuelta,
+            DIFERENCIA_MINIMA_FRACCION * juego.capacidad as f64
+        );
+        informe += &fila("espera (ms)", predicho.espera_ms, observado.espera_ms.unwrap_or_default(), DIFERENCIA_MINIMA_ESPERA_MS);
+    }
+    informe += &format!(
+        "\n{} divergencias con tolerancia {:.0}%\n",
+        divergencias, tolerancia * 100.0
+    );
+    informe
+}
+
+/// Corre el lote sobre el escenario `args` e imprime la comparación.
+pub fn correr(opciones: OpcionesTeoria, args: &Args) -> Result<(), String> {
+    let mut lote = opciones.lote.clone();
+    lote.resolver(args.semilla);
+    let mut args = args.clone();
+    args.rapido = !opciones.tiempo_real;
+    let resultados = correr_corridas(&args, lote.semillas(), lote.hilos)?;
+    print!("{}", informe(&args.juegos()?, &resultados, opciones.tolerancia));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn juego(capacidad: u32, duracion_ms: u32) -> ConfiguracionJuego {
+        ConfiguracionJuego { capacidad, duracion_ms, probabilidad_desperfecto: 0.0, tiempo_maximo_arreglo_ms: 0, ..Default::default() }
+    }
+
+    #[test]
+    fn con_pocas_llegadas_las_vueltas_salen_incompletas() {
+        // una llegada cada 10 segundos: la primera persona de cada vuelta
+        // espera a lo sumo 5 segundos, en los que llega en promedio media
+        // persona más
+        let prediccion = predecir(&juego(4, 100), 0.0001);
+        assert!((prediccion.personas_por_vuelta - 1.5).abs() < 1e-9);
+        assert!(prediccion.espera_ms.unwrap() < 1.0);
+    }
+
+    #[test]
+    fn con_muchas_llegadas_las_vueltas_salen_llenas() {
+        // 1 persona cada 50 ms, vueltas de 4 personas cada 100 ms: ρ = 0.5
+        let prediccion = predecir(&juego(4, 100), 0.02);
+        assert_eq!(prediccion.personas_por_vuelta, 4.0);
+        assert!((prediccion.utilizacion - 0.5).abs() < 1e-9);
+        assert!((prediccion.espera_ms.unwrap() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mas_unidades_reducen_la_espera() {
+        let mut dos_unidades = juego(4, 100);
+        dos_unidades.unidades = 2;
+        let una = predecir(&juego(4, 100), 0.02);
+        let dos = predecir(&dos_unidades, 0.02);
+        assert!((dos.utilizacion - una.utilizacion / 2.0).abs() < 1e-9);
+        assert!(dos.espera_ms.unwrap() < una.espera_ms.unwrap());
+        // inestable con una unidad, estable con dos
+        assert_eq!(predecir(&juego(2, 100), 0.03).espera_ms, None);
+        dos_unidades.capacidad = 2;
+        assert!(predecir(&dos_unidades, 0.03).espera_ms.is_some());
+    }
+
+    #[test]
+    fn la_carga_alarga_el_ciclo() {
+        let mut con_carga = juego(4, 100);
+        con_carga.carga_ms = 10;
+        con_carga.descarga_ms = 5;
+        let sin = predecir(&juego(4, 100), 0.02);
+        let con = predecir(&con_carga, 0.02);
+        assert_eq!(con.utilizacion, sin.utilizacion);
+        assert!(con.espera_ms.unwrap() > sin.espera_ms.unwrap());
+    }
+
+    #[test]
+    fn fila_inestable() {
+        assert_eq!(predecir(&juego(2, 100), 0.05).espera_ms, None);
+    }
+
+    #[test]
+    fn los_desperfectos_alargan_el_ciclo() {
+        let mut con_desperfectos = juego(4, 100);
+        con_desperfectos.probabilidad_desperfecto = 0.5;
+        con_desperfectos.tiempo_maximo_arreglo_ms = 100;
+        let sin = predecir(&juego(4, 100), 0.02).espera_ms.unwrap();
+        let con = predecir(&con_desperfectos, 0.02).espera_ms.unwrap();
+        assert!(con > sin);
+    }
+
+    #[test]
+    fn opciones() {
+        let argv: Vec<String> = ["--tiempo-real", "--tolerancia=0.2", "--corridas=3", "--capacidad=4"]
+            .iter().map(|a| a.to_string()).collect();
+        let (opciones, resto) = parse_opciones_teoria(&argv).unwrap();
+        assert!(opciones.tiempo_real);
+        assert_eq!(opciones.tolerancia, 0.2);
+        assert_eq!(opciones.lote.corridas, 3);
+        assert_eq!(resto, vec!["--capacidad=4".to_string()]);
+    }
+
+    #[test]
+    fn divergencias() {
+        assert!(!diverge(10.0, 12.0, 0.5, 1.0));
+        assert!(diverge(10.0, 40.0, 0.5, 1.0));
+        // diferencia relativa grande pero absoluta despreciable
+        assert!(!diverge(0.1, 0.5, 0.5, 1.0));
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering}
+    },
+    time::{Duration, Instant}
+};
+
+/// Estado reportado por un hilo de la simulación.
+struct Estado {
+    descripcion: String,
+    desde: Instant,
+    terminado: bool,
+}
+
+/// Registro del estado de cada juego y cada persona del parque.
+///
+/// Los hilos reportan qué están haciendo (y qué recurso retienen o
+/// esperan) y avisan cada vez que la simulación avanza (una vuelta
+/// terminada, una persona que se mueve). Con esa información se
+/// detectan situaciones sin progreso y personas que no avanzan.
+pub struct Watchdog {
+    progreso: AtomicU64,
+    juegos: Mutex<BTreeMap<String, Estado>>,
+    personas: Mutex<BTreeMap<String, Estado>>,
+    ultimo_progreso: Mutex<(u64, Instant)>,
+    inanicion_reportada: Mutex<BTreeSet<String>>,
+}
+
+/// Resultado de una verificación del watchdog.
+pub enum Diagnostico {
+    Ok,
+    /// Personas que llevan demasiado tiempo en el mismo estado mientras
+    /// el resto de la simulación avanza.
+    Inanicion(Vec<String>),
+    /// No hubo progreso en el tiempo límite. Contiene el volcado del
+    /// estado de cada juego y persona.
+    SinProgreso(String),
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            progreso: AtomicU64::new(0),
+            juegos: Mutex::new(BTreeMap::new()),
+            personas: Mutex::new(BTreeMap::new()),
+            ultimo_progreso: Mutex::new((0, Instant::now())),
+            inanicion_reportada: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Registra que la simulación avanzó.
+    pub fn registrar_progreso(&self) {
+        self.progreso.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Actualiza el estado de un juego. No cuenta como progreso.
+    pub fn estado_juego(&self, tag: &str, descripcion: String, terminado: bool) {
+        Self::actualizar(&self.juegos, tag, descripcion, terminado);
+    }
+
+    /// Actualiza el estado de una persona. Cada movimiento de una
+    /// persona cuenta como progreso.
+    pub fn estado_persona(&self, tag: &str, descripcion: String, terminado: bool) {
+        Self::actualizar(&self.personas, tag, descripcion, terminado);
+        self.registrar_progreso();
+    }
+
+    fn actualizar(mapa: &Mutex<BTreeMap<String, Estado>>, tag: &str, descripcion: String, terminado: bool) {
+        let mut mapa = mapa.lock().expect("poisoned");
+        mapa.insert(tag.into(), Estado { descripcion, desde: Instant::now(), terminado });
+    }
+
+    /// Revisa si hubo progreso en los últimos `limite` y si hay personas
+    /// que no se movieron en ese tiempo.
+    pub fn verificar(&self, limite: Duration) -> Diagnostico {
+        let progreso = self.progreso.load(Ordering::SeqCst);
+        let mut ultimo = self.ultimo_progreso.lock().expect("poisoned");
+        if progreso != ultimo.0 {
+            *ultimo = (progreso, Instant::now());
+        } else if ultimo.1.elapsed() >= limite {
+            return Diagnostico::SinProgreso(self.volcar_estado());
+        }
+        drop(ultimo);
+
+        let mut reportadas = self.inanicion_reportada.lock().expect("poisoned");
+        let mut nuevas = vec![];
+        for (tag, estado) in self.personas.lock().expect("poisoned").iter() {
+            if estado.terminado || estado.desde.elapsed() < limite {
+                continue;
+            }
+            let clave = format!("{}: {}", tag, estado.descripcion);
+            if reportadas.insert(clave.clone()) {
+                nuevas.push(format!("{} (hace {} ms)", clave, estado.desde.elapsed().as_millis()));
+            }
+        }
+
+        if nuevas.is_empty() {
+            Diagnostico::Ok
+        } else {
+            Diagnostico::Inanicion(nuevas)
+        }
+    }
+
+    /// Devuelve una descripción del estado de cada juego y cada persona,
+    /// una línea por hilo.
+    pub fn volcar_estado(&self) -> String {
+        let mut resultado = String::new();
+        for mapa in &[&self.juegos, &self.personas] {
+            for (tag, estado) in mapa.lock().expect("poisoned").iter() {
+                resultado += &format!(
+                    "  {:>12}: {} (hace {} ms)\n",
+                    tag, estado.descripcion, estado.desde.elapsed().as_millis()
+                );
+            }
+        }
+        resultado
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sin_progreso_devuelve_el_estado_de_todos() {
+        let watchdog = Watchdog::new();
+        watchdog.estado_juego("JUEGO 0", "esperando personas".into(), false);
+        watchdog.estado_persona("PERSONA 0", "en la fila del juego 0".into(), false);
+        assert!(matches!(watchdog.verificar(Duration::from_secs(60)), Diagnostico::Ok));
+        std::thread::sleep(Duration::from_millis(5));
+        match watchdog.verificar(Duration::from_millis(1)) {
+            Diagnostico::SinProgreso(volcado) => {
+                assert!(volcado.contains("JUEGO 0: esperando personas"));
+                assert!(volcado.contains("PERSONA 0: en la fila del juego 0"));
+            }
+            _ => panic!("se esperaba SinProgreso")
+        }
+    }
+
+    #[test]
+    fn persona_quieta_se_reporta_una_sola_vez() {
+        let watchdog = Watchdog::new();
+        watchdog.estado_persona("PERSONA 0", "en la fila del juego 0".into(), false);
+        watchdog.estado_persona("PERSONA 1", "fuera del parque".into(), true);
+        std::thread::sleep(Duration::from_millis(5));
+        watchdog.registrar_progreso();
+        match watchdog.verificar(Duration::from_millis(1)) {
+            Diagnostico::Inanicion(personas) => {
+                assert_eq!(personas.len(), 1);
+                assert!(personas[0].starts_with("PERSONA 0"));
+            }
+            _ => panic!("se esperaba Inanicion")
+        }
+        watchdog.registrar_progreso();
+        assert!(matches!(watchdog.verificar(Duration::from_millis(1)), Diagnostico::Ok));
+    }
+}